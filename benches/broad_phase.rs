@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{thread_rng, Rng};
+
+#[path = "../src/broad_phase.rs"]
+mod broad_phase;
+
+use broad_phase::sweep_and_prune;
+
+// Ships of the same size over the same sea, so larger fleets are also denser
+fn random_boundaries(count: usize) -> Vec<(f32, f32, f32, f32)> {
+    let mut rng = thread_rng();
+    (0..count)
+        .map(|_| {
+            let x = rng.gen_range(0.0..400.0);
+            let y = rng.gen_range(0.0..400.0);
+            (x - 4.0, x + 4.0, y + 4.0, y - 4.0)
+        })
+        .collect()
+}
+
+// What the collision system did before sweep and prune, comparing every pair
+fn brute_force_pairs(boundaries: &[(f32, f32, f32, f32)]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, (left, right, top, bottom)) in boundaries.iter().enumerate() {
+        for (j, (other_left, other_right, other_top, other_bottom)) in
+            boundaries.iter().enumerate().skip(i + 1)
+        {
+            if other_left <= right
+                && other_right >= left
+                && other_bottom <= top
+                && other_top >= bottom
+            {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+fn broad_phase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("broad_phase");
+    for ships in [100, 250, 500, 1000].iter() {
+        let boundaries = random_boundaries(*ships);
+        group.bench_with_input(
+            BenchmarkId::new("sweep_and_prune", ships),
+            &boundaries,
+            |b, boundaries| b.iter(|| sweep_and_prune(boundaries)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", ships),
+            &boundaries,
+            |b, boundaries| b.iter(|| brute_force_pairs(boundaries)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, broad_phase);
+criterion_main!(benches);
@@ -4,10 +4,14 @@ use std::{
 };
 
 use crate::{
+    calendar::{Calendar, Recurrence},
     components::{
-        Action, Affiliation, Ai, AiState, BoundingBox, Cargo, Contract, Controllable, Expiration,
-        ItemType, OwnedBy, Patrol, Pirate, Port, Ship, StateQuery,
+        Action, Affiliation, Ai, AiState, Bank, BoundingBox, Cargo, CollisionLayer, CollisionShape,
+        Contract, Controllable, Crew, Expiration, Helm, Hull, ItemType, Market, OwnedBy, Patrol,
+        Pirate, Port, Ship, ShipForSale, Shipyard, StateQuery, Tavern, Warehouse,
     },
+    event::CalendarEvent,
+    locale::{load_settings, Locale},
     map::Map,
 };
 use amethyst::{
@@ -15,9 +19,9 @@ use amethyst::{
     core::{
         math::{Point2, Point3, Vector3},
         transform::Transform,
-        WithNamed,
+        Named, WithNamed,
     },
-    ecs::Join,
+    ecs::{Entity, Join},
     prelude::*,
     renderer::{
         palette::LinSrgba,
@@ -31,15 +35,21 @@ use amethyst::{
 };
 use chrono::{Duration, TimeZone, Utc};
 use rand::{seq::SliceRandom, thread_rng, Rng};
-use std::iter;
+use std::{iter, path::PathBuf};
 
 pub const WORLD_WIDTH: f32 = 400.0;
 pub const WORLD_HEIGHT: f32 = 300.0;
+// How far past the islands the camera is allowed to look
+pub const CAMERA_MARGIN: f32 = 100.0;
 pub const DISTANCE_THRESHOLD: f32 = 0.15;
+pub const SECONDS_IN_DAY: f64 = 86400.0;
 
-pub type Notifications = VecDeque<String>;
+pub type Notifications = VecDeque<Notification>;
 
-pub struct MainState;
+pub struct MainState {
+    // Where the config and assets directories are, not necessarily the working directory
+    pub app_root: PathBuf,
+}
 
 impl SimpleState for MainState {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
@@ -72,12 +82,18 @@ impl SimpleState for MainState {
         });
 
         world.insert(Date::default());
+        world.insert(Locale::load(
+            &self.app_root,
+            &load_settings(&self.app_root).language,
+        ));
 
         initialise_map(world);
         initialise_ports(world);
+        initialise_calendar(world);
         initialise_contracts(world);
         initialise_player(world);
         initialise_pirates(world);
+        initialise_merchants(world);
         initialise_camera(world);
     }
 }
@@ -104,12 +120,24 @@ fn initialise_player(world: &mut World) -> () {
         })
         .with(Controllable)
         .with(Cargo::default())
+        .with(Hull::default())
+        .with(Crew {
+            sailors: 20,
+            berths: 30,
+            provisions: 600.0,
+            ..Crew::default()
+        })
         .with(sprite_render.clone())
         .with(transform)
         .with(BoundingBox {
             width: 8.0,
             origin: Point2::new(0.0, 0.0),
         })
+        .with(CollisionShape::Capsule {
+            length: 4.0,
+            radius: 2.0,
+        })
+        .with(Helm::default())
         .build();
 }
 
@@ -168,6 +196,39 @@ fn initialise_pirates(world: &mut World) {
             width: 8.0,
             origin: Point2::new(0.0, 0.0),
         })
+        .with(CollisionShape::Capsule {
+            length: 4.0,
+            radius: 2.0,
+        })
+        .with(Helm::default())
+        .build();
+}
+
+fn initialise_merchants(world: &mut World) {
+    let sprite_render = SpriteRender::new(load_sprite_sheet(world), 1);
+
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(270.0, 110.0, 0.0);
+
+    world
+        .create_entity()
+        .with(Ship { base_speed: 8.0 })
+        .named("Mary Rose")
+        .with(Affiliation {
+            name: "Merchants".to_string(),
+        })
+        .with(Cargo::default())
+        .with(sprite_render)
+        .with(transform)
+        .with(BoundingBox {
+            width: 8.0,
+            origin: Point2::new(0.0, 0.0),
+        })
+        .with(CollisionShape::Capsule {
+            length: 4.0,
+            radius: 2.0,
+        })
+        .with(Helm::default())
         .build();
 }
 
@@ -179,48 +240,176 @@ fn initialise_ports(world: &mut World) {
 
     world
         .create_entity()
-        .with(Port)
+        .with(Port { berths: 2 })
         .named("Portsmouth")
         .with(Cargo::default())
+        .with(Shipyard {
+            ships_for_sale: vec![ShipForSale {
+                name: "Swift".to_string(),
+                base_speed: 12.0,
+                berths: 20,
+                price: 800,
+            }],
+            repair_price: 4,
+        })
+        .with(Bank {
+            limit: 1500,
+            monthly_rate: 0.05,
+        })
+        .with(Warehouse::default())
+        .with(Tavern {
+            sailors: 12,
+            signing_fee: 5,
+        })
+        .with(Market {
+            size: 2,
+            demand: [(ItemType::Rum, 1.5)].iter().cloned().collect(),
+        })
         .with(sprite_render.clone())
         .with(transform)
         .with(BoundingBox {
             width: 10.0,
             origin: Point2::new(0.0, 0.0),
         })
+        .with(CollisionLayer::Port)
+        .with(CollisionShape::Circle { radius: 5.0 })
         .build();
 
     let mut transform = Transform::default();
     transform.set_translation_xyz(275.0, 110.0, 0.0);
     world
         .create_entity()
-        .with(Port)
+        .with(Port { berths: 3 })
         .named("London")
         .with(Cargo::default())
+        .with(Shipyard {
+            ships_for_sale: vec![
+                ShipForSale {
+                    name: "Endeavour".to_string(),
+                    base_speed: 10.0,
+                    berths: 40,
+                    price: 1200,
+                },
+                ShipForSale {
+                    name: "Resolution".to_string(),
+                    base_speed: 11.0,
+                    berths: 30,
+                    price: 1000,
+                },
+            ],
+            repair_price: 3,
+        })
+        .with(Bank {
+            limit: 3000,
+            monthly_rate: 0.04,
+        })
+        .with(Warehouse::default())
+        .with(Tavern {
+            sailors: 20,
+            signing_fee: 6,
+        })
+        .with(Market {
+            size: 3,
+            demand: [(ItemType::Whiskey, 1.5), (ItemType::Sugar, 1.2)]
+                .iter()
+                .cloned()
+                .collect(),
+        })
         .with(sprite_render.clone())
         .with(transform)
         .with(BoundingBox {
             width: 10.0,
             origin: Point2::new(0.0, 0.0),
         })
+        .with(CollisionLayer::Port)
+        .with(CollisionShape::Circle { radius: 5.0 })
         .build();
 
     let mut transform = Transform::default();
     transform.set_translation_xyz(140.0, 275.0, 0.0);
     world
         .create_entity()
-        .with(Port)
+        .with(Port { berths: 2 })
         .named("Liverpool")
         .with(Cargo::default())
+        .with(Warehouse::default())
+        .with(Tavern {
+            sailors: 8,
+            signing_fee: 4,
+        })
+        .with(Market {
+            size: 2,
+            demand: [(ItemType::Sugar, 1.5)].iter().cloned().collect(),
+        })
         .with(sprite_render.clone())
         .with(transform)
         .with(BoundingBox {
             width: 10.0,
             origin: Point2::new(0.0, 0.0),
         })
+        .with(CollisionLayer::Port)
+        .with(CollisionShape::Circle { radius: 5.0 })
         .build();
 }
 
+fn initialise_calendar(world: &mut World) {
+    let ports = {
+        let entities = world.entities();
+        let ports = world.read_component::<Port>();
+        let names = world.read_component::<Named>();
+        (&entities, &ports, &names)
+            .join()
+            .map(|(e, _, name)| (name.name.to_string(), e))
+            .collect::<HashMap<_, _>>()
+    };
+
+    let mut calendar = Calendar::default();
+    let mut yearly = |month, day, event| {
+        calendar.schedule(Recurrence::Yearly { month, day }, event);
+    };
+
+    yearly(6, 1, CalendarEvent::StormSeason { begins: true });
+    yearly(11, 30, CalendarEvent::StormSeason { begins: false });
+    if let Some(&london) = ports.get("London") {
+        yearly(
+            5,
+            1,
+            CalendarEvent::TradeFair {
+                port: london,
+                open: true,
+            },
+        );
+        yearly(
+            5,
+            21,
+            CalendarEvent::TradeFair {
+                port: london,
+                open: false,
+            },
+        );
+    }
+    if let Some(&liverpool) = ports.get("Liverpool") {
+        let harvest = |begins| CalendarEvent::Harvest {
+            port: liverpool,
+            item: ItemType::Whiskey,
+            begins,
+        };
+        yearly(9, 1, harvest(true));
+        yearly(10, 31, harvest(false));
+    }
+    // Every month has a 28th
+    calendar.schedule(Recurrence::Monthly { day: 28 }, CalendarEvent::Payday);
+    calendar.schedule(Recurrence::Monthly { day: 1 }, CalendarEvent::MonthBegins);
+    if let Some(&portsmouth) = ports.get("Portsmouth") {
+        calendar.schedule(
+            Recurrence::Monthly { day: 1 },
+            CalendarEvent::FleetSailing { port: portsmouth },
+        );
+    }
+
+    world.insert(calendar);
+}
+
 fn initialise_contracts(world: &mut World) {
     let port_entities = {
         let entities = world.entities();
@@ -373,16 +562,57 @@ pub fn point_in_rect(point: Point2<f32>, left: f32, right: f32, top: f32, bottom
     point.x >= left && point.x <= right && point.y <= top && point.y >= bottom
 }
 
+pub fn distance_to_segment(point: Point2<f32>, start: Point2<f32>, end: Point2<f32>) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.magnitude_squared();
+    if length_squared == 0.0 {
+        return (point - start).magnitude();
+    }
+
+    let t = ((point - start).dot(&segment) / length_squared)
+        .max(0.0)
+        .min(1.0);
+    (point - (start + t * segment)).magnitude()
+}
+
+// Left, right, top and bottom of the sea the camera and minimap cover
+pub fn sea_bounds(map: &Map) -> Option<(f32, f32, f32, f32)> {
+    map.bounds().map(|(left, right, top, bottom)| {
+        (
+            left - CAMERA_MARGIN,
+            right + CAMERA_MARGIN,
+            top + CAMERA_MARGIN,
+            bottom - CAMERA_MARGIN,
+        )
+    })
+}
+
 pub fn point_mouse_to_world(
     mouse_x: f32,
     mouse_y: f32,
     screen_dimensions: &ScreenDimensions,
     camera_position: &Vector3<f32>,
+    zoom: f32,
 ) -> Point2<f32> {
+    let (view_width, view_height) = (WORLD_WIDTH * zoom, WORLD_HEIGHT * zoom);
     Point2::new(
-        WORLD_WIDTH * mouse_x / screen_dimensions.width() + (camera_position.x - WORLD_WIDTH / 2.0),
-        WORLD_HEIGHT - WORLD_HEIGHT * mouse_y / screen_dimensions.height()
-            + (camera_position.y - WORLD_HEIGHT / 2.0),
+        view_width * mouse_x / screen_dimensions.width() + (camera_position.x - view_width / 2.0),
+        view_height - view_height * mouse_y / screen_dimensions.height()
+            + (camera_position.y - view_height / 2.0),
+    )
+}
+
+pub fn point_world_to_screen(
+    point: Point2<f32>,
+    screen_dimensions: &ScreenDimensions,
+    camera_position: &Vector3<f32>,
+    zoom: f32,
+) -> (f32, f32) {
+    let (view_width, view_height) = (WORLD_WIDTH * zoom, WORLD_HEIGHT * zoom);
+    (
+        (point.x - camera_position.x + view_width / 2.0) * screen_dimensions.width() / view_width,
+        (camera_position.y + view_height / 2.0 - point.y) * screen_dimensions.height()
+            / view_height,
     )
 }
 
@@ -391,10 +621,133 @@ pub struct PlayerStatus {
     pub money: i32,
 }
 
+pub struct ContractRecord {
+    pub destination: String,
+    pub goods: String,
+    pub completed: bool,
+    // Net change in money over the life of the contract
+    pub earnings: i32,
+    pub date: chrono::Date<Utc>,
+}
+
+// Selected ships given one destination keep their positions relative to each other
+#[derive(Default)]
+pub struct FormationMode {
+    pub enabled: bool,
+}
+
+// Waypoint being dragged by the player, found again by its last position
+#[derive(Default)]
+pub struct CourseEditing {
+    pub dragging: Option<(Entity, Point2<f32>)>,
+}
+
+// Zoom scales the area of the world the camera shows, 1.0 shows WORLD_WIDTH by WORLD_HEIGHT
+pub struct CameraView {
+    pub zoom: f32,
+    // Keeps the selected ships in the middle of the screen
+    pub following: bool,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        CameraView {
+            zoom: 1.0,
+            following: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotificationCategory {
+    Contracts,
+    Cargo,
+    Ships,
+    Trade,
+    General,
+}
+
+pub const NOTIFICATION_CATEGORIES: [NotificationCategory; 5] = [
+    NotificationCategory::Contracts,
+    NotificationCategory::Cargo,
+    NotificationCategory::Ships,
+    NotificationCategory::Trade,
+    NotificationCategory::General,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub message: String,
+    pub category: NotificationCategory,
+    pub severity: Severity,
+    // Ship or port the notification is about, focused when it is clicked
+    pub entity: Option<Entity>,
+}
+
+impl Notification {
+    pub fn new(category: NotificationCategory, message: String) -> Self {
+        Notification {
+            message,
+            category,
+            severity: Severity::Info,
+            entity: None,
+        }
+    }
+
+    pub fn warning(self) -> Self {
+        Notification {
+            severity: Severity::Warning,
+            ..self
+        }
+    }
+
+    pub fn critical(self) -> Self {
+        Notification {
+            severity: Severity::Critical,
+            ..self
+        }
+    }
+
+    pub fn about(self, entity: Entity) -> Self {
+        Notification {
+            entity: Some(entity),
+            ..self
+        }
+    }
+}
+
+pub struct LoggedNotification {
+    pub notification: Notification,
+    pub date: chrono::DateTime<Utc>,
+}
+
+// Every notification shown, oldest first
+#[derive(Default)]
+pub struct NotificationLog {
+    pub entries: Vec<LoggedNotification>,
+    // Index of the entry on screen in the notification bar
+    pub shown: Option<usize>,
+}
+
+// Completed and failed contracts, oldest first
+#[derive(Default)]
+pub struct ContractHistory {
+    pub records: Vec<ContractRecord>,
+}
+
 pub struct Date {
     pub time_elapsed: f64,
     pub current_speed: f32,
     pub paused: bool,
+    // Time stops for good once the game is lost
+    pub game_over: bool,
 }
 
 impl Default for Date {
@@ -403,23 +756,24 @@ impl Default for Date {
             time_elapsed: 0.,
             current_speed: 1.,
             paused: false,
+            game_over: false,
         }
     }
 }
 
 impl Date {
-    pub fn current_date_string(&self) -> String {
-        self.current_date().format("%e %B %Y").to_string()
-    }
-
     pub fn current_date(&self) -> chrono::Date<Utc> {
         let utc = Utc.ymd(1680, 1, 1);
 
         utc.add(Duration::seconds(self.time_elapsed as i64))
     }
 
+    pub fn current_date_time(&self) -> chrono::DateTime<Utc> {
+        Utc.ymd(1680, 1, 1).and_hms(0, 0, 0) + Duration::seconds(self.time_elapsed as i64)
+    }
+
     pub fn game_speed(&self) -> f32 {
-        if self.paused {
+        if self.paused || self.game_over {
             0.
         } else {
             self.current_speed
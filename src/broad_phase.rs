@@ -0,0 +1,81 @@
+use std::cmp::Ordering;
+
+// Returns the index pairs of boundaries overlapping on both axes. Boundaries are sorted by
+// their left edge so each one is only compared against those starting before it ends
+pub fn sweep_and_prune(boundaries: &[(f32, f32, f32, f32)]) -> Vec<(usize, usize)> {
+    let mut order = (0..boundaries.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        boundaries[*a]
+            .0
+            .partial_cmp(&boundaries[*b].0)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mut pairs = Vec::new();
+
+    for (n, i) in order.iter().enumerate() {
+        let (_, right, top, bottom) = boundaries[*i];
+
+        for j in order.iter().skip(n + 1) {
+            let (other_left, _, other_top, other_bottom) = boundaries[*j];
+
+            if other_left > right {
+                break;
+            }
+
+            if other_bottom <= top && other_top >= bottom {
+                pairs.push((*i, *j));
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, Rng};
+    use std::collections::HashSet;
+
+    fn random_boundaries(count: usize) -> Vec<(f32, f32, f32, f32)> {
+        let mut rng = thread_rng();
+        (0..count)
+            .map(|_| {
+                let x = rng.gen_range(0.0..400.0);
+                let y = rng.gen_range(0.0..400.0);
+                (x - 4.0, x + 4.0, y + 4.0, y - 4.0)
+            })
+            .collect()
+    }
+
+    fn brute_force_pairs(boundaries: &[(f32, f32, f32, f32)]) -> HashSet<(usize, usize)> {
+        let mut pairs = HashSet::new();
+        for (i, (left, right, top, bottom)) in boundaries.iter().enumerate() {
+            for (j, (other_left, other_right, other_top, other_bottom)) in
+                boundaries.iter().enumerate().skip(i + 1)
+            {
+                if other_left <= right
+                    && other_right >= left
+                    && other_bottom <= top
+                    && other_top >= bottom
+                {
+                    pairs.insert((i, j));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn sweep_and_prune_finds_same_pairs_as_brute_force() {
+        let boundaries = random_boundaries(500);
+
+        let pairs = sweep_and_prune(&boundaries)
+            .into_iter()
+            .map(|(i, j)| (i.min(j), i.max(j)))
+            .collect::<HashSet<_>>();
+
+        assert_eq!(brute_force_pairs(&boundaries), pairs);
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{Date, Datelike, Utc};
+
+use crate::event::CalendarEvent;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn of(date: Date<Utc>) -> Season {
+        match date.month() {
+            3..=5 => Season::Spring,
+            6..=8 => Season::Summer,
+            9..=11 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Recurrence {
+    // Months without this day are skipped
+    Monthly { day: u32 },
+    Yearly { month: u32, day: u32 },
+}
+
+impl Recurrence {
+    pub fn occurs_on(&self, date: Date<Utc>) -> bool {
+        match *self {
+            Recurrence::Monthly { day } => date.day() == day,
+            Recurrence::Yearly { month, day } => date.month() == month && date.day() == day,
+        }
+    }
+}
+
+// Events systems have asked to be sent on particular days
+#[derive(Default)]
+pub struct Calendar {
+    entries: Vec<(Recurrence, CalendarEvent)>,
+    pub storm_season: bool,
+}
+
+impl Calendar {
+    pub fn schedule(&mut self, recurrence: Recurrence, event: CalendarEvent) {
+        self.entries.push((recurrence, event));
+    }
+
+    // Events in the order they were scheduled
+    pub fn events_on(&self, date: Date<Utc>) -> Vec<CalendarEvent> {
+        self.entries
+            .iter()
+            .filter(|(recurrence, _)| recurrence.occurs_on(date))
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    #[test_case(Utc.ymd(1680, 3, 1), Season::Spring ; "start of spring")]
+    #[test_case(Utc.ymd(1680, 8, 31), Season::Summer ; "end of summer")]
+    #[test_case(Utc.ymd(1680, 11, 15), Season::Autumn ; "autumn")]
+    #[test_case(Utc.ymd(1681, 1, 1), Season::Winter ; "new year")]
+    fn season_follows_month(date: Date<Utc>, expected: Season) {
+        assert_eq!(expected, Season::of(date));
+    }
+
+    #[test_case(Recurrence::Monthly { day: 15 }, Utc.ymd(1680, 9, 15), true ; "monthly")]
+    #[test_case(Recurrence::Monthly { day: 31 }, Utc.ymd(1680, 6, 30), false ; "monthly skips short months")]
+    #[test_case(Recurrence::Yearly { month: 6, day: 1 }, Utc.ymd(1683, 6, 1), true ; "yearly")]
+    #[test_case(Recurrence::Yearly { month: 6, day: 1 }, Utc.ymd(1683, 7, 1), false ; "yearly other month")]
+    fn recurrence_occurs_on(recurrence: Recurrence, date: Date<Utc>, expected: bool) {
+        assert_eq!(expected, recurrence.occurs_on(date));
+    }
+}
@@ -11,36 +11,6 @@ pub struct BoundingBox {
 }
 
 impl BoundingBox {
-    pub fn as_2d_points(
-        &self,
-        local: &Transform,
-    ) -> (Point2<f32>, Point2<f32>, Point2<f32>, Point2<f32>) {
-        let centre_x = local.translation().x;
-        let centre_y = local.translation().y;
-
-        let top_left = Point2::new(
-            centre_x + self.origin.x - self.width * 0.5,
-            centre_y + self.origin.y + self.width * 0.5,
-        );
-
-        let top_right = Point2::new(
-            centre_x + self.origin.x + self.width * 0.5,
-            centre_y + self.origin.y + self.width * 0.5,
-        );
-
-        let bottom_right = Point2::new(
-            centre_x + self.origin.x + self.width * 0.5,
-            centre_y + self.origin.y - self.width * 0.5,
-        );
-
-        let bottom_left = Point2::new(
-            centre_x + self.origin.x - self.width * 0.5,
-            centre_y + self.origin.y - self.width * 0.5,
-        );
-
-        (top_left, top_right, bottom_right, bottom_left)
-    }
-
     pub fn as_boundaries(&self, local: &Transform) -> (f32, f32, f32, f32) {
         let centre_x = local.translation().x;
         let centre_y = local.translation().y;
@@ -1,4 +1,4 @@
-use amethyst::ecs::{storage::DenseVecStorage, Component};
+use amethyst::ecs::{storage::DenseVecStorage, Component, Entity};
 use std::collections::HashMap;
 
 use super::ItemType;
@@ -8,3 +8,13 @@ use super::ItemType;
 pub struct Cargo {
     pub items: HashMap<ItemType, u32>,
 }
+
+// Goods being loaded onto or unloaded from a docked ship for the player's contracts
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct CargoTransfer {
+    pub contracts: Vec<Entity>,
+    pub unloading: bool,
+    // Game time the transfer is finished at
+    pub complete_at: f64,
+}
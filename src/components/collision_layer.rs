@@ -0,0 +1,16 @@
+use amethyst::ecs::{storage::DenseVecStorage, Component};
+
+// Entities with a bounding box but no layer collide as ships
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[storage(DenseVecStorage)]
+pub enum CollisionLayer {
+    Ship,
+    Port,
+    Projectile,
+}
+
+impl Default for CollisionLayer {
+    fn default() -> Self {
+        CollisionLayer::Ship
+    }
+}
@@ -0,0 +1,35 @@
+use amethyst::{
+    core::math::Vector2,
+    ecs::{storage::DenseVecStorage, Component},
+};
+
+// Shape used for collisions, centred on the entity and aligned to its heading. Entities
+// without one collide using their bounding box
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+#[storage(DenseVecStorage)]
+pub enum CollisionShape {
+    Circle { radius: f32 },
+    // Length runs along the heading and width across it
+    Rectangle { length: f32, width: f32 },
+    // Line of the given length along the heading, padded by the radius on every side
+    Capsule { length: f32, radius: f32 },
+}
+
+impl CollisionShape {
+    // Half the width and height of the axis-aligned box containing the shape
+    pub fn half_extents(&self, heading: f32) -> Vector2<f32> {
+        let (sin, cos) = heading.sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+
+        match *self {
+            CollisionShape::Circle { radius } => Vector2::new(radius, radius),
+            CollisionShape::Rectangle { length, width } => Vector2::new(
+                cos * length * 0.5 + sin * width * 0.5,
+                sin * length * 0.5 + cos * width * 0.5,
+            ),
+            CollisionShape::Capsule { length, radius } => {
+                Vector2::new(cos * length * 0.5 + radius, sin * length * 0.5 + radius)
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use amethyst::ecs::{storage::DenseVecStorage, Component, Entity};
+use chrono::{Date, Utc};
 use rand::{seq::SliceRandom, thread_rng};
 use std::collections::HashMap;
 
@@ -9,6 +10,21 @@ pub struct Contract {
     pub destination: Entity,
     pub goods_required: HashMap<ItemType, u32>,
     pub fulfilled: bool,
+    pub kind: ContractKind,
+    // Taken when accepted and returned on completion
+    pub deposit: u32,
+    // Charged if an accepted contract expires
+    pub penalty: u32,
+    // Payment decays for deliveries after this date
+    pub due_date: Option<Date<Utc>>,
+    // Port the goods were handed over at, set when accepted
+    pub origin: Option<Entity>,
+    // Player ship the goods are reserved for, the first to dock at the origin if not set
+    pub carrier: Option<Entity>,
+    // Goods waiting at the origin to be loaded onto the carrier
+    pub reserved: HashMap<ItemType, u32>,
+    pub delivered: HashMap<ItemType, u32>,
+    pub earned: u32,
 }
 
 impl Contract {
@@ -18,8 +34,52 @@ impl Contract {
             destination,
             goods_required,
             fulfilled: false,
+            kind: ContractKind::Delivery,
+            deposit: 0,
+            penalty: 0,
+            due_date: None,
+            origin: None,
+            carrier: None,
+            reserved: HashMap::new(),
+            delivered: HashMap::new(),
+            earned: 0,
         }
     }
+
+    pub fn remaining_goods(&self) -> HashMap<ItemType, u32> {
+        self.goods_required
+            .iter()
+            .map(|(item, amount)| {
+                (
+                    *item,
+                    amount.saturating_sub(*self.delivered.get(item).unwrap_or(&0)),
+                )
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractKind {
+    Delivery,
+    // Always generated with a tight expiration date
+    Passengers,
+    Mail,
+    // Escorted ship has to reach the destination with a player ship nearby
+    Escort { escortee: Entity },
+    // Goods are confiscated if the ship is inspected on arrival
+    Smuggling { inspection_chance: f32 },
+    // The contract destination is the final drop. Goods required covers every drop
+    Bulk { drops: Vec<DropOff> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropOff {
+    pub destination: Entity,
+    pub goods: HashMap<ItemType, u32>,
+    pub payment: u32,
+    pub delivered: bool,
 }
 
 #[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy, Debug)]
@@ -27,6 +87,9 @@ pub enum ItemType {
     Rum,
     Sugar,
     Whiskey,
+    Passengers,
+    Mail,
+    Contraband,
 }
 
 impl std::fmt::Display for ItemType {
@@ -35,10 +98,22 @@ impl std::fmt::Display for ItemType {
     }
 }
 
+pub const TRADE_GOODS: [ItemType; 3] = [ItemType::Sugar, ItemType::Rum, ItemType::Whiskey];
+
 impl ItemType {
     pub fn choose() -> ItemType {
         let mut rng = thread_rng();
-        let choices = [ItemType::Sugar, ItemType::Rum, ItemType::Whiskey];
-        *choices.choose(&mut rng).unwrap()
+        *TRADE_GOODS.choose(&mut rng).unwrap()
+    }
+
+    // Price per unit at a market with no particular demand for it
+    pub fn base_price(&self) -> u32 {
+        match self {
+            ItemType::Rum => 12,
+            ItemType::Sugar => 8,
+            ItemType::Whiskey => 15,
+            ItemType::Contraband => 30,
+            ItemType::Passengers | ItemType::Mail => 0,
+        }
     }
 }
@@ -16,3 +16,10 @@ pub struct Patrol {
     pub waypoints: Vec<Point2<f32>>,
     pub next_waypoint_index: usize,
 }
+
+// Given with a formation order so the group keeps its shape on the way
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Formation {
+    pub speed: f32,
+}
@@ -0,0 +1,15 @@
+use amethyst::ecs::{storage::DenseVecStorage, Component, Entity};
+
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Docked {
+    pub port: Entity,
+}
+
+// Ship within the approach radius of the port it is making for,
+// holding position if every berth is taken
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Approaching {
+    pub port: Entity,
+}
@@ -0,0 +1,37 @@
+use amethyst::ecs::{storage::DenseVecStorage, Component};
+
+// Ships without a helm turn and change speed instantly
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Helm {
+    // Radians anticlockwise from the x axis, the sprite is rotated to match
+    pub heading: f32,
+    pub speed: f32,
+    // Radians per second
+    pub max_turn_rate: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+}
+
+impl Default for Helm {
+    fn default() -> Self {
+        Helm {
+            heading: 0.0,
+            speed: 0.0,
+            max_turn_rate: 1.5,
+            acceleration: 4.0,
+            deceleration: 8.0,
+        }
+    }
+}
+
+impl Helm {
+    // Radius of the tightest circle the ship can sail at its current speed
+    pub fn turning_radius(&self) -> f32 {
+        self.speed / self.max_turn_rate
+    }
+
+    pub fn stopping_distance(&self) -> f32 {
+        self.speed * self.speed / (2.0 * self.deceleration)
+    }
+}
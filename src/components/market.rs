@@ -0,0 +1,28 @@
+use amethyst::ecs::{storage::DenseVecStorage, Component};
+use std::collections::HashMap;
+
+use super::ItemType;
+
+#[derive(Component, Default)]
+#[storage(DenseVecStorage)]
+pub struct Market {
+    // Larger ports generate contracts more often
+    pub size: u32,
+    // Multiplier on payment for goods delivered to this market. Missing items count as 1.0
+    pub demand: HashMap<ItemType, f32>,
+}
+
+impl Market {
+    pub fn demand_for(&self, item: &ItemType) -> f32 {
+        *self.demand.get(item).unwrap_or(&1.0)
+    }
+
+    pub fn buy_price(&self, item: &ItemType) -> u32 {
+        (item.base_price() as f32 * self.demand_for(item)).round() as u32
+    }
+
+    // Markets buy back at a discount on what they sell for
+    pub fn sell_price(&self, item: &ItemType) -> u32 {
+        (self.buy_price(item) as f32 * 0.8).floor() as u32
+    }
+}
@@ -1,20 +1,30 @@
 pub use self::ai::{Action, Ai, AiState, StateQuery};
 pub use self::bounding_box::BoundingBox;
-pub use self::cargo::Cargo;
-pub use self::contract::{Contract, ItemType};
-pub use self::course::{Course, Patrol};
+pub use self::cargo::{Cargo, CargoTransfer};
+pub use self::collision_layer::CollisionLayer;
+pub use self::collision_shape::CollisionShape;
+pub use self::contract::{Contract, ContractKind, DropOff, ItemType};
+pub use self::course::{Course, Formation, Patrol};
+pub use self::docked::{Approaching, Docked};
 pub use self::expiration::Expiration;
+pub use self::helm::Helm;
+pub use self::market::Market;
 pub use self::owned_by::OwnedBy;
-pub use self::port::Port;
+pub use self::port::{Bank, Port, ShipForSale, Shipyard, Tavern, Warehouse};
 pub use self::selection::{Controllable, Selected};
-pub use self::ship::{Affiliation, Pirate, Ship};
+pub use self::ship::{Affiliation, Crew, Hull, Pirate, Ship, LOW_MORALE};
 
 pub mod ai;
 pub mod bounding_box;
 pub mod cargo;
+pub mod collision_layer;
+pub mod collision_shape;
 pub mod contract;
 pub mod course;
+pub mod docked;
 pub mod expiration;
+pub mod helm;
+pub mod market;
 pub mod owned_by;
 pub mod port;
 pub mod selection;
@@ -1,5 +1,56 @@
-use amethyst::ecs::{storage::NullStorage, Component};
+use amethyst::ecs::{storage::DenseVecStorage, Component};
+use std::collections::HashMap;
 
+use super::ItemType;
+
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Port {
+    // Number of ships that can be docked at once
+    pub berths: usize,
+}
+
+impl Default for Port {
+    fn default() -> Self {
+        Port { berths: 2 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShipForSale {
+    pub name: String,
+    pub base_speed: f32,
+    pub berths: u32,
+    pub price: u32,
+}
+
+#[derive(Component, Default)]
+#[storage(DenseVecStorage)]
+pub struct Shipyard {
+    pub ships_for_sale: Vec<ShipForSale>,
+    // Cost of repairing one percent of hull damage
+    pub repair_price: u32,
+}
+
+// The player's goods stored at a port
+#[derive(Component, Default)]
+#[storage(DenseVecStorage)]
+pub struct Warehouse {
+    pub items: HashMap<ItemType, u32>,
+}
+
+// Sailors waiting at a port to sign on
+#[derive(Component, Default)]
+#[storage(DenseVecStorage)]
+pub struct Tavern {
+    pub sailors: u32,
+    pub signing_fee: u32,
+}
+
+// Lends the player money, up to a limit, at a monthly rate of interest
 #[derive(Component, Default)]
-#[storage(NullStorage)]
-pub struct Port;
+#[storage(DenseVecStorage)]
+pub struct Bank {
+    pub limit: u32,
+    pub monthly_rate: f32,
+}
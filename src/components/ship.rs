@@ -9,6 +9,79 @@ pub struct Ship {
     pub base_speed: f32,
 }
 
+// Percentage of the hull left undamaged
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Hull {
+    pub condition: u32,
+}
+
+impl Default for Hull {
+    fn default() -> Self {
+        Hull { condition: 100 }
+    }
+}
+
+// Below this the crew is unhappy and works the ship badly
+pub const LOW_MORALE: f32 = 30.0;
+
+#[derive(Component)]
+#[storage(DenseVecStorage)]
+pub struct Crew {
+    pub sailors: u32,
+    // Most sailors the ship has room for
+    pub berths: u32,
+    // Out of 100, the crew mutinies if it reaches nothing
+    pub morale: f32,
+    // Days of food for one sailor
+    pub provisions: f32,
+    // Since the ship last left port
+    pub days_at_sea: f32,
+}
+
+impl Default for Crew {
+    fn default() -> Self {
+        Crew {
+            sailors: 0,
+            berths: 0,
+            morale: 75.0,
+            provisions: 0.0,
+            days_at_sea: 0.0,
+        }
+    }
+}
+
+impl Crew {
+    // Share of the berths filled
+    pub fn manning(&self) -> f32 {
+        if self.berths == 0 {
+            1.0
+        } else {
+            (self.sailors as f32 / self.berths as f32).min(1.0)
+        }
+    }
+
+    // Half a crew can sail the ship at full speed, fewer slow it down
+    pub fn speed_multiplier(&self) -> f32 {
+        let morale = if self.morale < LOW_MORALE { 0.8 } else { 1.0 };
+        (0.5 + self.manning()).min(1.0) * morale
+    }
+
+    // Between nothing for an empty or mutinous ship and 1 for a full, happy crew
+    pub fn fighting_strength(&self) -> f32 {
+        self.manning() * self.morale / 100.0
+    }
+
+    // How many days the food will last
+    pub fn days_of_provisions(&self) -> u32 {
+        if self.sailors == 0 {
+            0
+        } else {
+            (self.provisions / self.sailors as f32) as u32
+        }
+    }
+}
+
 #[derive(Component, Default)]
 #[storage(DenseVecStorage)]
 pub struct Affiliation {
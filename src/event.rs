@@ -1,13 +1,234 @@
 use amethyst::ecs::Entity;
+use chrono::{Date, Utc};
+use std::collections::HashMap;
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::{
+    calendar::Season,
+    components::{ContractKind, ItemType},
+    finance::ProfitAndLoss,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum UiUpdateEvent {
     Target(Entity),
     Deselected(Entity),
     PlayerStatus,
+    // Accepted contracts or contract history changed
+    Journal,
+    NewDay,
 }
 
 pub struct CollisionEvent {
     pub entity: Entity,
     pub other_entity: Entity,
 }
+
+pub struct DockEvent {
+    pub ship: Entity,
+    pub port: Entity,
+}
+
+pub struct UndockEvent {
+    pub ship: Entity,
+    pub port: Entity,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortAction {
+    Buy(ItemType),
+    Sell(ItemType),
+    Store(ItemType),
+    Retrieve(ItemType),
+    Repair,
+    Hire,
+    BuyProvisions,
+    // Index into the shipyard's ships for sale
+    BuyShip(usize),
+    TakeLoan,
+    RepayLoan,
+}
+
+// Chosen from the port screen, for the player's ship docked at the port if the action needs one
+pub struct PortActionEvent {
+    pub port: Entity,
+    pub ship: Option<Entity>,
+    pub action: PortAction,
+}
+
+// Why the port couldn't carry out an action
+#[derive(Clone, Debug, PartialEq)]
+pub enum PortRefusal {
+    CannotAfford(u32),
+    CannotAffordRepairs,
+    NoRepairsNeeded,
+    NoShipyard,
+    NoSailors,
+    NoBerths,
+    NothingStored,
+    NotEnough(ItemType),
+    AlreadySold,
+    NoShipDocked,
+    NoBank,
+    LoanLimitReached,
+    NothingOwed,
+}
+
+// Sent by the calendar on the day they happen
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalendarEvent {
+    SeasonBegins(Season),
+    TradeFair {
+        port: Entity,
+        open: bool,
+    },
+    Harvest {
+        port: Entity,
+        item: ItemType,
+        begins: bool,
+    },
+    StormSeason {
+        begins: bool,
+    },
+    // Merchants gather at the port and want escorts and cargo carried
+    FleetSailing {
+        port: Entity,
+    },
+    // Crews of the player's ships are owed their wages
+    Payday,
+    // Interest falls due and last month's accounts are drawn up
+    MonthBegins,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameOverReason {
+    Bankrupt,
+    // No ships left and no money to buy another
+    Shipless,
+}
+
+// Things that happen in the game. Notifications are written from these in one place, and anything
+// else interested in what happened can read them without picking apart notification text
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    DepositUnaffordable {
+        deposit: u32,
+    },
+    ContractAccepted {
+        port: Entity,
+        destination: Entity,
+        kind: ContractKind,
+        carrier: Option<Entity>,
+        goods: HashMap<ItemType, u32>,
+        deposit: u32,
+        expires: Option<Date<Utc>>,
+    },
+    EscortLost {
+        penalty: u32,
+    },
+    // Part of a contract delivered, with more still to go
+    GoodsDelivered {
+        port: Entity,
+        goods: HashMap<ItemType, u32>,
+        payment: u32,
+        still_required: HashMap<ItemType, u32>,
+    },
+    ContractFulfilled {
+        port: Entity,
+        kind: ContractKind,
+        goods: HashMap<ItemType, u32>,
+        earned: u32,
+        deposit: u32,
+    },
+    GoodsSeized {
+        port: Entity,
+        goods: HashMap<ItemType, u32>,
+        fine: u32,
+    },
+    ContractExpired {
+        reclaimed: HashMap<ItemType, u32>,
+        reclaimed_by: Option<Entity>,
+        penalty: u32,
+    },
+    TransferInterrupted {
+        ship: Entity,
+        port: Entity,
+        unloading: bool,
+    },
+    CargoLoaded {
+        ship: Entity,
+        port: Entity,
+        goods: HashMap<ItemType, u32>,
+    },
+    ShipDestroyed {
+        ship: Entity,
+    },
+    // One of the player's ships sailed into a storm
+    CaughtInStorm {
+        ship: Entity,
+    },
+    // The ship is gone by the time this is read, so it carries the name
+    ShipFoundered {
+        name: String,
+    },
+    WagesPaid {
+        amount: u32,
+    },
+    WagesUnpaid {
+        amount: u32,
+    },
+    ProvisionsExhausted {
+        ship: Entity,
+    },
+    MoraleLow {
+        ship: Entity,
+    },
+    // The crew took the ship, which is gone by the time this is read
+    Mutiny {
+        name: String,
+    },
+    // A crew strong enough to fight sank the pirate that attacked them
+    PiratesFoughtOff {
+        ship: Entity,
+    },
+    // Last month's accounts
+    MonthlyReport {
+        report: ProfitAndLoss,
+    },
+    InterestCharged {
+        amount: u32,
+    },
+    // Still in debt at the start of the month, with this many months left to pay it off
+    InDebt {
+        months_left: u32,
+    },
+    GameOver {
+        reason: GameOverReason,
+    },
+    WaitingForBerth {
+        ship: Entity,
+        port: Entity,
+    },
+    FormationChanged {
+        enabled: bool,
+    },
+    ShipPurchased {
+        name: String,
+        port: Entity,
+    },
+    PortActionRefused {
+        port: Entity,
+        reason: PortRefusal,
+    },
+    // Bound from the key bindings screen, described the way the screen lists it
+    KeyRebound {
+        action: String,
+        binding: String,
+    },
+    KeyNotRebound {
+        action: String,
+        error: String,
+    },
+    KeyBindingsNotSaved {
+        error: String,
+    },
+}
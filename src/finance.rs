@@ -0,0 +1,260 @@
+use amethyst::ecs::Entity;
+use chrono::{Date, Datelike, Utc};
+
+use crate::age_of_sail::PlayerStatus;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransactionKind {
+    ContractIncome,
+    Sales,
+    // Paid when a contract is accepted and returned when it is completed
+    Deposits,
+    Purchases,
+    Wages,
+    Repairs,
+    Penalties,
+    Interest,
+    // Money borrowed from or paid back to a bank, which is neither profit nor loss
+    Loans,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transaction {
+    pub date: Date<Utc>,
+    pub kind: TransactionKind,
+    pub amount: i32,
+}
+
+// Owed to the bank at a port. Interest is charged on it every month
+#[derive(Clone, Debug, PartialEq)]
+pub struct Loan {
+    pub lender: Entity,
+    pub owed: u32,
+    pub monthly_rate: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfitAndLoss {
+    pub year: i32,
+    pub month: u32,
+    // Net amount for each kind of transaction in the month
+    pub lines: Vec<(TransactionKind, i32)>,
+}
+
+impl ProfitAndLoss {
+    pub fn income(&self) -> i32 {
+        self.lines.iter().map(|(_, amount)| (*amount).max(0)).sum()
+    }
+
+    pub fn expenses(&self) -> i32 {
+        -self
+            .lines
+            .iter()
+            .map(|(_, amount)| (*amount).min(0))
+            .sum::<i32>()
+    }
+
+    pub fn net(&self) -> i32 {
+        self.lines.iter().map(|(_, amount)| amount).sum()
+    }
+}
+
+// Every change to the player's money, with the loans behind some of them
+#[derive(Default)]
+pub struct Ledger {
+    pub transactions: Vec<Transaction>,
+    pub loans: Vec<Loan>,
+    // Months in a row the player has begun in debt
+    pub months_in_debt: u32,
+}
+
+impl Ledger {
+    pub fn record(
+        &mut self,
+        player_status: &mut PlayerStatus,
+        date: Date<Utc>,
+        kind: TransactionKind,
+        amount: i32,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        player_status.money += amount;
+        self.transactions.push(Transaction { date, kind, amount });
+    }
+
+    pub fn borrow(
+        &mut self,
+        player_status: &mut PlayerStatus,
+        date: Date<Utc>,
+        lender: Entity,
+        amount: u32,
+        monthly_rate: f32,
+    ) {
+        match self.loans.iter_mut().find(|loan| loan.lender == lender) {
+            Some(loan) => loan.owed += amount,
+            None => self.loans.push(Loan {
+                lender,
+                owed: amount,
+                monthly_rate,
+            }),
+        }
+        self.record(player_status, date, TransactionKind::Loans, amount as i32);
+    }
+
+    // Pays back up to the amount, returning how much was owed and paid
+    pub fn repay(
+        &mut self,
+        player_status: &mut PlayerStatus,
+        date: Date<Utc>,
+        lender: Entity,
+        amount: u32,
+    ) -> u32 {
+        let repaid = match self.loans.iter_mut().find(|loan| loan.lender == lender) {
+            Some(loan) => {
+                let repaid = loan.owed.min(amount);
+                loan.owed -= repaid;
+                repaid
+            }
+            None => 0,
+        };
+        self.loans.retain(|loan| loan.owed > 0);
+        self.record(
+            player_status,
+            date,
+            TransactionKind::Loans,
+            -(repaid as i32),
+        );
+        repaid
+    }
+
+    // Interest on every loan is paid whether or not the player has the money
+    pub fn charge_interest(&mut self, player_status: &mut PlayerStatus, date: Date<Utc>) -> u32 {
+        let interest = self
+            .loans
+            .iter()
+            .map(|loan| (loan.owed as f32 * loan.monthly_rate).ceil() as u32)
+            .sum::<u32>();
+        self.record(
+            player_status,
+            date,
+            TransactionKind::Interest,
+            -(interest as i32),
+        );
+        interest
+    }
+
+    pub fn owed_to(&self, lender: Entity) -> u32 {
+        self.loans
+            .iter()
+            .filter(|loan| loan.lender == lender)
+            .map(|loan| loan.owed)
+            .sum()
+    }
+
+    pub fn total_owed(&self) -> u32 {
+        self.loans.iter().map(|loan| loan.owed).sum()
+    }
+
+    pub fn profit_and_loss(&self, year: i32, month: u32) -> ProfitAndLoss {
+        let mut lines: Vec<(TransactionKind, i32)> = Vec::new();
+        for transaction in self.transactions.iter().filter(|t| {
+            t.date.year() == year && t.date.month() == month && t.kind != TransactionKind::Loans
+        }) {
+            match lines.iter_mut().find(|(kind, _)| *kind == transaction.kind) {
+                Some((_, amount)) => *amount += transaction.amount,
+                None => lines.push((transaction.kind, transaction.amount)),
+            }
+        }
+        lines.sort_by_key(|(kind, _)| *kind);
+
+        ProfitAndLoss { year, month, lines }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::prelude::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn recording_changes_money() {
+        let mut ledger = Ledger::default();
+        let mut player_status = PlayerStatus { money: 100 };
+
+        let date = Utc.ymd(1680, 1, 5);
+        ledger.record(&mut player_status, date, TransactionKind::Wages, -30);
+        ledger.record(&mut player_status, date, TransactionKind::Sales, 0);
+
+        assert_eq!(70, player_status.money);
+        assert_eq!(
+            vec![Transaction {
+                date,
+                kind: TransactionKind::Wages,
+                amount: -30
+            }],
+            ledger.transactions
+        );
+    }
+
+    #[test]
+    fn loans_are_repaid_and_charged_interest() {
+        let mut world = World::new();
+        let lender = world.create_entity().build();
+        let mut ledger = Ledger::default();
+        let mut player_status = PlayerStatus::default();
+        let date = Utc.ymd(1680, 1, 5);
+
+        ledger.borrow(&mut player_status, date, lender, 500, 0.05);
+        ledger.borrow(&mut player_status, date, lender, 500, 0.05);
+        assert_eq!(1000, ledger.owed_to(lender), "Owed");
+
+        assert_eq!(
+            50,
+            ledger.charge_interest(&mut player_status, date),
+            "Interest"
+        );
+        assert_eq!(950, player_status.money, "Money after interest");
+
+        assert_eq!(
+            1000,
+            ledger.repay(&mut player_status, date, lender, 1500),
+            "Repaid"
+        );
+        assert_eq!(-50, player_status.money, "Money after repaying");
+        assert!(ledger.loans.is_empty(), "Loans left");
+    }
+
+    #[test]
+    fn profit_and_loss_covers_one_month() {
+        let mut ledger = Ledger::default();
+        let mut player_status = PlayerStatus::default();
+
+        let mut record = |month, day, kind, amount| {
+            ledger.record(&mut player_status, Utc.ymd(1680, month, day), kind, amount)
+        };
+        record(1, 31, TransactionKind::ContractIncome, 500);
+        record(2, 1, TransactionKind::Loans, 1000);
+        record(2, 3, TransactionKind::ContractIncome, 200);
+        record(2, 10, TransactionKind::Repairs, -40);
+        record(2, 15, TransactionKind::ContractIncome, 150);
+        record(2, 28, TransactionKind::Wages, -60);
+        record(3, 1, TransactionKind::Interest, -50);
+
+        let report = ledger.profit_and_loss(1680, 2);
+
+        assert_eq!(
+            vec![
+                (TransactionKind::ContractIncome, 350),
+                (TransactionKind::Wages, -60),
+                (TransactionKind::Repairs, -40),
+            ],
+            report.lines
+        );
+        assert_eq!(350, report.income(), "Income");
+        assert_eq!(100, report.expenses(), "Expenses");
+        assert_eq!(250, report.net(), "Net");
+    }
+}
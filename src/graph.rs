@@ -12,6 +12,15 @@ pub struct Graph {
 
 impl Graph {
     pub fn a_star(&self, start: usize, end: usize) -> Vec<Point2<f32>> {
+        self.a_star_with_penalty(start, end, |_, _| 0.0)
+    }
+
+    // Penalty is added to the distance between two nodes, so routes can be steered away from
+    // trouble without being ruled out
+    pub fn a_star_with_penalty<F>(&self, start: usize, end: usize, penalty: F) -> Vec<Point2<f32>>
+    where
+        F: Fn(Point2<f32>, Point2<f32>) -> f32,
+    {
         let mut frontier = PriorityQueue::new();
         frontier.push(start, 0);
         let mut came_from = HashMap::<usize, Option<usize>>::new();
@@ -27,7 +36,9 @@ impl Graph {
             }
 
             for next in self.neighbours(current) {
-                let new_cost = cost_so_far[&current] + self.cost(current, next);
+                let new_cost = cost_so_far[&current]
+                    + self.cost(current, next)
+                    + penalty(self.nodes[current], self.nodes[next]);
                 if !cost_so_far.contains_key(&next) || new_cost < cost_so_far[&next] {
                     cost_so_far.insert(next, new_cost);
                     let priority = new_cost + self.cost(end, next);
@@ -98,4 +109,25 @@ mod tests {
         let route = graph.a_star(0, 1);
         assert_eq!(route, vec![start, Point2::new(2.5, 2.5), end], "Route");
     }
+
+    #[test]
+    fn a_star_with_penalty_avoids_costly_edges() {
+        let start = Point2::new(0.0, 0.0);
+        let end = Point2::new(10.0, 0.0);
+        let detour = Point2::new(5.0, 5.0);
+
+        let graph = Graph {
+            nodes: vec![start, end, detour],
+            edges: vec![Edge(0, 1), Edge(0, 2), Edge(1, 2)],
+        };
+
+        let route = graph.a_star_with_penalty(0, 1, |from, to| {
+            if from.y == 0.0 && to.y == 0.0 {
+                20.0
+            } else {
+                0.0
+            }
+        });
+        assert_eq!(route, vec![start, detour, end], "Route");
+    }
 }
@@ -0,0 +1,277 @@
+use chrono::{Date, DateTime, Datelike, Utc};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+use crate::{calendar::Season, components::ItemType};
+
+const ENGLISH: &str = include_str!("../assets/locale/en.ron");
+
+// Language chosen in config/settings.ron
+#[derive(Debug, Deserialize)]
+pub struct Settings {
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            language: "en".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+enum Message {
+    Text(String),
+    Plural { one: String, other: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct Catalogue {
+    // chrono formats, %B and %b are replaced with the catalogue's month names
+    date: String,
+    date_time: String,
+    months: Vec<String>,
+    short_months: Vec<String>,
+    money: String,
+    thousands_separator: String,
+    list_separator: String,
+    messages: HashMap<String, Message>,
+}
+
+// Every piece of text shown to the player is looked up here
+pub struct Locale {
+    catalogue: Catalogue,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            catalogue: ron::de::from_str(ENGLISH).expect("Failed parsing English catalogue"),
+        }
+    }
+}
+
+impl Locale {
+    // Messages missing from the language's catalogue fall back to English
+    pub fn load(app_root: &Path, language: &str) -> Self {
+        let mut locale = Locale::default();
+        if language == "en" {
+            return locale;
+        }
+
+        let path = app_root.join(format!("assets/locale/{}.ron", language));
+        let catalogue = std::fs::File::open(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|reader| {
+                ron::de::from_reader::<_, Catalogue>(reader).map_err(|error| error.to_string())
+            });
+
+        match catalogue {
+            Ok(mut catalogue) => {
+                for (key, message) in locale.catalogue.messages.drain() {
+                    catalogue.messages.entry(key).or_insert(message);
+                }
+                locale.catalogue = catalogue;
+            }
+            Err(error) => log::warn!(
+                "Could not load {}, using English: {}",
+                path.display(),
+                error
+            ),
+        }
+
+        locale
+    }
+
+    pub fn text(&self, key: &str, params: &[(&str, &dyn Display)]) -> String {
+        match self.catalogue.messages.get(key) {
+            Some(Message::Text(text)) => substitute(text, params),
+            Some(Message::Plural { other, .. }) => substitute(other, params),
+            None => key.to_string(),
+        }
+    }
+
+    // The count is available to the message as {count}
+    pub fn plural(&self, key: &str, count: u32, params: &[(&str, &dyn Display)]) -> String {
+        let mut all_params: Vec<(&str, &dyn Display)> = vec![("count", &count)];
+        all_params.extend_from_slice(params);
+
+        match self.catalogue.messages.get(key) {
+            Some(Message::Plural { one, .. }) if count == 1 => substitute(one, &all_params),
+            Some(Message::Plural { other, .. }) => substitute(other, &all_params),
+            Some(Message::Text(text)) => substitute(text, &all_params),
+            None => key.to_string(),
+        }
+    }
+
+    pub fn money<T: Into<i64>>(&self, amount: T) -> String {
+        let amount = amount.into();
+        let digits = amount.abs().to_string();
+        let grouped = digits
+            .as_bytes()
+            .rchunks(3)
+            .rev()
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .join(&self.catalogue.thousands_separator);
+        let formatted = substitute(&self.catalogue.money, &[("amount", &grouped)]);
+
+        if amount < 0 {
+            format!("-{}", formatted)
+        } else {
+            formatted
+        }
+    }
+
+    pub fn date(&self, date: Date<Utc>) -> String {
+        let format = self.month_names(&self.catalogue.date, date.month0() as usize);
+        date.format(&format).to_string()
+    }
+
+    pub fn date_time(&self, date_time: DateTime<Utc>) -> String {
+        let format = self.month_names(&self.catalogue.date_time, date_time.month0() as usize);
+        date_time.format(&format).to_string()
+    }
+
+    // Month numbered from 1, as chrono does
+    pub fn month(&self, year: i32, month: u32) -> String {
+        let name = &self.catalogue.months[month as usize - 1];
+        self.text("month_of_year", &[("month", name), ("year", &year)])
+    }
+
+    fn month_names(&self, format: &str, month: usize) -> String {
+        format
+            .replace("%B", &self.catalogue.months[month])
+            .replace("%b", &self.catalogue.short_months[month])
+    }
+
+    pub fn season(&self, season: Season) -> String {
+        self.text(&format!("season_{:?}", season).to_lowercase(), &[])
+    }
+
+    pub fn item(&self, item: ItemType) -> String {
+        self.text(&format!("item_{:?}", item).to_lowercase(), &[])
+    }
+
+    pub fn quantity(&self, item: ItemType, amount: u32) -> String {
+        let key = match item {
+            ItemType::Passengers => "quantity_passengers",
+            ItemType::Mail => "quantity_sacks",
+            _ => "quantity_tons",
+        };
+        self.plural(key, amount, &[])
+    }
+
+    pub fn goods(&self, item: ItemType, amount: u32) -> String {
+        match item {
+            ItemType::Passengers => self.plural("goods_passengers", amount, &[]),
+            _ => self.text(
+                "goods_amount",
+                &[
+                    ("quantity", &self.quantity(item, amount)),
+                    ("item", &self.item(item)),
+                ],
+            ),
+        }
+    }
+
+    pub fn goods_list(&self, goods: &HashMap<ItemType, u32>) -> String {
+        goods
+            .iter()
+            .sorted_by_key(|(&item, _)| item)
+            .map(|(item, amount)| self.goods(*item, *amount))
+            .join(&self.catalogue.list_separator)
+    }
+
+    pub fn list(&self, items: &[&str]) -> String {
+        items.join(&self.catalogue.list_separator)
+    }
+}
+
+fn substitute(text: &str, params: &[(&str, &dyn Display)]) -> String {
+    params.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+pub fn load_settings(app_root: &Path) -> Settings {
+    std::fs::File::open(app_root.join("config/settings.ron"))
+        .ok()
+        .and_then(|reader| ron::de::from_reader(reader).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn every_message_parses() {
+        let locale = Locale::default();
+
+        assert_eq!("Rum", locale.item(ItemType::Rum));
+        assert_eq!("Nothing to show", locale.text("log_empty", &[]));
+    }
+
+    #[test]
+    fn unknown_keys_show_the_key() {
+        assert_eq!(
+            "no_such_message",
+            Locale::default().text("no_such_message", &[])
+        );
+    }
+
+    #[test]
+    fn parameters_are_substituted() {
+        let locale = Locale::default();
+
+        assert_eq!(
+            "Docked at London",
+            locale.text("docked_at", &[("port", &"London")])
+        );
+    }
+
+    #[test]
+    fn plurals_follow_the_count() {
+        let locale = Locale::default();
+
+        assert_eq!("1 ton of Sugar", locale.goods(ItemType::Sugar, 1));
+        assert_eq!("5 tons of Sugar", locale.goods(ItemType::Sugar, 5));
+        assert_eq!("1 passenger", locale.goods(ItemType::Passengers, 1));
+        assert_eq!("12 passengers", locale.goods(ItemType::Passengers, 12));
+        assert_eq!("Expires in 1 day", locale.plural("expires_in", 1, &[]));
+    }
+
+    #[test]
+    fn money_is_grouped_and_signed() {
+        let locale = Locale::default();
+
+        assert_eq!("£50", locale.money(50));
+        assert_eq!("£12,500", locale.money(12500));
+        assert_eq!("-£1,000", locale.money(-1000));
+    }
+
+    #[test]
+    fn goods_are_listed_in_item_order() {
+        let locale = Locale::default();
+        let goods = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+            .iter()
+            .cloned()
+            .collect();
+
+        assert_eq!("5 tons of Rum, 10 tons of Sugar", locale.goods_list(&goods));
+    }
+
+    #[test]
+    fn dates_use_catalogue_month_names() {
+        let mut locale = Locale::default();
+        locale.catalogue.months[2] = "Mars".to_string();
+        let date = Utc.ymd(1680, 3, 5);
+
+        assert_eq!(" 5 Mars 1680", locale.date(date));
+        assert_eq!(" 5 Mar 14:30", locale.date_time(date.and_hms(14, 30, 0)));
+        assert_eq!("Mars 1680", locale.month(1680, 3));
+    }
+}
@@ -3,7 +3,7 @@ use amethyst::{
     input::{InputBundle, StringBindings},
     prelude::*,
     renderer::{
-        plugins::{RenderFlat2D, RenderFlat3D, RenderToWindow},
+        plugins::{RenderDebugLines, RenderFlat2D, RenderFlat3D, RenderToWindow},
         types::DefaultBackend,
         RenderingBundle,
     },
@@ -11,19 +11,29 @@ use amethyst::{
     utils::application_root_dir,
 };
 use systems::{
-    AiSystem, ChaseSystem, CollisionSystem, ContractPanelSystemDesc, DestroySystemDesc,
-    DockingSystem, ExpirationSystem, ExpireContractSystem, FulfillContractSystem,
-    GameSpeedSystemDesc, NotificationSystem, PanningSystem, PatrolSystem, PlayerStatusSystemDesc,
-    PlotCourseSystem, PortPanelSystemDesc, SelectPortSystem, SelectShipSystem, ShipPanelSystemDesc,
-    UpdateTimeSystem,
+    AiSystem, CalendarSystem, CargoTransferSystemDesc, ChaseSystem, CollisionSystem,
+    ContractGenerationSystemDesc, ContractPanelSystemDesc, CourseDisplaySystem, CrewSystemDesc,
+    DestroySystemDesc, DockingSystem, EditWaypointsSystem, EventNotificationSystemDesc,
+    ExpirationSystem, ExpireContractSystem, FinanceSystemDesc, FulfillContractSystem,
+    GameSpeedSystemDesc, KeyBindingsSystemDesc, MinimapSystemDesc, NotificationLogSystemDesc,
+    NotificationSystem, PanningSystem, PatrolSystem, PlayerStatusSystemDesc, PlotCourseSystem,
+    PortPanelSystemDesc, PortServicesSystemDesc, SelectPortSystem, SelectShipSystem,
+    ShipPanelSystemDesc, StormDamageSystem, UpdateTimeSystem, WeatherDisplaySystem, WeatherSystem,
+    WorldEventSystemDesc,
 };
 
 mod age_of_sail;
+mod broad_phase;
+mod calendar;
 mod components;
 mod event;
+mod finance;
 mod graph;
+mod locale;
 mod map;
 mod systems;
+mod weather;
+mod widgets;
 
 use crate::age_of_sail::MainState;
 use crate::systems::{AcceptContractSystemDesc, MoveShipsSystem, SelectSystem};
@@ -51,33 +61,78 @@ fn main() -> amethyst::Result<()> {
                 )
                 .with_plugin(RenderUi::default())
                 .with_plugin(RenderFlat2D::default())
-                .with_plugin(RenderFlat3D::default()),
+                .with_plugin(RenderFlat3D::default())
+                .with_plugin(RenderDebugLines::default()),
         )?
         .with(UpdateTimeSystem, "time", &[])
         .with(ExpirationSystem, "expiration", &[])
         .with(ExpireContractSystem, "expired_contract", &[])
-        .with(AiSystem, "ai", &[])
+        .with(CalendarSystem::default(), "calendar", &["time"])
+        .with_system_desc(
+            WorldEventSystemDesc::default(),
+            "world_events",
+            &["calendar"],
+        )
+        .with_system_desc(
+            ContractGenerationSystemDesc::default(),
+            "contract_generation",
+            &["calendar"],
+        )
+        .with_system_desc(CrewSystemDesc::default(), "crew", &["calendar"])
+        .with_system_desc(
+            FinanceSystemDesc::default(),
+            "finance",
+            &["calendar", "crew"],
+        )
+        .with(
+            WeatherSystem::default(),
+            "weather",
+            &["time", "world_events"],
+        )
+        .with(StormDamageSystem::default(), "storm_damage", &["weather"])
+        .with(AiSystem, "ai", &["weather"])
         .with(PatrolSystem, "patrol", &[])
         .with(ChaseSystem, "chase", &[])
-        .with(MoveShipsSystem, "move_ships", &[])
-        .with(PlotCourseSystem, "plot_course", &[])
-        .with(DockingSystem, "docking", &[])
-        .with(SelectSystem::default(), "select", &[])
+        .with(MoveShipsSystem, "move_ships", &["weather"])
+        .with(PlotCourseSystem::default(), "plot_course", &[])
+        .with(DockingSystem, "docking", &["move_ships"])
+        .with_system_desc(
+            CargoTransferSystemDesc::default(),
+            "cargo_transfer",
+            &["docking"],
+        )
+        .with(EditWaypointsSystem::default(), "edit_waypoints", &[])
+        .with(SelectSystem::default(), "select", &["edit_waypoints"])
         .with(SelectPortSystem, "select_port", &[])
         .with(SelectShipSystem, "select_ship", &[])
         .with(CollisionSystem, "collision", &[])
         .with_system_desc(PlayerStatusSystemDesc::default(), "ui_player_status", &[])
         .with_system_desc(GameSpeedSystemDesc::default(), "ui_game_speed", &[])
+        .with_system_desc(
+            EventNotificationSystemDesc::default(),
+            "event_notifications",
+            &[],
+        )
         .with(NotificationSystem::default(), "ui_notification_system", &[])
         .with_system_desc(AcceptContractSystemDesc::default(), "accept_contract", &[])
+        .with_system_desc(PortServicesSystemDesc::default(), "port_services", &[])
         .with_system_desc(DestroySystemDesc::default(), "destroy", &[])
-        .with(FulfillContractSystem, "fulfill_contract", &[])
+        .with(
+            FulfillContractSystem,
+            "fulfill_contract",
+            &["cargo_transfer"],
+        )
         .with_thread_local_desc(PortPanelSystemDesc::default())
         .with_thread_local_desc(ShipPanelSystemDesc::default())
         .with_thread_local_desc(ContractPanelSystemDesc::default())
-        .with_thread_local(PanningSystem);
+        .with_thread_local_desc(KeyBindingsSystemDesc::new(key_bindings_path))
+        .with_thread_local_desc(MinimapSystemDesc::default())
+        .with_thread_local_desc(NotificationLogSystemDesc::default())
+        .with_thread_local(CourseDisplaySystem::default())
+        .with_thread_local(WeatherDisplaySystem)
+        .with_thread_local(PanningSystem::default());
 
-    let mut game = Application::new(resources, MainState, game_data)?;
+    let mut game = Application::new(resources, MainState { app_root }, game_data)?;
     game.run();
 
     Ok(())
@@ -877,11 +877,48 @@ impl Map {
                 }
                 adjusted_corner
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        // Corners are connected wherever a ship can sail straight between them. Corners on the
+        // inside of a bend in the coast get pushed onto land so are left unconnected
+        let corners_on_land = adjusted_corners
+            .iter()
+            .map(|&c| self.on_land(c))
+            .collect::<Vec<_>>();
+
+        let edges = (0..adjusted_corners.len())
+            .flat_map(|a| (a + 1..adjusted_corners.len()).map(move |b| GraphEdge(a, b)))
+            .filter(|&GraphEdge(a, b)| {
+                !corners_on_land[a]
+                    && !corners_on_land[b]
+                    && self
+                        .closest_point_of_line_on_edge(
+                            adjusted_corners[a],
+                            adjusted_corners[b] - adjusted_corners[a],
+                            true,
+                        )
+                        .is_none()
+            })
+            .collect::<Vec<_>>();
 
         (adjusted_corners, edges)
     }
 
+    // Left, right, top and bottom of the area covered by islands
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let points = self.islands.iter().flatten().collect::<Vec<_>>();
+        if points.is_empty() {
+            return None;
+        }
+
+        Some((
+            points.iter().map(|p| p.x).min().unwrap() as f32,
+            points.iter().map(|p| p.x).max().unwrap() as f32,
+            points.iter().map(|p| p.y).max().unwrap() as f32,
+            points.iter().map(|p| p.y).min().unwrap() as f32,
+        ))
+    }
+
     pub fn on_land(&self, point: Point2<f32>) -> bool {
         self.triangulated_islands.iter().any(|island| {
             island.chunks(3).any(|triangle| {
@@ -1082,6 +1119,25 @@ mod tests {
         assert_eq!(2, islands_as_vertices.len(), "number of islands");
     }
 
+    #[test]
+    fn bounds_cover_every_island() {
+        let map = Map::new(vec![
+            vec![
+                Point2::new(50, 100),
+                Point2::new(100, 125),
+                Point2::new(100, 75),
+            ],
+            vec![
+                Point2::new(50, 0),
+                Point2::new(100, 25),
+                Point2::new(150, 1),
+                Point2::new(100, -25),
+            ],
+        ]);
+        assert_eq!(Some((50.0, 150.0, 125.0, -25.0)), map.bounds());
+        assert_eq!(None, Map::default().bounds());
+    }
+
     #[test_case(Point2::new(0.0, 0.0) => false ; "outside of land")]
     #[test_case(Point2::new(75.0, 110.0) => true ; "on smaller island")]
     #[test_case(Point2::new(110.0, 0.0) => true ; "on bigger island")]
@@ -1231,4 +1287,47 @@ mod tests {
         assert_eq!(4, graph.nodes.len(), "Graph nodes");
         assert_eq!(4, graph.edges.len(), "Graph edges");
     }
+
+    #[test]
+    fn nodes_and_edges_connected_connects_corners_in_sight_of_each_other() {
+        let map = Map::new(vec![vec![
+            Point2::new(0, 0),
+            Point2::new(100, 0),
+            Point2::new(100, 100),
+            Point2::new(50, 50),
+            Point2::new(0, 100),
+        ]]);
+
+        let graph = map.nodes_and_edges_connected(vec![]);
+        let edges = HashSet::<GraphEdge>::from_iter(graph.edges.into_iter());
+
+        assert!(
+            edges.contains(&GraphEdge(2, 4)),
+            "Edges contains edge across the bend in the coast"
+        );
+        assert!(
+            !edges.contains(&GraphEdge(0, 2)),
+            "Edges does not contain edge across land"
+        );
+    }
+
+    #[test]
+    fn nodes_and_edges_connected_does_not_connect_corners_pushed_onto_land() {
+        // No two corners at the same height, which the triangulation can't handle
+        let map = Map::new(vec![vec![
+            Point2::new(0, 0),
+            Point2::new(100, 10),
+            Point2::new(95, 100),
+            Point2::new(50, 50),
+            Point2::new(5, 90),
+        ]]);
+
+        let graph = map.nodes_and_edges_connected(vec![]);
+
+        assert!(map.on_land(graph.nodes[3]), "Corner pushed onto land");
+        assert!(
+            graph.edges.iter().all(|edge| edge.0 != 3 && edge.1 != 3),
+            "No edges to the corner on land"
+        );
+    }
 }
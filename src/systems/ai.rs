@@ -1,9 +1,12 @@
 use amethyst::{
     core::{alga::linear::EuclideanSpace, math::Point2, Transform},
-    ecs::{Entities, Entity, Join, ReadStorage, System, WriteStorage},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
 };
 
-use crate::components::{Ai, Ship, StateQuery};
+use crate::{
+    components::{Ai, Ship, StateQuery},
+    weather::Weather,
+};
 
 // Only handle transitions
 pub struct AiSystem;
@@ -14,17 +17,20 @@ impl<'s> System<'s> for AiSystem {
         ReadStorage<'s, Transform>,
         ReadStorage<'s, Ship>,
         WriteStorage<'s, Ai>,
+        Read<'s, Weather>,
     );
 
-    fn run(&mut self, (entities, locals, ships, mut ais): Self::SystemData) {
+    fn run(&mut self, (entities, locals, ships, mut ais, weather): Self::SystemData) {
         for (e, ai) in (&entities, &mut ais).join() {
             let current_state = ai.current_state();
             let mut next_state = ai.current_state_index;
             for (query, s) in current_state.transitions.iter() {
                 let transition = match query {
-                    StateQuery::TargetNearby(d) => target_nearby(e, &entities, &locals, &ships, *d),
+                    StateQuery::TargetNearby(d) => {
+                        target_nearby(e, &entities, &locals, &ships, &weather, *d)
+                    }
                     StateQuery::TargetNotNearby(d) => {
-                        !target_nearby(e, &entities, &locals, &ships, *d)
+                        !target_nearby(e, &entities, &locals, &ships, &weather, *d)
                     }
                 };
 
@@ -44,6 +50,7 @@ fn target_nearby<'a>(
     entities: &Entities<'a>,
     locals: &ReadStorage<'a, Transform>,
     ships: &ReadStorage<'a, Ship>,
+    weather: &Weather,
     distance: u32,
 ) -> bool {
     let e_transform = locals.get(e).unwrap();
@@ -52,7 +59,11 @@ fn target_nearby<'a>(
     for (other_e, local, _) in (entities, locals, ships).join() {
         if other_e != e {
             let other_e_location = Point2::new(local.translation().x, local.translation().y);
-            if other_e_location.distance(&e_location) < distance as f32 {
+            // Fog around either ship cuts how far apart they can be seen
+            let sight = weather
+                .sight_multiplier(e_location)
+                .min(weather.sight_multiplier(other_e_location));
+            if other_e_location.distance(&e_location) < distance as f32 * sight {
                 return true;
             }
         }
@@ -63,8 +74,11 @@ fn target_nearby<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::{Action, AiState};
-    use amethyst::{prelude::*, Result};
+    use crate::{
+        components::{Action, AiState},
+        weather::{WeatherCell, WeatherKind},
+    };
+    use amethyst::{core::math::Vector2, prelude::*, Result};
     use amethyst_test::prelude::*;
     use std::collections::HashMap;
 
@@ -121,6 +135,68 @@ mod tests {
             .run()
     }
 
+    #[test]
+    fn ai_does_not_see_target_hidden_in_fog() -> Result<()> {
+        const ORIGINAL_STATE_INDEX: usize = 0;
+
+        AmethystApplication::blank()
+            .with_system(AiSystem, "ai", &[])
+            .with_effect(|world| {
+                world.insert(Weather {
+                    cells: vec![WeatherCell {
+                        kind: WeatherKind::Fog,
+                        centre: Point2::new(5.0, 5.0),
+                        radius: 3.0,
+                        velocity: Vector2::new(0.0, 0.0),
+                        expires: f64::MAX,
+                    }],
+                });
+
+                let mut target_transform = Transform::default();
+                target_transform.set_translation_xyz(5.0, 5.0, 0.0);
+                world
+                    .create_entity()
+                    .with(target_transform)
+                    .with(Ship { base_speed: 1.0 })
+                    .build();
+
+                let mut ai_transform = Transform::default();
+                ai_transform.set_translation_xyz(2.0, 0.0, 0.0);
+                let ai_entity = world
+                    .create_entity()
+                    .with(Ai {
+                        states: vec![
+                            AiState {
+                                transitions: [(StateQuery::TargetNearby(10), 1)]
+                                    .iter()
+                                    .cloned()
+                                    .collect(),
+                                action: Action::Patrol,
+                            },
+                            AiState {
+                                transitions: HashMap::new(),
+                                action: Action::Chase,
+                            },
+                        ],
+                        current_state_index: ORIGINAL_STATE_INDEX,
+                        previous_state_index: 0,
+                    })
+                    .with(ai_transform)
+                    .build();
+
+                world.insert(EffectReturn(ai_entity));
+            })
+            .with_assertion(|world| {
+                let ai_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let ai_storage = world.read_storage::<Ai>();
+                let ai = ai_storage
+                    .get(ai_entity)
+                    .expect("Entity should have an `Ai` component.");
+                assert_eq!(ORIGINAL_STATE_INDEX, ai.current_state_index);
+            })
+            .run()
+    }
+
     #[test]
     fn ai_transitions_to_correct_state_when_no_target_nearby() -> Result<()> {
         const ORIGINAL_STATE_INDEX: usize = 0;
@@ -0,0 +1,234 @@
+use amethyst::{
+    ecs::{Read, System, SystemData, World, Write, WriteStorage},
+    prelude::SystemDesc,
+    shrev::{EventChannel, ReaderId},
+};
+use chrono::Utc;
+
+use crate::{
+    age_of_sail::Date,
+    calendar::{Calendar, Season},
+    components::{contract::TRADE_GOODS, ItemType, Market},
+    event::CalendarEvent,
+};
+
+// Multipliers on market demand while a fair or harvest is on
+const TRADE_FAIR_DEMAND: f32 = 1.25;
+const HARVEST_DEMAND: f32 = 0.7;
+
+// Sends the calendar's events for each day as it passes
+#[derive(Default)]
+pub struct CalendarSystem {
+    last_date: Option<chrono::Date<Utc>>,
+}
+
+impl<'s> System<'s> for CalendarSystem {
+    type SystemData = (
+        Read<'s, Date>,
+        Read<'s, Calendar>,
+        Write<'s, EventChannel<CalendarEvent>>,
+    );
+
+    fn run(&mut self, (date, calendar, mut channel): Self::SystemData) {
+        let today = date.current_date();
+
+        // Days skipped over at high speed still get their events
+        let mut day = match self.last_date {
+            Some(last_date) if last_date >= today => return,
+            Some(last_date) => last_date.succ(),
+            None => today,
+        };
+        self.last_date = Some(today);
+
+        while day <= today {
+            let season = Season::of(day);
+            if season != Season::of(day.pred()) {
+                channel.single_write(CalendarEvent::SeasonBegins(season));
+            }
+            channel.iter_write(calendar.events_on(day));
+            day = day.succ();
+        }
+    }
+}
+
+// Applies what calendar events do to the world
+pub struct WorldEventSystem {
+    reader_id: ReaderId<CalendarEvent>,
+}
+
+impl WorldEventSystem {
+    fn new(reader_id: ReaderId<CalendarEvent>) -> Self {
+        WorldEventSystem { reader_id }
+    }
+}
+
+impl<'s> System<'s> for WorldEventSystem {
+    type SystemData = (
+        WriteStorage<'s, Market>,
+        Write<'s, Calendar>,
+        Read<'s, EventChannel<CalendarEvent>>,
+    );
+
+    fn run(&mut self, (mut markets, mut calendar, channel): Self::SystemData) {
+        for event in channel.read(&mut self.reader_id) {
+            match event {
+                CalendarEvent::TradeFair { port, open } => {
+                    if let Some(market) = markets.get_mut(*port) {
+                        for item in TRADE_GOODS.iter() {
+                            scale_demand(market, *item, TRADE_FAIR_DEMAND, *open);
+                        }
+                    }
+                }
+                CalendarEvent::Harvest { port, item, begins } => {
+                    if let Some(market) = markets.get_mut(*port) {
+                        scale_demand(market, *item, HARVEST_DEMAND, *begins);
+                    }
+                }
+                CalendarEvent::StormSeason { begins } => calendar.storm_season = *begins,
+                CalendarEvent::SeasonBegins(_)
+                | CalendarEvent::FleetSailing { .. }
+                | CalendarEvent::Payday
+                | CalendarEvent::MonthBegins => (),
+            }
+        }
+    }
+}
+
+// Undone by dividing again when the event ends
+fn scale_demand(market: &mut Market, item: ItemType, factor: f32, starting: bool) {
+    let demand = market.demand.entry(item).or_insert(1.0);
+    if starting {
+        *demand *= factor;
+    } else {
+        *demand /= factor;
+    }
+}
+
+pub struct WorldEventSystemDesc;
+
+impl Default for WorldEventSystemDesc {
+    fn default() -> Self {
+        WorldEventSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, WorldEventSystem> for WorldEventSystemDesc {
+    fn build(self, world: &mut World) -> WorldEventSystem {
+        <WorldEventSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<CalendarEvent>>()
+            .register_reader();
+
+        WorldEventSystem::new(reader_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{age_of_sail::SECONDS_IN_DAY, calendar::Recurrence};
+    use amethyst::{ecs::Entity, prelude::*, Result};
+    use amethyst_test::prelude::*;
+
+    #[test]
+    fn scheduled_events_and_new_seasons_are_sent_on_the_day() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CalendarSystem::default(), "calendar", &[])
+            .with_effect(|world| {
+                let mut calendar = Calendar::default();
+                calendar.schedule(
+                    Recurrence::Yearly { month: 3, day: 1 },
+                    CalendarEvent::StormSeason { begins: false },
+                );
+                calendar.schedule(
+                    Recurrence::Yearly { month: 3, day: 2 },
+                    CalendarEvent::StormSeason { begins: true },
+                );
+                world.insert(calendar);
+
+                // 1 March 1680
+                world.insert(Date {
+                    time_elapsed: 60.0 * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                let channel = world.fetch_mut::<EventChannel<CalendarEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CalendarEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![
+                        CalendarEvent::SeasonBegins(Season::Spring),
+                        CalendarEvent::StormSeason { begins: false },
+                    ],
+                    events
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn trade_fair_raises_demand_until_it_closes() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(WorldEventSystemDesc, "world_events", &[])
+            .with_effect(|world| {
+                let port = world
+                    .create_entity()
+                    .with(Market {
+                        size: 1,
+                        demand: [(ItemType::Rum, 2.0)].iter().cloned().collect(),
+                    })
+                    .build();
+
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::TradeFair { port, open: true });
+
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let markets = world.read_storage::<Market>();
+                let market = markets.get(port).unwrap();
+                assert_eq!(2.5, market.demand_for(&ItemType::Rum), "Rum demand");
+                assert_eq!(1.25, market.demand_for(&ItemType::Sugar), "Sugar demand");
+            })
+            .with_effect(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::TradeFair { port, open: false });
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let markets = world.read_storage::<Market>();
+                let market = markets.get(port).unwrap();
+                assert_eq!(2.0, market.demand_for(&ItemType::Rum), "Rum demand");
+                assert_eq!(1.0, market.demand_for(&ItemType::Sugar), "Sugar demand");
+            })
+            .run()
+    }
+
+    #[test]
+    fn storm_season_is_tracked() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(WorldEventSystemDesc, "world_events", &[])
+            .with_effect(|world| {
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::StormSeason { begins: true });
+            })
+            .with_assertion(|world| {
+                assert!(world.read_resource::<Calendar>().storm_season);
+            })
+            .run()
+    }
+}
@@ -1,49 +1,213 @@
 use amethyst::{
-    core::{Time, Transform},
-    ecs::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    core::{
+        math::{Point2, Vector2},
+        Time, Transform,
+    },
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
     input::{InputHandler, StringBindings},
     renderer::Camera,
     window::ScreenDimensions,
 };
 
+use crate::{
+    age_of_sail::{point_mouse_to_world, sea_bounds, CameraView, WORLD_HEIGHT, WORLD_WIDTH},
+    components::{Selected, Ship},
+    map::Map,
+};
+
 const CAMERA_SPEED: f32 = 30.0;
 const PANNING_REGION_PIXELS: f32 = 15.0;
+// Each notch of the scroll wheel zooms by this factor
+const ZOOM_STEP: f32 = 1.2;
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 2.0;
 
-pub struct PanningSystem;
+#[derive(Default)]
+pub struct PanningSystem {
+    follow_key_down: bool,
+}
 
 impl<'s> System<'s> for PanningSystem {
     type SystemData = (
-        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Camera>,
         WriteStorage<'s, Transform>,
+        ReadStorage<'s, Ship>,
+        ReadStorage<'s, Selected>,
         Read<'s, InputHandler<StringBindings>>,
         ReadExpect<'s, ScreenDimensions>,
         Read<'s, Time>,
+        Read<'s, Map>,
+        Write<'s, CameraView>,
     );
 
-    fn run(&mut self, (cameras, mut locals, input, screen_dimensions, time): Self::SystemData) {
-        for (_, local) in (&cameras, &mut locals).join() {
-            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
-                let camera_pixel_shift = CAMERA_SPEED * time.delta_real_seconds();
+    fn run(
+        &mut self,
+        (
+            mut cameras,
+            mut locals,
+            ships,
+            selecteds,
+            input,
+            screen_dimensions,
+            time,
+            map,
+            mut camera_view,
+        ): Self::SystemData,
+    ) {
+        let follow_key_down = input.action_is_down("follow").unwrap_or(false);
+        if follow_key_down && !self.follow_key_down {
+            camera_view.following = !camera_view.following;
+        }
+        self.follow_key_down = follow_key_down;
+
+        let selected_locations = (&ships, &selecteds, &locals)
+            .join()
+            .map(|(_, _, local)| Point2::new(local.translation().x, local.translation().y))
+            .collect::<Vec<_>>();
+
+        let zoom = camera_view.zoom;
+        let new_zoom = (zoom * ZOOM_STEP.powf(-input.axis_value("zoom").unwrap_or(0.0)))
+            .max(MIN_ZOOM)
+            .min(MAX_ZOOM);
+        camera_view.zoom = new_zoom;
+
+        for (camera, local) in (&mut cameras, &mut locals).join() {
+            let mut position = Point2::new(local.translation().x, local.translation().y);
+
+            if new_zoom != zoom {
+                *camera = Camera::standard_2d(WORLD_WIDTH * new_zoom, WORLD_HEIGHT * new_zoom);
+
+                // Keep the point under the cursor where it is on screen
+                if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                    let focus = point_mouse_to_world(
+                        mouse_x,
+                        mouse_y,
+                        &*screen_dimensions,
+                        local.translation(),
+                        zoom,
+                    );
+                    position = zoom_towards(position, focus, new_zoom / zoom);
+                }
+            }
+
+            let (mut shift_x, mut shift_y) = (
+                input.axis_value("pan_horizontal").unwrap_or(0.0),
+                input.axis_value("pan_vertical").unwrap_or(0.0),
+            );
 
-                let shift_x = if mouse_x < PANNING_REGION_PIXELS {
-                    -camera_pixel_shift
+            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                if mouse_x < PANNING_REGION_PIXELS {
+                    shift_x -= 1.0;
                 } else if mouse_x > screen_dimensions.width() - PANNING_REGION_PIXELS {
-                    camera_pixel_shift
-                } else {
-                    0.0
-                };
+                    shift_x += 1.0;
+                }
 
-                let shift_y = if mouse_y < PANNING_REGION_PIXELS {
-                    camera_pixel_shift
+                if mouse_y < PANNING_REGION_PIXELS {
+                    shift_y += 1.0;
                 } else if mouse_y > screen_dimensions.height() - PANNING_REGION_PIXELS {
-                    -camera_pixel_shift
-                } else {
-                    0.0
-                };
+                    shift_y -= 1.0;
+                }
+            }
+
+            // Panning by hand stops following the selected ships
+            if shift_x != 0.0 || shift_y != 0.0 {
+                camera_view.following = false;
+            }
+
+            let camera_pixel_shift = CAMERA_SPEED * new_zoom * time.delta_real_seconds();
+            position.x += shift_x.max(-1.0).min(1.0) * camera_pixel_shift;
+            position.y += shift_y.max(-1.0).min(1.0) * camera_pixel_shift;
 
-                local.prepend_translation_x(shift_x);
-                local.prepend_translation_y(shift_y);
+            if camera_view.following && !selected_locations.is_empty() {
+                position = Point2::from(
+                    selected_locations
+                        .iter()
+                        .fold(Vector2::zeros(), |sum, location| sum + location.coords)
+                        / selected_locations.len() as f32,
+                );
             }
+
+            if let Some(bounds) = sea_bounds(&map) {
+                position = clamp_camera(
+                    position,
+                    (WORLD_WIDTH * new_zoom, WORLD_HEIGHT * new_zoom),
+                    bounds,
+                );
+            }
+
+            local.set_translation_x(position.x);
+            local.set_translation_y(position.y);
         }
     }
 }
+
+// Moves the camera so the focus stays at the same place on screen after zooming
+fn zoom_towards(position: Point2<f32>, focus: Point2<f32>, zoom_ratio: f32) -> Point2<f32> {
+    focus + (position - focus) * zoom_ratio
+}
+
+// Keeps the view within the bounds, centring it if the view is larger than them
+fn clamp_camera(
+    position: Point2<f32>,
+    (view_width, view_height): (f32, f32),
+    (left, right, top, bottom): (f32, f32, f32, f32),
+) -> Point2<f32> {
+    let clamp_axis = |value: f32, min: f32, max: f32, view: f32| {
+        let (min, max) = (min + view / 2.0, max - view / 2.0);
+        if min > max {
+            (min + max) / 2.0
+        } else {
+            value.max(min).min(max)
+        }
+    };
+
+    Point2::new(
+        clamp_axis(position.x, left, right, view_width),
+        clamp_axis(position.y, bottom, top, view_height),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zooming_keeps_the_focus_in_place() {
+        let position = zoom_towards(Point2::new(100.0, 100.0), Point2::new(120.0, 90.0), 0.5);
+
+        assert_eq!(Point2::new(110.0, 95.0), position);
+    }
+
+    #[test]
+    fn camera_inside_bounds_is_not_moved() {
+        let position = clamp_camera(
+            Point2::new(200.0, 150.0),
+            (400.0, 300.0),
+            (-130.0, 420.0, 700.0, -110.0),
+        );
+
+        assert_eq!(Point2::new(200.0, 150.0), position);
+    }
+
+    #[test]
+    fn camera_is_kept_within_bounds() {
+        let position = clamp_camera(
+            Point2::new(-500.0, 900.0),
+            (400.0, 300.0),
+            (-130.0, 420.0, 700.0, -110.0),
+        );
+
+        assert_eq!(Point2::new(70.0, 550.0), position);
+    }
+
+    #[test]
+    fn view_larger_than_bounds_is_centred() {
+        let position = clamp_camera(
+            Point2::new(0.0, 0.0),
+            (800.0, 300.0),
+            (-130.0, 420.0, 700.0, -110.0),
+        );
+
+        assert_eq!(145.0, position.x);
+    }
+}
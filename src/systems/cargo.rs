@@ -0,0 +1,637 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    ecs::{
+        Entities, Entity, Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage,
+    },
+    prelude::SystemDesc,
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::{
+    age_of_sail::Date,
+    components::{
+        Cargo, CargoTransfer, Contract, ContractKind, Controllable, Docked, ItemType, OwnedBy,
+    },
+    event::{GameEvent, UiUpdateEvent, UndockEvent},
+};
+
+// Game seconds to load or unload one ton, passenger or sack of mail
+pub const SECONDS_TO_HANDLE_UNIT: f64 = 1800.0;
+
+pub struct CargoTransferSystem {
+    reader_id: ReaderId<UndockEvent>,
+}
+
+impl CargoTransferSystem {
+    fn new(reader_id: ReaderId<UndockEvent>) -> Self {
+        CargoTransferSystem { reader_id }
+    }
+}
+
+impl<'s> System<'s> for CargoTransferSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Docked>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, OwnedBy>,
+        WriteStorage<'s, Contract>,
+        WriteStorage<'s, Cargo>,
+        WriteStorage<'s, CargoTransfer>,
+        Read<'s, Date>,
+        Read<'s, EventChannel<UndockEvent>>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            dockeds,
+            controllables,
+            owned_bys,
+            mut contracts,
+            mut cargos,
+            mut cargo_transfers,
+            date,
+            undock_channel,
+            mut game_events,
+            mut channel,
+        ): Self::SystemData,
+    ) {
+        // Leaving port abandons any loading or unloading
+        for event in undock_channel.read(&mut self.reader_id) {
+            if let Some(transfer) = cargo_transfers.remove(event.ship) {
+                if transfer.complete_at > date.time_elapsed {
+                    game_events.single_write(GameEvent::TransferInterrupted {
+                        ship: event.ship,
+                        port: event.port,
+                        unloading: transfer.unloading,
+                    });
+                    channel.single_write(UiUpdateEvent::Journal);
+                }
+            }
+        }
+
+        // Only the player's ships carry goods for contracts. This goes by who is docked rather
+        // than by DockEvent, as a contract accepted while the ship is already in port still
+        // needs loading and transfers finish long after the ship docked
+        for (ship, docked, _) in (&entities, &dockeds, &controllables).join() {
+            let port = docked.port;
+
+            if let Some(transfer) = cargo_transfers.get(ship) {
+                if transfer.complete_at > date.time_elapsed {
+                    continue;
+                }
+
+                if transfer.unloading {
+                    // FulfillContractSystem hands the unloaded goods over. Keep the ship
+                    // free to do so until nothing is left for this port
+                    if goods_to_unload(&entities, &contracts, &owned_bys, &cargos, ship, port)
+                        .1
+                        .is_empty()
+                    {
+                        cargo_transfers.remove(ship);
+                    }
+                    continue;
+                }
+
+                let transfer = cargo_transfers.remove(ship).unwrap();
+
+                // Contracts may have expired while loading
+                let mut loaded = HashMap::new();
+                for contract in &transfer.contracts {
+                    if let Some(contract) = contracts.get_mut(*contract) {
+                        for (item, amount) in contract.reserved.drain() {
+                            *loaded.entry(item).or_insert(0) += amount;
+                        }
+                    }
+                }
+
+                if let Some(cargo) = cargos.get_mut(ship) {
+                    for (item, amount) in &loaded {
+                        *cargo.items.entry(*item).or_insert(0) += amount;
+                    }
+                }
+
+                if !loaded.is_empty() {
+                    game_events.single_write(GameEvent::CargoLoaded {
+                        ship,
+                        port,
+                        goods: loaded,
+                    });
+                    channel.single_write(UiUpdateEvent::Journal);
+                }
+                continue;
+            }
+
+            let (unloading_contracts, unloading_goods) =
+                goods_to_unload(&entities, &contracts, &owned_bys, &cargos, ship, port);
+
+            if !unloading_goods.is_empty() {
+                cargo_transfers
+                    .insert(
+                        ship,
+                        CargoTransfer {
+                            contracts: unloading_contracts,
+                            complete_at: date.time_elapsed + handling_time(&unloading_goods),
+                            unloading: true,
+                        },
+                    )
+                    .unwrap();
+                channel.single_write(UiUpdateEvent::Journal);
+                continue;
+            }
+
+            // Claim any goods waiting here that are reserved for this ship or no ship in particular
+            let mut loading_contracts = Vec::new();
+            let mut loading_goods = HashMap::new();
+            for (e, contract, _) in (&entities, &mut contracts, !&owned_bys).join() {
+                if contract.origin == Some(port)
+                    && !contract.reserved.is_empty()
+                    && contract.carrier.map_or(true, |carrier| carrier == ship)
+                {
+                    contract.carrier = Some(ship);
+                    loading_contracts.push(e);
+                    for (item, amount) in &contract.reserved {
+                        *loading_goods.entry(*item).or_insert(0) += amount;
+                    }
+                }
+            }
+
+            if !loading_goods.is_empty() {
+                cargo_transfers
+                    .insert(
+                        ship,
+                        CargoTransfer {
+                            contracts: loading_contracts,
+                            complete_at: date.time_elapsed + handling_time(&loading_goods),
+                            unloading: false,
+                        },
+                    )
+                    .unwrap();
+                channel.single_write(UiUpdateEvent::Journal);
+            }
+        }
+    }
+}
+
+pub struct CargoTransferSystemDesc;
+
+impl Default for CargoTransferSystemDesc {
+    fn default() -> Self {
+        CargoTransferSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, CargoTransferSystem> for CargoTransferSystemDesc {
+    fn build(self, world: &mut World) -> CargoTransferSystem {
+        <CargoTransferSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<UndockEvent>>()
+            .register_reader();
+
+        CargoTransferSystem::new(reader_id)
+    }
+}
+
+// Goods aboard the ship for the player's contracts delivering to the port
+fn goods_to_unload<'a>(
+    entities: &Entities<'a>,
+    contracts: &WriteStorage<'a, Contract>,
+    owned_bys: &ReadStorage<'a, OwnedBy>,
+    cargos: &WriteStorage<'a, Cargo>,
+    ship: Entity,
+    port: Entity,
+) -> (Vec<Entity>, HashMap<ItemType, u32>) {
+    let mut held = cargos
+        .get(ship)
+        .map_or(HashMap::new(), |cargo| cargo.items.clone());
+    let mut unloading_contracts = Vec::new();
+    let mut unloading_goods = HashMap::new();
+
+    for (e, contract, _) in (entities, contracts, !owned_bys).join() {
+        if contract.carrier != Some(ship) {
+            continue;
+        }
+
+        // As FulfillContractSystem hands them over, bulk drops and smuggled goods only go
+        // ashore whole while other contracts take whatever part of their goods is aboard
+        let (needed, whole) = match &contract.kind {
+            ContractKind::Bulk { drops } => (
+                drops
+                    .iter()
+                    .filter(|d| !d.delivered && d.destination == port)
+                    .map(|d| d.goods.clone())
+                    .collect::<Vec<_>>(),
+                true,
+            ),
+            ContractKind::Smuggling { .. } if contract.destination == port => {
+                (vec![contract.remaining_goods()], true)
+            }
+            _ if contract.destination == port => (vec![contract.remaining_goods()], false),
+            _ => (Vec::new(), false),
+        };
+
+        let mut unloading_any = false;
+        for goods in needed {
+            let aboard = goods
+                .iter()
+                .all(|(item, amount)| held.get(item) >= Some(amount));
+            if whole && !aboard {
+                continue;
+            }
+
+            for (item, amount) in goods {
+                if let Some(amount_held) = held.get_mut(&item) {
+                    let amount_unloaded = (*amount_held).min(amount);
+                    if amount_unloaded > 0 {
+                        *amount_held -= amount_unloaded;
+                        *unloading_goods.entry(item).or_insert(0) += amount_unloaded;
+                        unloading_any = true;
+                    }
+                }
+            }
+        }
+
+        if unloading_any {
+            unloading_contracts.push(e);
+        }
+    }
+
+    (unloading_contracts, unloading_goods)
+}
+
+fn handling_time(goods: &HashMap<ItemType, u32>) -> f64 {
+    goods.values().sum::<u32>() as f64 * SECONDS_TO_HANDLE_UNIT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::DropOff;
+    use amethyst::{core::WithNamed, ecs::Entity, prelude::*, Result};
+    use amethyst_test::prelude::*;
+
+    #[test]
+    fn carrier_docked_at_origin_starts_loading_reserved_goods() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("London").build();
+                let destination = world.create_entity().build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Controllable)
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .build();
+
+                let goods: HashMap<ItemType, u32> =
+                    [(ItemType::Sugar, 10)].iter().cloned().collect();
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        origin: Some(port),
+                        reserved: goods.clone(),
+                        ..Contract::new(100, destination, goods)
+                    })
+                    .build();
+
+                world.insert(EffectReturn((ship, contract)));
+            })
+            .with_assertion(|world| {
+                let (ship, contract) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                let transfer = cargo_transfers.get(ship).unwrap();
+                assert!(!transfer.unloading, "Loading");
+                assert_eq!(vec![contract], transfer.contracts, "Contracts");
+                assert_eq!(
+                    10.0 * SECONDS_TO_HANDLE_UNIT,
+                    transfer.complete_at,
+                    "Complete at"
+                );
+
+                let cargos = world.read_storage::<Cargo>();
+                assert!(cargos.get(ship).unwrap().items.is_empty(), "Cargo on ship");
+
+                let contracts = world.read_storage::<Contract>();
+                assert_eq!(
+                    Some(ship),
+                    contracts.get(contract).unwrap().carrier,
+                    "Carrier"
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn finished_loading_moves_reserved_goods_onto_ship() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("London").build();
+                let destination = world.create_entity().build();
+
+                let goods: HashMap<ItemType, u32> =
+                    [(ItemType::Sugar, 10)].iter().cloned().collect();
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        origin: Some(port),
+                        reserved: goods.clone(),
+                        ..Contract::new(100, destination, goods)
+                    })
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .named("Mary Rose")
+                    .with(Controllable)
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .with(CargoTransfer {
+                        contracts: vec![contract],
+                        unloading: false,
+                        complete_at: 0.0,
+                    })
+                    .build();
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                world.insert(EffectReturn((port, ship, contract)));
+            })
+            .with_assertion(|world| {
+                let (port, ship, contract) = world
+                    .read_resource::<EffectReturn<(Entity, Entity, Entity)>>()
+                    .0;
+
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    10,
+                    cargos.get(ship).unwrap().items[&ItemType::Sugar],
+                    "Sugar on ship"
+                );
+
+                let contracts = world.read_storage::<Contract>();
+                assert!(
+                    contracts.get(contract).unwrap().reserved.is_empty(),
+                    "Reserved goods"
+                );
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                assert!(
+                    cargo_transfers.get(ship).is_none(),
+                    "Cargo transfer finished"
+                );
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![GameEvent::CargoLoaded {
+                        ship,
+                        port,
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                    }],
+                    events
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn reserved_goods_not_loaded_onto_other_ships() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().build();
+                let destination = world.create_entity().build();
+
+                let carrier = world
+                    .create_entity()
+                    .with(Controllable)
+                    .with(Cargo::default())
+                    .build();
+
+                let other_player_ship = world
+                    .create_entity()
+                    .with(Controllable)
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .build();
+
+                let pirate = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .build();
+
+                let goods: HashMap<ItemType, u32> =
+                    [(ItemType::Sugar, 10)].iter().cloned().collect();
+                world
+                    .create_entity()
+                    .with(Contract {
+                        origin: Some(port),
+                        carrier: Some(carrier),
+                        reserved: goods.clone(),
+                        ..Contract::new(100, destination, goods)
+                    })
+                    .build();
+
+                world.insert(EffectReturn((other_player_ship, pirate)));
+            })
+            .with_assertion(|world| {
+                let (other_player_ship, pirate) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                assert!(
+                    cargo_transfers.get(other_player_ship).is_none(),
+                    "Other player ship not loading"
+                );
+                assert!(cargo_transfers.get(pirate).is_none(), "Pirate not loading");
+            })
+            .run()
+    }
+
+    #[test]
+    fn carrier_docked_at_destination_starts_unloading() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let destination = world.create_entity().build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Controllable)
+                    .with(Docked { port: destination })
+                    .with(Cargo {
+                        items: [(ItemType::Sugar, 4), (ItemType::Rum, 5)]
+                            .iter()
+                            .cloned()
+                            .collect(),
+                    })
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(
+                            100,
+                            destination,
+                            [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        )
+                    })
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                let transfer = cargo_transfers.get(ship).unwrap();
+                assert!(transfer.unloading, "Unloading");
+                assert_eq!(
+                    4.0 * SECONDS_TO_HANDLE_UNIT,
+                    transfer.complete_at,
+                    "Complete at"
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn bulk_drop_only_partly_aboard_is_not_unloaded() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let destination = world.create_entity().build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Controllable)
+                    .with(Docked { port: destination })
+                    .with(Cargo {
+                        items: [(ItemType::Sugar, 4), (ItemType::Rum, 5)]
+                            .iter()
+                            .cloned()
+                            .collect(),
+                    })
+                    .build();
+
+                let drops = vec![
+                    DropOff {
+                        destination,
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        payment: 100,
+                        delivered: false,
+                    },
+                    DropOff {
+                        destination,
+                        goods: [(ItemType::Rum, 5)].iter().cloned().collect(),
+                        payment: 50,
+                        delivered: false,
+                    },
+                ];
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        kind: ContractKind::Bulk { drops },
+                        ..Contract::new(
+                            150,
+                            destination,
+                            [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+                                .iter()
+                                .cloned()
+                                .collect(),
+                        )
+                    })
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                let transfer = cargo_transfers.get(ship).unwrap();
+                assert!(transfer.unloading, "Unloading");
+                assert_eq!(
+                    5.0 * SECONDS_TO_HANDLE_UNIT,
+                    transfer.complete_at,
+                    "Only the rum drop is unloaded"
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn leaving_port_abandons_loading() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CargoTransferSystemDesc, "cargo_transfer", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("London").build();
+
+                let ship = world
+                    .create_entity()
+                    .named("Mary Rose")
+                    .with(Controllable)
+                    .with(Cargo::default())
+                    .with(CargoTransfer {
+                        contracts: Vec::new(),
+                        unloading: false,
+                        complete_at: 1000.0,
+                    })
+                    .build();
+
+                world
+                    .fetch_mut::<EventChannel<UndockEvent>>()
+                    .single_write(UndockEvent { ship, port });
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                world.insert(EffectReturn((port, ship)));
+            })
+            .with_assertion(|world| {
+                let (port, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let cargo_transfers = world.read_storage::<CargoTransfer>();
+                assert!(
+                    cargo_transfers.get(ship).is_none(),
+                    "Cargo transfer abandoned"
+                );
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![GameEvent::TransferInterrupted {
+                        ship,
+                        port,
+                        unloading: false,
+                    }],
+                    events
+                );
+            })
+            .run()
+    }
+}
@@ -1,44 +1,141 @@
 use std::collections::HashSet;
 
 use amethyst::{
-    core::Transform,
-    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, World, Write},
+    core::{
+        math::{Point2, Vector2},
+        Transform,
+    },
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, SystemData, World, Write},
     prelude::SystemDesc,
     shrev::{EventChannel, ReaderId},
 };
 
 use crate::{
-    age_of_sail::{point_in_rect, Notifications},
-    components::{bounding_box::BoundingBox, Pirate, Ship},
-    event::CollisionEvent,
+    age_of_sail::distance_to_segment,
+    broad_phase::sweep_and_prune,
+    components::{bounding_box::BoundingBox, CollisionLayer, CollisionShape, Crew, Pirate, Ship},
+    event::{CollisionEvent, GameEvent},
 };
 
+pub struct CollisionSettings {
+    // Pairs of layers that collide with each other, in either order
+    pub layers: HashSet<(CollisionLayer, CollisionLayer)>,
+    // Send an event for each entity in a colliding pair instead of one per pair
+    pub symmetric: bool,
+}
+
+impl Default for CollisionSettings {
+    fn default() -> Self {
+        CollisionSettings {
+            layers: [
+                (CollisionLayer::Ship, CollisionLayer::Ship),
+                (CollisionLayer::Ship, CollisionLayer::Port),
+                (CollisionLayer::Projectile, CollisionLayer::Ship),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            symmetric: false,
+        }
+    }
+}
+
+impl CollisionSettings {
+    pub fn collides(&self, layer: CollisionLayer, other_layer: CollisionLayer) -> bool {
+        self.layers.contains(&(layer, other_layer)) || self.layers.contains(&(other_layer, layer))
+    }
+}
+
+struct Collider {
+    entity: Entity,
+    layer: CollisionLayer,
+    shape: CollisionShape,
+    centre: Point2<f32>,
+    heading: f32,
+}
+
 pub struct CollisionSystem;
 
 impl<'s> System<'s> for CollisionSystem {
     type SystemData = (
         Entities<'s>,
         ReadStorage<'s, BoundingBox>,
+        ReadStorage<'s, CollisionShape>,
+        ReadStorage<'s, CollisionLayer>,
         ReadStorage<'s, Transform>,
+        Read<'s, CollisionSettings>,
         Write<'s, EventChannel<CollisionEvent>>,
     );
 
-    fn run(&mut self, (entities, bounding_boxes, locals, mut channel): Self::SystemData) {
-        for (e, bounding_box, local) in (&entities, &bounding_boxes, &locals).join() {
-            for (other_e, other_bounding_box, other_local) in
-                (&entities, &bounding_boxes, &locals).join()
-            {
-                if e != other_e
-                    && bounding_boxes_intersect(
-                        bounding_box,
-                        local,
-                        other_bounding_box,
-                        other_local,
-                    )
-                {
+    fn run(
+        &mut self,
+        (entities, bounding_boxes, shapes, layers, locals, settings, mut channel): Self::SystemData,
+    ) {
+        let mut colliders = Vec::new();
+
+        for (e, local, bounding_box, shape) in
+            (&entities, &locals, bounding_boxes.maybe(), shapes.maybe()).join()
+        {
+            let centre = Point2::new(local.translation().x, local.translation().y);
+            let (shape, centre, heading) = match (shape, bounding_box) {
+                (Some(shape), _) => (*shape, centre, local.rotation().euler_angles().2),
+                (None, Some(bounding_box)) => (
+                    CollisionShape::Rectangle {
+                        length: bounding_box.width,
+                        width: bounding_box.width,
+                    },
+                    centre + bounding_box.origin.coords,
+                    0.0,
+                ),
+                (None, None) => continue,
+            };
+
+            colliders.push(Collider {
+                entity: e,
+                layer: layers.get(e).cloned().unwrap_or_default(),
+                shape,
+                centre,
+                heading,
+            });
+        }
+
+        let boundaries = colliders
+            .iter()
+            .map(|collider| {
+                let half_extents = collider.shape.half_extents(collider.heading);
+                (
+                    collider.centre.x - half_extents.x,
+                    collider.centre.x + half_extents.x,
+                    collider.centre.y + half_extents.y,
+                    collider.centre.y - half_extents.y,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (i, j) in sweep_and_prune(&boundaries) {
+            let (collider, other_collider) = (&colliders[i], &colliders[j]);
+
+            if !settings.collides(collider.layer, other_collider.layer) {
+                continue;
+            }
+
+            if shapes_overlap(
+                &collider.shape,
+                collider.centre,
+                collider.heading,
+                &other_collider.shape,
+                other_collider.centre,
+                other_collider.heading,
+            ) {
+                channel.single_write(CollisionEvent {
+                    entity: collider.entity,
+                    other_entity: other_collider.entity,
+                });
+
+                if settings.symmetric {
                     channel.single_write(CollisionEvent {
-                        entity: e,
-                        other_entity: other_e,
+                        entity: other_collider.entity,
+                        other_entity: collider.entity,
                     });
                 }
             }
@@ -46,6 +143,9 @@ impl<'s> System<'s> for CollisionSystem {
     }
 }
 
+// Crews at least this strong sink the pirates that attack them
+const REPEL_STRENGTH: f32 = 0.6;
+
 pub struct DestroySystem {
     reader_id: ReaderId<CollisionEvent>,
 }
@@ -61,30 +161,52 @@ impl<'s> System<'s> for DestroySystem {
         Entities<'s>,
         ReadStorage<'s, Ship>,
         ReadStorage<'s, Pirate>,
-        Write<'s, Notifications>,
+        ReadStorage<'s, Crew>,
+        Write<'s, EventChannel<GameEvent>>,
         Read<'s, EventChannel<CollisionEvent>>,
     );
 
-    fn run(&mut self, (entities, ships, pirates, mut notifications, channel): Self::SystemData) {
-        let mut entities_to_destroy = HashSet::new();
+    fn run(
+        &mut self,
+        (entities, ships, pirates, crews, mut game_events, channel): Self::SystemData,
+    ) {
+        // Each ship and the pirate attacking it
+        let mut attacks = HashSet::new();
 
         for collision in channel.read(&mut self.reader_id) {
             if ships.get(collision.entity).is_some()
                 && pirates.get(collision.other_entity).is_some()
             {
-                entities_to_destroy.insert(collision.entity);
+                attacks.insert((collision.entity, collision.other_entity));
             }
 
             if ships.get(collision.other_entity).is_some()
                 && pirates.get(collision.entity).is_some()
             {
-                entities_to_destroy.insert(collision.other_entity);
+                attacks.insert((collision.other_entity, collision.entity));
+            }
+        }
+
+        let mut entities_to_destroy = HashSet::new();
+        let mut pirates_sunk = HashSet::new();
+
+        for (ship, pirate) in attacks {
+            let fights_off = crews
+                .get(ship)
+                .map_or(false, |crew| crew.fighting_strength() >= REPEL_STRENGTH);
+            if !fights_off {
+                entities_to_destroy.insert(ship);
+            } else if pirates_sunk.insert(pirate) {
+                game_events.single_write(GameEvent::PiratesFoughtOff { ship });
             }
         }
 
         for entity in entities_to_destroy {
             entities.delete(entity).unwrap();
-            notifications.push_back("Ship destroyed by pirate".to_string());
+            game_events.single_write(GameEvent::ShipDestroyed { ship: entity });
+        }
+        for pirate in pirates_sunk {
+            entities.delete(pirate).unwrap();
         }
     }
 }
@@ -109,39 +231,133 @@ impl<'a, 'b> SystemDesc<'a, 'b, DestroySystem> for DestroySystemDesc {
     }
 }
 
-fn bounding_boxes_intersect(
-    bounding_box: &BoundingBox,
-    local: &Transform,
-    other_bounding_box: &BoundingBox,
-    other_local: &Transform,
+// A shape placed in the world. Circles and capsules are kept as the line through their middle
+enum Outline {
+    Rectangle([Point2<f32>; 4]),
+    Rounded(Point2<f32>, Point2<f32>, f32),
+}
+
+fn outline(shape: &CollisionShape, centre: Point2<f32>, heading: f32) -> Outline {
+    let direction = Vector2::new(heading.cos(), heading.sin());
+
+    match *shape {
+        CollisionShape::Circle { radius } => Outline::Rounded(centre, centre, radius),
+        CollisionShape::Rectangle { length, width } => {
+            let along = direction * length * 0.5;
+            let across = Vector2::new(-direction.y, direction.x) * width * 0.5;
+            Outline::Rectangle([
+                centre + along + across,
+                centre - along + across,
+                centre - along - across,
+                centre + along - across,
+            ])
+        }
+        CollisionShape::Capsule { length, radius } => {
+            let along = direction * length * 0.5;
+            Outline::Rounded(centre - along, centre + along, radius)
+        }
+    }
+}
+
+fn shapes_overlap(
+    shape: &CollisionShape,
+    centre: Point2<f32>,
+    heading: f32,
+    other_shape: &CollisionShape,
+    other_centre: Point2<f32>,
+    other_heading: f32,
+) -> bool {
+    match (
+        outline(shape, centre, heading),
+        outline(other_shape, other_centre, other_heading),
+    ) {
+        (Outline::Rectangle(corners), Outline::Rectangle(other_corners)) => {
+            rectangles_overlap(&corners, &other_corners)
+        }
+        (Outline::Rectangle(corners), Outline::Rounded(start, end, radius))
+        | (Outline::Rounded(start, end, radius), Outline::Rectangle(corners)) => {
+            rectangle_within_distance(&corners, start, end, radius)
+        }
+        (
+            Outline::Rounded(start, end, radius),
+            Outline::Rounded(other_start, other_end, other_radius),
+        ) => segments_distance(start, end, other_start, other_end) <= radius + other_radius,
+    }
+}
+
+// Separating axis test. Rectangle edges are their own normals, so the rectangles overlap unless
+// their projections onto one of the edges leave a gap
+fn rectangles_overlap(corners: &[Point2<f32>; 4], other_corners: &[Point2<f32>; 4]) -> bool {
+    rectangle_axes(corners)
+        .iter()
+        .chain(rectangle_axes(other_corners).iter())
+        .all(|axis| {
+            let (min, max) = project(corners, axis);
+            let (other_min, other_max) = project(other_corners, axis);
+            min <= other_max && other_min <= max
+        })
+}
+
+fn rectangle_within_distance(
+    corners: &[Point2<f32>; 4],
+    start: Point2<f32>,
+    end: Point2<f32>,
+    distance: f32,
 ) -> bool {
-    let (top_left, top_right, bottom_right, bottom_left) = bounding_box.as_2d_points(local);
-    let (other_left, other_right, other_top, other_bottom) =
-        other_bounding_box.as_boundaries(other_local);
-
-    point_in_rect(top_left, other_left, other_right, other_top, other_bottom)
-        || point_in_rect(top_right, other_left, other_right, other_top, other_bottom)
-        || point_in_rect(
-            bottom_right,
-            other_left,
-            other_right,
-            other_top,
-            other_bottom,
-        )
-        || point_in_rect(
-            bottom_left,
-            other_left,
-            other_right,
-            other_top,
-            other_bottom,
-        )
+    let start_inside = rectangle_axes(corners).iter().all(|axis| {
+        let (min, max) = project(corners, axis);
+        let projection = start.coords.dot(axis);
+        min <= projection && projection <= max
+    });
+
+    start_inside
+        || (0..4)
+            .any(|i| segments_distance(corners[i], corners[(i + 1) % 4], start, end) <= distance)
+}
+
+fn rectangle_axes(corners: &[Point2<f32>; 4]) -> [Vector2<f32>; 2] {
+    [corners[1] - corners[0], corners[2] - corners[1]]
+}
+
+fn project(corners: &[Point2<f32>; 4], axis: &Vector2<f32>) -> (f32, f32) {
+    corners
+        .iter()
+        .map(|corner| corner.coords.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), projection| {
+            (min.min(projection), max.max(projection))
+        })
+}
+
+fn segments_distance(
+    start: Point2<f32>,
+    end: Point2<f32>,
+    other_start: Point2<f32>,
+    other_end: Point2<f32>,
+) -> f32 {
+    let side =
+        |point: Point2<f32>, from: Point2<f32>, to: Point2<f32>| (to - from).perp(&(point - from));
+    let crosses = |a: f32, b: f32| (a < 0.0 && b > 0.0) || (a > 0.0 && b < 0.0);
+
+    if crosses(
+        side(start, other_start, other_end),
+        side(end, other_start, other_end),
+    ) && crosses(side(other_start, start, end), side(other_end, start, end))
+    {
+        return 0.0;
+    }
+
+    distance_to_segment(start, other_start, other_end)
+        .min(distance_to_segment(end, other_start, other_end))
+        .min(distance_to_segment(other_start, start, end))
+        .min(distance_to_segment(other_end, start, end))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use amethyst::{core::math::Point2, ecs::Entity, prelude::*, Result};
+    use amethyst::{prelude::*, Result};
     use amethyst_test::prelude::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
 
     #[test]
     fn collision_events_not_sent_for_entities_colliding_with_themselves() -> Result<()> {
@@ -212,7 +428,7 @@ mod tests {
     }
 
     #[test]
-    fn two_collision_events_sent_for_each_pair_of_collisions() -> Result<()> {
+    fn one_collision_event_sent_for_each_pair_of_collisions() -> Result<()> {
         AmethystApplication::blank()
             .with_system(CollisionSystem, "collision", &[])
             .with_effect(|world| {
@@ -246,7 +462,7 @@ mod tests {
             .with_assertion(|world| {
                 let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
                 let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
-                assert_eq!(2, collision_event_channel.read(&mut reader_id).len());
+                assert_eq!(1, collision_event_channel.read(&mut reader_id).len());
             })
             .run()
     }
@@ -298,11 +514,367 @@ mod tests {
             .with_assertion(|world| {
                 let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
                 let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
-                assert_eq!(6, collision_event_channel.read(&mut reader_id).len());
+                assert_eq!(3, collision_event_channel.read(&mut reader_id).len());
+            })
+            .run()
+    }
+
+    #[test]
+    fn two_collision_events_sent_for_each_pair_of_collisions_when_symmetric() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CollisionSystem, "collision", &[])
+            .with_effect(|world| {
+                world.insert(CollisionSettings {
+                    symmetric: true,
+                    ..CollisionSettings::default()
+                });
+
+                let entity = world
+                    .create_entity()
+                    .with(BoundingBox {
+                        width: 5.0,
+                        origin: Point2::<f32>::origin(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                let mut other_transform = Transform::default();
+                other_transform.set_translation_xyz(2.0, 2.0, 0.0);
+
+                let other_entity = world
+                    .create_entity()
+                    .with(BoundingBox {
+                        width: 5.0,
+                        origin: Point2::<f32>::origin(),
+                    })
+                    .with(other_transform)
+                    .build();
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .register_reader();
+
+                world.insert(reader_id);
+                world.insert(EffectReturn((entity, other_entity)));
+            })
+            .with_assertion(|world| {
+                let (entity, other_entity) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+                let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
+                let events = collision_event_channel
+                    .read(&mut reader_id)
+                    .map(|event| (event.entity, event.other_entity))
+                    .collect::<HashSet<_>>();
+
+                assert_eq!(2, events.len());
+                assert!(events.contains(&(entity, other_entity)));
+                assert!(events.contains(&(other_entity, entity)));
             })
             .run()
     }
 
+    #[test]
+    fn collision_events_sent_for_ship_colliding_with_port() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CollisionSystem, "collision", &[])
+            .with_effect(|world| {
+                world
+                    .create_entity()
+                    .with(BoundingBox {
+                        width: 5.0,
+                        origin: Point2::<f32>::origin(),
+                    })
+                    .with(CollisionLayer::Ship)
+                    .with(Transform::default())
+                    .build();
+
+                let mut other_transform = Transform::default();
+                other_transform.set_translation_xyz(2.0, 2.0, 0.0);
+
+                world
+                    .create_entity()
+                    .with(BoundingBox {
+                        width: 5.0,
+                        origin: Point2::<f32>::origin(),
+                    })
+                    .with(CollisionLayer::Port)
+                    .with(other_transform)
+                    .build();
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .register_reader();
+
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
+                assert_eq!(1, collision_event_channel.read(&mut reader_id).len());
+            })
+            .run()
+    }
+
+    #[test]
+    fn collision_events_not_sent_for_layers_that_do_not_collide() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CollisionSystem, "collision", &[])
+            .with_effect(|world| {
+                for layer in [
+                    CollisionLayer::Port,
+                    CollisionLayer::Port,
+                    CollisionLayer::Projectile,
+                    CollisionLayer::Projectile,
+                ]
+                .iter()
+                {
+                    world
+                        .create_entity()
+                        .with(BoundingBox {
+                            width: 5.0,
+                            origin: Point2::<f32>::origin(),
+                        })
+                        .with(*layer)
+                        .with(Transform::default())
+                        .build();
+                }
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .register_reader();
+
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
+                assert_eq!(0, collision_event_channel.read(&mut reader_id).len());
+            })
+            .run()
+    }
+
+    #[test]
+    fn collision_events_sent_for_hundreds_of_ships() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CollisionSystem, "collision", &[])
+            .with_effect(|world| {
+                // Each ship overlaps the ones either side of it
+                for i in 0..500 {
+                    let mut transform = Transform::default();
+                    transform.set_translation_xyz(i as f32 * 4.0, (i % 2) as f32, 0.0);
+
+                    world
+                        .create_entity()
+                        .with(BoundingBox {
+                            width: 5.0,
+                            origin: Point2::<f32>::origin(),
+                        })
+                        .with(transform)
+                        .build();
+                }
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .register_reader();
+
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
+                assert_eq!(499, collision_event_channel.read(&mut reader_id).len());
+            })
+            .run()
+    }
+
+    #[test]
+    fn collision_events_sent_for_crossing_ships() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(CollisionSystem, "collision", &[])
+            .with_effect(|world| {
+                world
+                    .create_entity()
+                    .with(CollisionShape::Capsule {
+                        length: 20.0,
+                        radius: 1.0,
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                let mut other_transform = Transform::default();
+                other_transform.set_rotation_2d(FRAC_PI_2);
+
+                world
+                    .create_entity()
+                    .with(CollisionShape::Capsule {
+                        length: 20.0,
+                        radius: 1.0,
+                    })
+                    .with(other_transform)
+                    .build();
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .register_reader();
+
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                let collision_event_channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<CollisionEvent>>();
+                assert_eq!(1, collision_event_channel.read(&mut reader_id).len());
+            })
+            .run()
+    }
+
+    #[test]
+    fn cross_shaped_rectangles_overlap() {
+        // Neither rectangle has a corner inside the other
+        let rectangle = CollisionShape::Rectangle {
+            length: 20.0,
+            width: 4.0,
+        };
+
+        assert!(shapes_overlap(
+            &rectangle,
+            Point2::origin(),
+            0.0,
+            &rectangle,
+            Point2::origin(),
+            FRAC_PI_2,
+        ));
+    }
+
+    #[test]
+    fn cross_shaped_rectangle_and_capsule_overlap() {
+        assert!(shapes_overlap(
+            &CollisionShape::Rectangle {
+                length: 20.0,
+                width: 4.0,
+            },
+            Point2::origin(),
+            0.0,
+            &CollisionShape::Capsule {
+                length: 20.0,
+                radius: 1.0,
+            },
+            Point2::new(2.0, 0.0),
+            FRAC_PI_2,
+        ));
+    }
+
+    #[test]
+    fn parallel_rotated_rectangles_do_not_overlap() {
+        // Their bounding boxes overlap but the rectangles themselves are apart
+        let rectangle = CollisionShape::Rectangle {
+            length: 20.0,
+            width: 2.0,
+        };
+
+        assert!(!shapes_overlap(
+            &rectangle,
+            Point2::origin(),
+            FRAC_PI_4,
+            &rectangle,
+            Point2::new(-3.0, 3.0),
+            FRAC_PI_4,
+        ));
+    }
+
+    #[test]
+    fn capsule_inside_rectangle_overlaps() {
+        assert!(shapes_overlap(
+            &CollisionShape::Rectangle {
+                length: 20.0,
+                width: 20.0,
+            },
+            Point2::origin(),
+            0.0,
+            &CollisionShape::Capsule {
+                length: 4.0,
+                radius: 1.0,
+            },
+            Point2::new(2.0, 2.0),
+            FRAC_PI_4,
+        ));
+    }
+
+    #[test]
+    fn capsules_overlap_end_to_end() {
+        let capsule = CollisionShape::Capsule {
+            length: 10.0,
+            radius: 1.0,
+        };
+
+        assert!(shapes_overlap(
+            &capsule,
+            Point2::origin(),
+            0.0,
+            &capsule,
+            Point2::new(11.5, 0.0),
+            0.0,
+        ));
+        assert!(!shapes_overlap(
+            &capsule,
+            Point2::origin(),
+            0.0,
+            &capsule,
+            Point2::new(12.5, 0.0),
+            0.0,
+        ));
+    }
+
+    #[test]
+    fn circles_overlap_within_their_radii() {
+        let circle = CollisionShape::Circle { radius: 2.0 };
+
+        assert!(shapes_overlap(
+            &circle,
+            Point2::origin(),
+            0.0,
+            &circle,
+            Point2::new(3.0, 2.0),
+            0.0,
+        ));
+        assert!(!shapes_overlap(
+            &circle,
+            Point2::origin(),
+            0.0,
+            &circle,
+            Point2::new(3.0, 3.0),
+            0.0,
+        ));
+    }
+
+    #[test]
+    fn circle_near_rectangle_corner_overlaps_only_within_radius() {
+        let rectangle = CollisionShape::Rectangle {
+            length: 10.0,
+            width: 10.0,
+        };
+        let circle = CollisionShape::Circle { radius: 1.0 };
+
+        assert!(shapes_overlap(
+            &rectangle,
+            Point2::origin(),
+            0.0,
+            &circle,
+            Point2::new(5.6, 5.6),
+            0.0,
+        ));
+        assert!(!shapes_overlap(
+            &rectangle,
+            Point2::origin(),
+            0.0,
+            &circle,
+            Point2::new(5.9, 5.9),
+            0.0,
+        ));
+    }
+
     #[test]
     fn pirate_does_not_destroy_non_ship() -> Result<()> {
         AmethystApplication::blank()
@@ -370,7 +942,42 @@ mod tests {
     }
 
     #[test]
-    fn notification_sent_when_pirate_destroys_ship() -> Result<()> {
+    fn strong_crew_fights_off_pirate() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(DestroySystemDesc, "destroy", &[])
+            .with_effect(|world| {
+                let pirate = world.create_entity().with(Pirate).build();
+                let entity = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Crew {
+                        sailors: 30,
+                        berths: 30,
+                        morale: 80.0,
+                        ..Crew::default()
+                    })
+                    .build();
+
+                world.insert(EffectReturn((entity, pirate)));
+
+                world
+                    .fetch_mut::<EventChannel<CollisionEvent>>()
+                    .single_write(CollisionEvent {
+                        entity: pirate,
+                        other_entity: entity,
+                    });
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let (entity, pirate) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+                assert!(world.entities().is_alive(entity), "Ship survives");
+                assert!(!world.entities().is_alive(pirate), "Pirate sunk");
+            })
+            .run()
+    }
+
+    #[test]
+    fn game_event_sent_when_pirate_destroys_ship() -> Result<()> {
         AmethystApplication::blank()
             .with_system_desc(DestroySystemDesc, "destroy", &[])
             .with_effect(|world| {
@@ -383,6 +990,11 @@ mod tests {
 
                 world.insert(EffectReturn(entity));
 
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
                 let mut channel = world.fetch_mut::<EventChannel<CollisionEvent>>();
                 channel.single_write(CollisionEvent {
                     entity: pirate,
@@ -395,13 +1007,12 @@ mod tests {
                 });
             })
             .with_assertion(|world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
-                assert_eq!(
-                    "Ship destroyed by pirate",
-                    notifications.front().unwrap(),
-                    "Notification"
-                );
+                let entity = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(vec![GameEvent::ShipDestroyed { ship: entity }], events);
             })
             .run()
     }
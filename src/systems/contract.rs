@@ -1,18 +1,68 @@
 use crate::{
-    age_of_sail::{Notifications, PlayerStatus, DISTANCE_THRESHOLD},
-    components::{Cargo, Contract, Expiration, OwnedBy, Ship},
-    event::UiUpdateEvent,
+    age_of_sail::{
+        distance_to_segment, ContractHistory, ContractRecord, Date, PlayerStatus, SECONDS_IN_DAY,
+    },
+    components::{
+        contract::TRADE_GOODS, Cargo, CargoTransfer, Contract, ContractKind, Controllable, Course,
+        Docked, DropOff, Expiration, ItemType, Market, OwnedBy, Pirate, Port, Selected, Ship,
+    },
+    event::{CalendarEvent, GameEvent, UiUpdateEvent},
+    finance::{Ledger, TransactionKind},
+    locale::Locale,
+    map::Map,
 };
 use amethyst::{
     core::{alga::linear::EuclideanSpace, math::Point2, Named, Transform},
-    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
     prelude::SystemDesc,
     shred::World,
     shrev::{EventChannel, ReaderId},
     ui::{UiEvent, UiEventType},
 };
-use itertools::Itertools;
-use std::collections::HashSet;
+use chrono::{Duration, Utc};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    seq::SliceRandom,
+    thread_rng, Rng,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Add,
+};
+
+// A port of size 1 offers a new contract roughly this often
+const DAYS_PER_CONTRACT: f64 = 10.0;
+// Extra contracts offered at a port when a merchant fleet sails from it
+const FLEET_SAILING_CONTRACTS: f64 = 2.0;
+pub const MAX_OPEN_CONTRACTS_PER_PORT: usize = 5;
+
+// Weighting for a destination halves at this distance
+const DESTINATION_DISTANCE_FALLOFF: f32 = 100.0;
+const BASE_PAYMENT_PER_TON: f32 = 2.0;
+const PAYMENT_PER_TON_PER_DISTANCE: f32 = 0.2;
+// Extra proportion of payment for each pirate near the route
+const RISK_PREMIUM: f32 = 0.25;
+const RISK_DISTANCE: f32 = 50.0;
+const MIN_CONTRACT_DAYS: i64 = 5;
+const DISTANCE_PER_EXTRA_CONTRACT_DAY: f32 = 20.0;
+// Proportions of the payment taken as a deposit and charged if the contract expires
+const DEPOSIT_PROPORTION: f32 = 0.1;
+const PENALTY_PROPORTION: f32 = 0.25;
+const LATE_PAYMENT_DECAY_PER_DAY: f32 = 0.1;
+const MIN_LATE_PAYMENT_MULTIPLIER: f32 = 0.2;
+
+// Relative chance of each kind of contract being offered, in the order
+// delivery, passengers, mail, smuggling, bulk, escort
+const CONTRACT_KIND_WEIGHTS: [u32; 6] = [50, 15, 15, 10, 10, 10];
+const PASSENGER_FARE_MULTIPLIER: f32 = 1.5;
+const MAIL_PAYMENT_MULTIPLIER: f32 = 4.0;
+const SMUGGLING_PAYMENT_MULTIPLIER: f32 = 3.0;
+const ESCORT_PAYMENT_PER_DISTANCE: f32 = 3.0;
+const MAX_BULK_DROPS: usize = 3;
+// Merchants within this distance of a port can ask for an escort
+const ESCORT_PICKUP_DISTANCE: f32 = 10.0;
+// Escort and escorted ship need to be this close to the destination
+const ESCORT_ARRIVAL_DISTANCE: f32 = 5.0;
 
 pub struct AcceptContractSystem {
     reader_id: ReaderId<UiEvent>,
@@ -26,27 +76,41 @@ impl AcceptContractSystem {
 
 impl<'s> System<'s> for AcceptContractSystem {
     type SystemData = (
-        ReadStorage<'s, Contract>,
+        Entities<'s>,
+        WriteStorage<'s, Contract>,
         ReadStorage<'s, Expiration>,
-        ReadStorage<'s, Named>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Selected>,
         WriteStorage<'s, OwnedBy>,
-        WriteStorage<'s, Cargo>,
+        WriteStorage<'s, Course>,
         Read<'s, EventChannel<UiEvent>>,
         Write<'s, EventChannel<UiUpdateEvent>>,
-        Write<'s, Notifications>,
+        Write<'s, EventChannel<GameEvent>>,
+        Read<'s, Date>,
+        Read<'s, Map>,
+        Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
     );
 
     fn run(
         &mut self,
         (
-            contracts,
+            entities,
+            mut contracts,
             expirations,
-            names,
+            locals,
+            controllables,
+            selecteds,
             mut owned_bys,
-            mut cargos,
+            mut courses,
             channel,
             mut update_channel,
-            mut notifications,
+            mut game_events,
+            date,
+            map,
+            mut player_status,
+            mut ledger,
         ): Self::SystemData,
     ) {
         for event in channel.read(&mut self.reader_id) {
@@ -59,41 +123,67 @@ impl<'s> System<'s> for AcceptContractSystem {
                 if let Some(associated_entity) =
                     owned_bys.get(clicked).map_or(None, |o| Some(o.entity))
                 {
-                    if let Some(contract) = contracts.get(associated_entity) {
+                    if let Some(contract) = contracts.get_mut(associated_entity) {
+                        if player_status.money < contract.deposit as i32 {
+                            game_events.single_write(GameEvent::DepositUnaffordable {
+                                deposit: contract.deposit,
+                            });
+                            continue;
+                        }
+
                         let port = owned_bys.get(associated_entity).unwrap().entity;
-                        let port_cargo = cargos.get_mut(port).unwrap();
 
                         owned_bys.remove(associated_entity);
-
-                        for (item_type, amount) in &contract.goods_required {
-                            *port_cargo.items.entry(*item_type).or_insert(0) += amount;
-                        }
-
-                        let items_notification = contract
-                            .goods_required
-                            .iter()
-                            .sorted_by_key(|(&item, _)| item)
-                            .map(|(item, amount)| format!("{} tons of {}", amount, item))
-                            .collect::<Vec<_>>()
-                            .join(", ");
-
-                        let contract_accepted_message = format!(
-                            "Contract accepted. {} ready to be loaded at {}.",
-                            items_notification,
-                            names.get(port).unwrap().name.to_string(),
+                        contract.origin = Some(port);
+                        ledger.record(
+                            &mut player_status,
+                            date.current_date(),
+                            TransactionKind::Deposits,
+                            -(contract.deposit as i32),
                         );
 
-                        if let Some(expiration) = expirations.get(associated_entity) {
-                            notifications.push_back(format!(
-                                "{} It will expire on {}.",
-                                contract_accepted_message,
-                                expiration.expiration_date.format("%e %B %Y").to_string()
-                            ));
-                        } else {
-                            notifications.push_back(contract_accepted_message);
+                        // Goods wait at the port for the selected ship, or whichever
+                        // of the player's ships docks there first
+                        contract.reserved = contract.goods_required.clone();
+                        contract.carrier = (&entities, &controllables, &selecteds)
+                            .join()
+                            .map(|(e, _, _)| e)
+                            .next();
+
+                        // The escorted ship sets sail for the destination straight away
+                        if let ContractKind::Escort { escortee } = contract.kind {
+                            if let (Some(from), Some(to)) =
+                                (locals.get(escortee), locals.get(contract.destination))
+                            {
+                                let from = Point2::new(from.translation().x, from.translation().y);
+                                let to = Point2::new(to.translation().x, to.translation().y);
+                                courses
+                                    .insert(
+                                        escortee,
+                                        Course {
+                                            waypoints: route_around_land(&map, from, to),
+                                        },
+                                    )
+                                    .ok();
+                            }
                         }
 
+                        game_events.single_write(GameEvent::ContractAccepted {
+                            port,
+                            destination: contract.destination,
+                            kind: contract.kind.clone(),
+                            carrier: contract.carrier,
+                            goods: contract.goods_required.clone(),
+                            deposit: contract.deposit,
+                            expires: expirations
+                                .get(associated_entity)
+                                .map(|expiration| expiration.expiration_date),
+                        });
+
                         update_channel.single_write(UiUpdateEvent::Target(associated_entity));
+                        if contract.deposit > 0 {
+                            update_channel.single_write(UiUpdateEvent::PlayerStatus);
+                        }
                     }
                 }
             }
@@ -126,12 +216,19 @@ impl<'s> System<'s> for FulfillContractSystem {
         Entities<'s>,
         WriteStorage<'s, Contract>,
         ReadStorage<'s, Ship>,
+        ReadStorage<'s, Controllable>,
         ReadStorage<'s, OwnedBy>,
         ReadStorage<'s, Transform>,
         ReadStorage<'s, Named>,
+        ReadStorage<'s, Docked>,
+        ReadStorage<'s, CargoTransfer>,
         WriteStorage<'s, Cargo>,
-        Write<'s, Notifications>,
+        Read<'s, Date>,
+        Read<'s, Locale>,
+        Write<'s, EventChannel<GameEvent>>,
         Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
+        Write<'s, ContractHistory>,
         Write<'s, EventChannel<UiUpdateEvent>>,
     );
 
@@ -141,74 +238,535 @@ impl<'s> System<'s> for FulfillContractSystem {
             entities,
             mut contracts,
             ships,
+            controllables,
             owned_bys,
             locals,
             names,
+            dockeds,
+            cargo_transfers,
             mut cargos,
-            mut notifications,
+            date,
+            locale,
+            mut game_events,
             mut player_status,
+            mut ledger,
+            mut history,
             mut channel,
         ): Self::SystemData,
     ) {
+        let mut rng = thread_rng();
+        let current_date = date.current_date();
+
+        // Ships still loading or unloading can't hand over their goods yet
+        let unavailable = (&entities, &cargo_transfers)
+            .join()
+            .filter(|(_, t)| t.complete_at > date.time_elapsed)
+            .map(|(ship, _)| ship)
+            .collect::<HashSet<_>>();
+
         // for each active contract (not owned by a port)
         for (e, contract, _) in (&entities, &mut contracts, !&owned_bys).join() {
-            let port_transform = locals.get(contract.destination).unwrap();
-            let port_location = Point2::new(
-                port_transform.translation().x,
-                port_transform.translation().y,
-            );
-
-            // If a ship is nearby and has items in cargo, contract is fulfilled
-            //TODO: consider event for docking in the future rather than distance
-            let suitable_ship = (&entities, &ships, &cargos, &locals)
-                .join()
-                .filter(|(_, _, cargo, l)| {
-                    let ship_location = Point2::new(l.translation().x, l.translation().y);
+            let late_multiplier = late_payment_multiplier(contract.due_date, current_date);
+
+            match contract.kind.clone() {
+                ContractKind::Escort { escortee } => {
+                    if !entities.is_alive(escortee) {
+                        ledger.record(
+                            &mut player_status,
+                            current_date,
+                            TransactionKind::Penalties,
+                            -(contract.penalty as i32),
+                        );
+                        channel.single_write(UiUpdateEvent::PlayerStatus);
+
+                        let earnings = -((contract.deposit + contract.penalty) as i32);
+                        record_outcome(
+                            &mut history,
+                            contract,
+                            &names,
+                            false,
+                            earnings,
+                            &date,
+                            &locale,
+                        );
+                        channel.single_write(UiUpdateEvent::Journal);
+
+                        entities.delete(e).unwrap();
+                        channel.single_write(UiUpdateEvent::Target(e));
+                        game_events.single_write(GameEvent::EscortLost {
+                            penalty: contract.penalty,
+                        });
+                        continue;
+                    }
 
-                    let ship_has_cargo = contract
-                        .goods_required
-                        .iter()
-                        .map(|(item, amount)| {
-                            cargo.items.get(item).unwrap_or(&(0 as u32)) >= amount
+                    let port_transform = locals.get(contract.destination).unwrap();
+                    let port_location = Point2::new(
+                        port_transform.translation().x,
+                        port_transform.translation().y,
+                    );
+
+                    let arrived = |ship: Entity| {
+                        locals.get(ship).map_or(false, |l| {
+                            Point2::new(l.translation().x, l.translation().y)
+                                .distance(&port_location)
+                                < ESCORT_ARRIVAL_DISTANCE
                         })
-                        .fold(true, |a, b| a && b);
+                    };
+
+                    let escort_arrived = (&entities, &ships, &controllables)
+                        .join()
+                        .any(|(ship, _, _)| ship != escortee && arrived(ship));
+
+                    if arrived(escortee) && escort_arrived {
+                        contract.earned = late_payment(contract.payment, late_multiplier);
+                        pay_out(&mut ledger, &mut player_status, current_date, contract);
+                        channel.single_write(UiUpdateEvent::PlayerStatus);
+
+                        record_outcome(
+                            &mut history,
+                            contract,
+                            &names,
+                            true,
+                            contract.earned as i32,
+                            &date,
+                            &locale,
+                        );
+                        channel.single_write(UiUpdateEvent::Journal);
+
+                        entities.delete(e).unwrap();
+                        contract.fulfilled = true;
+                        channel.single_write(UiUpdateEvent::Target(e));
+                        game_events.single_write(GameEvent::ContractFulfilled {
+                            port: contract.destination,
+                            kind: contract.kind.clone(),
+                            goods: HashMap::new(),
+                            earned: contract.earned,
+                            deposit: contract.deposit,
+                        });
+                    }
+                }
+                ContractKind::Bulk { mut drops } => {
+                    let mut delivered_any = false;
+
+                    for drop_off in drops.iter_mut().filter(|d| !d.delivered) {
+                        let suitable_ship = carrier_with_goods_at(
+                            contract.carrier,
+                            &cargos,
+                            &dockeds,
+                            &unavailable,
+                            drop_off.destination,
+                            &drop_off.goods,
+                        );
+
+                        if let Some(ship) = suitable_ship {
+                            remove_goods(cargos.get_mut(ship).unwrap(), &drop_off.goods);
+                            drop_off.delivered = true;
+                            delivered_any = true;
+
+                            for (item, amount) in &drop_off.goods {
+                                *contract.delivered.entry(*item).or_insert(0) += amount;
+                            }
+
+                            let paid = late_payment(drop_off.payment, late_multiplier);
+                            contract.earned += paid;
+                            ledger.record(
+                                &mut player_status,
+                                current_date,
+                                TransactionKind::ContractIncome,
+                                paid as i32,
+                            );
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+                            game_events.single_write(GameEvent::GoodsDelivered {
+                                port: drop_off.destination,
+                                goods: drop_off.goods.clone(),
+                                payment: paid,
+                                still_required: HashMap::new(),
+                            });
+                        }
+                    }
 
-                    ship_location.distance(&port_location) < DISTANCE_THRESHOLD && ship_has_cargo
-                })
-                .map(|(e, _, _, _)| e)
-                .next();
+                    let all_delivered = drops.iter().all(|d| d.delivered);
+                    contract.kind = ContractKind::Bulk { drops };
 
-            if let Some(ship) = suitable_ship {
-                let cargo = cargos.get_mut(ship).unwrap();
-                let items_notification = contract
-                    .goods_required
-                    .iter()
-                    .sorted_by_key(|(&item, _)| item)
-                    .map(|(item, amount)| format!("{} tons of {}", amount, item))
-                    .collect::<Vec<_>>()
-                    .join(", ");
+                    if all_delivered {
+                        ledger.record(
+                            &mut player_status,
+                            current_date,
+                            TransactionKind::Deposits,
+                            contract.deposit as i32,
+                        );
+                        channel.single_write(UiUpdateEvent::PlayerStatus);
+
+                        record_outcome(
+                            &mut history,
+                            contract,
+                            &names,
+                            true,
+                            contract.earned as i32,
+                            &date,
+                            &locale,
+                        );
+                        channel.single_write(UiUpdateEvent::Journal);
+
+                        entities.delete(e).unwrap();
+                        contract.fulfilled = true;
+                        game_events.single_write(GameEvent::ContractFulfilled {
+                            port: contract.destination,
+                            kind: contract.kind.clone(),
+                            goods: contract.delivered.clone(),
+                            earned: contract.earned,
+                            deposit: contract.deposit,
+                        });
+                    }
+
+                    if delivered_any {
+                        channel.single_write(UiUpdateEvent::Target(e));
+                    }
+                }
+                ContractKind::Smuggling { inspection_chance } => {
+                    let suitable_ship = carrier_with_goods_at(
+                        contract.carrier,
+                        &cargos,
+                        &dockeds,
+                        &unavailable,
+                        contract.destination,
+                        &contract.goods_required,
+                    );
+
+                    if let Some(ship) = suitable_ship {
+                        remove_goods(cargos.get_mut(ship).unwrap(), &contract.goods_required);
+                        entities.delete(e).unwrap();
+                        channel.single_write(UiUpdateEvent::Target(e));
+
+                        if rng.gen::<f32>() < inspection_chance {
+                            // Caught by customs: the goods are seized, the deposit is lost and a fine is due
+                            let fine = contract.payment / 2;
+                            ledger.record(
+                                &mut player_status,
+                                current_date,
+                                TransactionKind::Penalties,
+                                -(fine as i32),
+                            );
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+
+                            let earnings = -((fine + contract.deposit) as i32);
+                            record_outcome(
+                                &mut history,
+                                contract,
+                                &names,
+                                false,
+                                earnings,
+                                &date,
+                                &locale,
+                            );
+                            channel.single_write(UiUpdateEvent::Journal);
+                            game_events.single_write(GameEvent::GoodsSeized {
+                                port: contract.destination,
+                                goods: contract.goods_required.clone(),
+                                fine,
+                            });
+                        } else {
+                            contract.earned = late_payment(contract.payment, late_multiplier);
+                            pay_out(&mut ledger, &mut player_status, current_date, contract);
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+                            contract.fulfilled = true;
+
+                            record_outcome(
+                                &mut history,
+                                contract,
+                                &names,
+                                true,
+                                contract.earned as i32,
+                                &date,
+                                &locale,
+                            );
+                            channel.single_write(UiUpdateEvent::Journal);
+                            game_events.single_write(GameEvent::ContractFulfilled {
+                                port: contract.destination,
+                                kind: contract.kind.clone(),
+                                goods: contract.goods_required.clone(),
+                                earned: contract.earned,
+                                deposit: contract.deposit,
+                            });
+                        }
+                    }
+                }
+                ContractKind::Delivery | ContractKind::Passengers | ContractKind::Mail => {
+                    // Any of the remaining goods can be delivered, paid pro-rata
+                    let remaining_goods = contract.remaining_goods();
+                    let suitable_ship = carrier_with_any_goods_at(
+                        contract.carrier,
+                        &cargos,
+                        &dockeds,
+                        &unavailable,
+                        contract.destination,
+                        &remaining_goods,
+                    );
 
-                for (item, amount) in &contract.goods_required {
-                    *cargo.items.get_mut(&item).unwrap() -= amount;
+                    if let Some(ship) = suitable_ship {
+                        let cargo = cargos.get_mut(ship).unwrap();
+                        let unloaded = remaining_goods
+                            .iter()
+                            .map(|(item, amount)| {
+                                (*item, (*amount).min(*cargo.items.get(item).unwrap_or(&0)))
+                            })
+                            .filter(|(_, amount)| *amount > 0)
+                            .collect::<HashMap<_, _>>();
+                        remove_goods(cargo, &unloaded);
+
+                        let total = contract.goods_required.values().sum::<u32>();
+                        let delivered_before = contract.delivered.values().sum::<u32>();
+                        for (item, amount) in &unloaded {
+                            *contract.delivered.entry(*item).or_insert(0) += amount;
+                        }
+                        let delivered_after = contract.delivered.values().sum::<u32>();
+
+                        let earned = contract.payment * delivered_after / total
+                            - contract.payment * delivered_before / total;
+                        let paid = late_payment(earned, late_multiplier);
+                        contract.earned += paid;
+                        ledger.record(
+                            &mut player_status,
+                            current_date,
+                            TransactionKind::ContractIncome,
+                            paid as i32,
+                        );
+
+                        let still_required = contract.remaining_goods();
+
+                        if still_required.is_empty() {
+                            ledger.record(
+                                &mut player_status,
+                                current_date,
+                                TransactionKind::Deposits,
+                                contract.deposit as i32,
+                            );
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+
+                            record_outcome(
+                                &mut history,
+                                contract,
+                                &names,
+                                true,
+                                contract.earned as i32,
+                                &date,
+                                &locale,
+                            );
+                            channel.single_write(UiUpdateEvent::Journal);
+
+                            entities.delete(e).unwrap();
+                            contract.fulfilled = true;
+                            channel.single_write(UiUpdateEvent::Target(e));
+                            game_events.single_write(GameEvent::ContractFulfilled {
+                                port: contract.destination,
+                                kind: contract.kind.clone(),
+                                goods: unloaded,
+                                earned: contract.earned,
+                                deposit: contract.deposit,
+                            });
+                        } else {
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+                            channel.single_write(UiUpdateEvent::Target(e));
+                            game_events.single_write(GameEvent::GoodsDelivered {
+                                port: contract.destination,
+                                goods: unloaded,
+                                payment: paid,
+                                still_required,
+                            });
+                        }
+                    }
                 }
+            }
+        }
+    }
+}
+
+// The carrier, if it's at the port carrying all of the goods
+fn carrier_with_goods_at<'a>(
+    carrier: Option<Entity>,
+    cargos: &WriteStorage<'a, Cargo>,
+    dockeds: &ReadStorage<'a, Docked>,
+    unavailable: &HashSet<Entity>,
+    port: Entity,
+    goods: &HashMap<ItemType, u32>,
+) -> Option<Entity> {
+    carrier_at(carrier, cargos, dockeds, unavailable, port).filter(|ship| {
+        let cargo = cargos.get(*ship).unwrap();
+        goods
+            .iter()
+            .all(|(item, amount)| cargo.items.get(item).unwrap_or(&0) >= amount)
+    })
+}
+
+// The carrier, if it's at the port carrying some of the goods
+fn carrier_with_any_goods_at<'a>(
+    carrier: Option<Entity>,
+    cargos: &WriteStorage<'a, Cargo>,
+    dockeds: &ReadStorage<'a, Docked>,
+    unavailable: &HashSet<Entity>,
+    port: Entity,
+    goods: &HashMap<ItemType, u32>,
+) -> Option<Entity> {
+    carrier_at(carrier, cargos, dockeds, unavailable, port).filter(|ship| {
+        let cargo = cargos.get(*ship).unwrap();
+        goods
+            .keys()
+            .any(|item| *cargo.items.get(item).unwrap_or(&0) > 0)
+    })
+}
 
-                player_status.money += contract.payment as i32;
-                channel.single_write(UiUpdateEvent::PlayerStatus);
-
-                entities.delete(e).unwrap();
-                contract.fulfilled = true;
-                channel.single_write(UiUpdateEvent::Target(e));
-                notifications.push_back(format!(
-                    "Completed contract for £{} at {}. {} removed from cargo.",
-                    contract.payment,
-                    names.get(contract.destination).unwrap().name.to_string(),
-                    items_notification
-                ));
+// Only the contract's carrier hands over its goods, once docked at the port and done
+// loading or unloading, so two contracts can't both be paid from one hold
+fn carrier_at<'a>(
+    carrier: Option<Entity>,
+    cargos: &WriteStorage<'a, Cargo>,
+    dockeds: &ReadStorage<'a, Docked>,
+    unavailable: &HashSet<Entity>,
+    port: Entity,
+) -> Option<Entity> {
+    carrier.filter(|ship| {
+        cargos.get(*ship).is_some()
+            && dockeds
+                .get(*ship)
+                .map_or(false, |docked| docked.port == port)
+            && !unavailable.contains(ship)
+    })
+}
+
+// Waypoints from a ship to a port going round any land in the way, as plotted for a
+// course. A port on land is reached at the nearest point of the coast
+fn route_around_land(map: &Map, from: Point2<f32>, to: Point2<f32>) -> VecDeque<Point2<f32>> {
+    let to = if map.on_land(to) {
+        map.closest_point_on_edge(to)
+    } else {
+        to
+    };
+    let graph = map.nodes_and_edges_connected(vec![from, to]);
+
+    let (start, end) = (graph.nodes.len() - 2, graph.nodes.len() - 1);
+    let mut waypoints = VecDeque::from(graph.a_star(start, end));
+    // The ship is already at the start
+    waypoints.pop_front();
+    waypoints
+}
+
+fn remove_goods(cargo: &mut Cargo, goods: &HashMap<ItemType, u32>) {
+    for (item, amount) in goods {
+        *cargo.items.get_mut(&item).unwrap() -= amount;
+    }
+}
+
+// Moves up to the outstanding amount of each item out of the cargo
+fn take_goods(
+    cargo: &mut Cargo,
+    outstanding: &mut HashMap<ItemType, u32>,
+    taken: &mut HashMap<ItemType, u32>,
+) {
+    for (item, amount) in outstanding.iter_mut() {
+        if let Some(held) = cargo.items.get_mut(item) {
+            let amount_taken = (*held).min(*amount);
+            *held -= amount_taken;
+            *amount -= amount_taken;
+            if amount_taken > 0 {
+                *taken.entry(*item).or_insert(0) += amount_taken;
             }
         }
     }
 }
 
+// Goods handed over for a failed contract go back to the port they came from,
+// whether they're still waiting there or already aboard the contract's carrier
+fn reclaim_goods<'a>(
+    contract: &Contract,
+    cargos: &mut WriteStorage<'a, Cargo>,
+) -> HashMap<ItemType, u32> {
+    let mut outstanding = contract.remaining_goods();
+    let mut reclaimed = HashMap::new();
+
+    for (item, amount) in contract.reserved.iter().filter(|(_, amount)| **amount > 0) {
+        let amount_taken = (*amount).min(*outstanding.get(item).unwrap_or(&0));
+        if amount_taken > 0 {
+            *outstanding.get_mut(item).unwrap() -= amount_taken;
+            *reclaimed.entry(*item).or_insert(0) += amount_taken;
+        }
+    }
+
+    if let Some(carrier_cargo) = contract.carrier.and_then(|carrier| cargos.get_mut(carrier)) {
+        take_goods(carrier_cargo, &mut outstanding, &mut reclaimed);
+    }
+
+    reclaimed
+}
+
+pub fn late_payment_multiplier(
+    due_date: Option<chrono::Date<Utc>>,
+    current_date: chrono::Date<Utc>,
+) -> f32 {
+    match due_date {
+        Some(due_date) if current_date > due_date => {
+            let days_late = current_date.signed_duration_since(due_date).num_days() as f32;
+            (1.0 - LATE_PAYMENT_DECAY_PER_DAY * days_late).max(MIN_LATE_PAYMENT_MULTIPLIER)
+        }
+        _ => 1.0,
+    }
+}
+
+fn late_payment(payment: u32, late_multiplier: f32) -> u32 {
+    (payment as f32 * late_multiplier).round() as u32
+}
+
+// Payment for a completed contract, and the deposit back
+fn pay_out(
+    ledger: &mut Ledger,
+    player_status: &mut PlayerStatus,
+    date: chrono::Date<Utc>,
+    contract: &Contract,
+) {
+    ledger.record(
+        player_status,
+        date,
+        TransactionKind::ContractIncome,
+        contract.earned as i32,
+    );
+    ledger.record(
+        player_status,
+        date,
+        TransactionKind::Deposits,
+        contract.deposit as i32,
+    );
+}
+
+fn record_outcome<'a>(
+    history: &mut ContractHistory,
+    contract: &Contract,
+    names: &ReadStorage<'a, Named>,
+    completed: bool,
+    earnings: i32,
+    date: &Date,
+    locale: &Locale,
+) {
+    let name_of = |e: Entity| {
+        names
+            .get(e)
+            .map_or("???".to_string(), |n| n.name.to_string())
+    };
+
+    let goods = match contract.kind {
+        ContractKind::Escort { escortee } => {
+            locale.text("escort_of", &[("escortee", &name_of(escortee))])
+        }
+        _ => locale.goods_list(&contract.goods_required),
+    };
+
+    history.records.push(ContractRecord {
+        destination: name_of(contract.destination),
+        goods,
+        completed,
+        earnings,
+        date: date.current_date(),
+    });
+}
+
 pub struct ExpireContractSystem;
 
 impl<'s> System<'s> for ExpireContractSystem {
@@ -217,7 +775,14 @@ impl<'s> System<'s> for ExpireContractSystem {
         ReadStorage<'s, Contract>,
         ReadStorage<'s, OwnedBy>,
         ReadStorage<'s, Expiration>,
-        Write<'s, Notifications>,
+        ReadStorage<'s, Named>,
+        WriteStorage<'s, Cargo>,
+        Read<'s, Date>,
+        Read<'s, Locale>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
+        Write<'s, ContractHistory>,
         Write<'s, EventChannel<UiUpdateEvent>>,
     );
 
@@ -228,19 +793,55 @@ impl<'s> System<'s> for ExpireContractSystem {
             contracts,
             owned_bys,
             expirations,
-            mut notifications,
+            names,
+            mut cargos,
+            date,
+            locale,
+            mut game_events,
+            mut player_status,
+            mut ledger,
+            mut history,
             mut channel,
         ): Self::SystemData,
     ) {
         let mut contracts_to_destroy = HashSet::new();
 
-        for (e, _, expiration) in (&entities, &contracts, &expirations).join() {
+        for (e, contract, expiration) in (&entities, &contracts, &expirations).join() {
             if expiration.expired {
                 contracts_to_destroy.insert(e);
                 match owned_bys.get(e) {
                     Some(_) => channel.single_write(UiUpdateEvent::Target(e)),
-                    None => notifications
-                        .push_back("A contract you have accepted has expired".to_string()),
+                    None => {
+                        let reclaimed = reclaim_goods(contract, &mut cargos);
+                        ledger.record(
+                            &mut player_status,
+                            date.current_date(),
+                            TransactionKind::Penalties,
+                            -(contract.penalty as i32),
+                        );
+
+                        let earnings =
+                            contract.earned as i32 - (contract.deposit + contract.penalty) as i32;
+                        record_outcome(
+                            &mut history,
+                            contract,
+                            &names,
+                            false,
+                            earnings,
+                            &date,
+                            &locale,
+                        );
+                        channel.single_write(UiUpdateEvent::Journal);
+
+                        if !reclaimed.is_empty() || contract.penalty > 0 {
+                            channel.single_write(UiUpdateEvent::PlayerStatus);
+                        }
+                        game_events.single_write(GameEvent::ContractExpired {
+                            reclaimed,
+                            reclaimed_by: contract.origin,
+                            penalty: contract.penalty,
+                        });
+                    }
                 }
             }
         }
@@ -251,27 +852,397 @@ impl<'s> System<'s> for ExpireContractSystem {
     }
 }
 
+pub struct ContractGenerationSystem {
+    reader_id: ReaderId<CalendarEvent>,
+    last_time_elapsed: f64,
+    progress: HashMap<Entity, f64>,
+}
+
+impl ContractGenerationSystem {
+    fn new(reader_id: ReaderId<CalendarEvent>) -> Self {
+        ContractGenerationSystem {
+            reader_id,
+            last_time_elapsed: 0.0,
+            progress: HashMap::new(),
+        }
+    }
+}
+
+impl<'s> System<'s> for ContractGenerationSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Port>,
+        ReadStorage<'s, Market>,
+        ReadStorage<'s, Ship>,
+        ReadStorage<'s, Pirate>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, Contract>,
+        WriteStorage<'s, OwnedBy>,
+        WriteStorage<'s, Expiration>,
+        Read<'s, Date>,
+        Read<'s, EventChannel<CalendarEvent>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            ports,
+            markets,
+            ships,
+            pirates,
+            controllables,
+            locals,
+            mut contracts,
+            mut owned_bys,
+            mut expirations,
+            date,
+            calendar_channel,
+            mut channel,
+        ): Self::SystemData,
+    ) {
+        for event in calendar_channel.read(&mut self.reader_id) {
+            if let CalendarEvent::FleetSailing { port } = event {
+                *self.progress.entry(*port).or_insert(0.0) += FLEET_SAILING_CONTRACTS;
+            }
+        }
+
+        let days_passed = (date.time_elapsed - self.last_time_elapsed) / SECONDS_IN_DAY;
+        self.last_time_elapsed = date.time_elapsed;
+
+        if days_passed <= 0.0 {
+            return;
+        }
+
+        let port_locations = (&entities, &ports, &locals)
+            .join()
+            .map(|(e, _, l)| (e, Point2::new(l.translation().x, l.translation().y)))
+            .collect::<Vec<_>>();
+
+        let pirate_locations = (&pirates, &locals)
+            .join()
+            .map(|(_, l)| Point2::new(l.translation().x, l.translation().y))
+            .collect::<Vec<_>>();
+
+        // Number of pirates near the route between two points
+        let route_risk = |from: Point2<f32>, to: Point2<f32>| {
+            pirate_locations
+                .iter()
+                .filter(|&&p| distance_to_segment(p, from, to) < RISK_DISTANCE)
+                .count() as u32
+        };
+
+        // Merchants that already have an escort on offer
+        let mut escorted = contracts
+            .join()
+            .filter_map(|c| match c.kind {
+                ContractKind::Escort { escortee } => Some(escortee),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        let mut rng = thread_rng();
+
+        for &(port, port_location) in &port_locations {
+            let market = match markets.get(port) {
+                Some(market) => market,
+                None => continue,
+            };
+
+            let open_contracts = (&contracts, &owned_bys)
+                .join()
+                .filter(|(_, o)| o.entity == port)
+                .count();
+
+            let progress = self.progress.entry(port).or_insert(0.0);
+            *progress += market.size as f64 * days_passed / DAYS_PER_CONTRACT;
+
+            let mut new_contracts = 0;
+            while *progress >= 1.0 && open_contracts + new_contracts < MAX_OPEN_CONTRACTS_PER_PORT {
+                *progress -= 1.0;
+
+                let destinations = port_locations
+                    .iter()
+                    .filter(|(d, _)| *d != port)
+                    .collect::<Vec<_>>();
+
+                // Larger ports nearby are favoured as destinations
+                let destination = destinations.choose_weighted(&mut rng, |(d, l)| {
+                    let size = markets.get(*d).map_or(1, |m| m.size) as f32;
+                    size / (1.0 + l.distance(&port_location) / DESTINATION_DISTANCE_FALLOFF)
+                });
+
+                let (destination, destination_location) = match destination {
+                    Ok(destination) => **destination,
+                    Err(_) => break,
+                };
+
+                let destination_market = markets.get(destination);
+                let distance = port_location.distance(&destination_location);
+                let risk = route_risk(port_location, destination_location);
+
+                let escortee = (&entities, &ships, &locals, !&pirates, !&controllables)
+                    .join()
+                    .filter(|(e, _, l, _, _)| {
+                        Point2::new(l.translation().x, l.translation().y).distance(&port_location)
+                            < ESCORT_PICKUP_DISTANCE
+                            && !escorted.contains(e)
+                    })
+                    .map(|(e, _, _, _, _)| e)
+                    .next();
+
+                let mut kind_weights = CONTRACT_KIND_WEIGHTS;
+                if destinations.len() < 2 {
+                    kind_weights[4] = 0;
+                }
+                if escortee.is_none() {
+                    kind_weights[5] = 0;
+                }
+
+                let mut kind = ContractKind::Delivery;
+                let mut destination = destination;
+                let mut days_ahead =
+                    MIN_CONTRACT_DAYS + (distance / DISTANCE_PER_EXTRA_CONTRACT_DAY) as i64;
+                // Deliveries can be made late for reduced payment until the contract expires
+                let mut days_late_allowed = rng.gen_range(0..10);
+
+                let (goods_required, payment) = match WeightedIndex::new(&kind_weights)
+                    .unwrap()
+                    .sample(&mut rng)
+                {
+                    1 => {
+                        // Passengers won't wait around, so there's no slack on the deadline
+                        kind = ContractKind::Passengers;
+                        days_late_allowed = 0;
+                        let goods_required = [(ItemType::Passengers, rng.gen_range(2..21))]
+                            .iter()
+                            .cloned()
+                            .collect();
+                        let payment = contract_payment(&goods_required, None, distance, risk)
+                            as f32
+                            * PASSENGER_FARE_MULTIPLIER;
+                        (goods_required, round_payment(payment))
+                    }
+                    2 => {
+                        kind = ContractKind::Mail;
+                        days_late_allowed = 0;
+                        let goods_required = [(ItemType::Mail, rng.gen_range(1..4))]
+                            .iter()
+                            .cloned()
+                            .collect();
+                        let payment = contract_payment(&goods_required, None, distance, risk)
+                            as f32
+                            * MAIL_PAYMENT_MULTIPLIER;
+                        (goods_required, round_payment(payment))
+                    }
+                    3 => {
+                        kind = ContractKind::Smuggling {
+                            inspection_chance: rng.gen_range(0.1..0.4),
+                        };
+                        let goods_required = [(ItemType::Contraband, rng.gen_range(1..11))]
+                            .iter()
+                            .cloned()
+                            .collect();
+                        let payment = contract_payment(&goods_required, None, distance, risk)
+                            as f32
+                            * SMUGGLING_PAYMENT_MULTIPLIER;
+                        (goods_required, round_payment(payment))
+                    }
+                    4 => {
+                        let number_of_drops =
+                            rng.gen_range(2..=destinations.len().min(MAX_BULK_DROPS));
+                        let drops = destinations
+                            .choose_multiple(&mut rng, number_of_drops)
+                            .map(|&&(drop_destination, drop_location)| {
+                                let drop_market = markets.get(drop_destination);
+                                let goods = generate_goods(&mut rng, drop_market);
+                                let payment = contract_payment(
+                                    &goods,
+                                    drop_market,
+                                    port_location.distance(&drop_location),
+                                    route_risk(port_location, drop_location),
+                                );
+                                DropOff {
+                                    destination: drop_destination,
+                                    goods,
+                                    payment,
+                                    delivered: false,
+                                }
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut goods_required = HashMap::new();
+                        for drop_off in &drops {
+                            for (item, amount) in &drop_off.goods {
+                                *goods_required.entry(*item).or_insert(0) += amount;
+                            }
+                        }
+
+                        // Extra time for each stop along the way
+                        let longest_distance = drops
+                            .iter()
+                            .filter_map(|d| {
+                                port_locations.iter().find(|(p, _)| *p == d.destination)
+                            })
+                            .map(|(_, l)| port_location.distance(l))
+                            .fold(0.0, f32::max);
+                        days_ahead = MIN_CONTRACT_DAYS * drops.len() as i64
+                            + (longest_distance / DISTANCE_PER_EXTRA_CONTRACT_DAY) as i64;
+
+                        let payment = drops.iter().map(|d| d.payment).sum();
+                        destination = drops.last().unwrap().destination;
+                        kind = ContractKind::Bulk { drops };
+                        (goods_required, payment)
+                    }
+                    5 => {
+                        let escortee = escortee.unwrap();
+                        escorted.insert(escortee);
+                        kind = ContractKind::Escort { escortee };
+                        let payment = ESCORT_PAYMENT_PER_DISTANCE
+                            * distance
+                            * (1.0 + RISK_PREMIUM * risk as f32);
+                        (HashMap::new(), round_payment(payment))
+                    }
+                    _ => {
+                        let goods_required = generate_goods(&mut rng, destination_market);
+                        let payment =
+                            contract_payment(&goods_required, destination_market, distance, risk);
+                        (goods_required, payment)
+                    }
+                };
+
+                let due_date = date.current_date().add(Duration::days(days_ahead));
+
+                let contract = entities
+                    .build_entity()
+                    .with(
+                        Contract {
+                            kind,
+                            deposit: round_payment(payment as f32 * DEPOSIT_PROPORTION),
+                            penalty: round_payment(payment as f32 * PENALTY_PROPORTION),
+                            due_date: if days_late_allowed > 0 {
+                                Some(due_date)
+                            } else {
+                                None
+                            },
+                            ..Contract::new(payment, destination, goods_required)
+                        },
+                        &mut contracts,
+                    )
+                    .with(OwnedBy { entity: port }, &mut owned_bys)
+                    .with(
+                        Expiration {
+                            expiration_date: due_date.add(Duration::days(days_late_allowed)),
+                            expired: false,
+                        },
+                        &mut expirations,
+                    )
+                    .build();
+
+                channel.single_write(UiUpdateEvent::Target(contract));
+                new_contracts += 1;
+            }
+
+            // Ports with a full board don't build up a backlog of contracts
+            if open_contracts + new_contracts >= MAX_OPEN_CONTRACTS_PER_PORT {
+                *progress = (*progress).min(1.0);
+            }
+        }
+    }
+}
+
+pub struct ContractGenerationSystemDesc;
+
+impl Default for ContractGenerationSystemDesc {
+    fn default() -> Self {
+        ContractGenerationSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, ContractGenerationSystem> for ContractGenerationSystemDesc {
+    fn build(self, world: &mut World) -> ContractGenerationSystem {
+        <ContractGenerationSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<CalendarEvent>>()
+            .register_reader();
+
+        ContractGenerationSystem::new(reader_id)
+    }
+}
+
+fn generate_goods<R: Rng>(
+    rng: &mut R,
+    destination_market: Option<&Market>,
+) -> HashMap<ItemType, u32> {
+    let mut goods = HashMap::new();
+    for _ in 0..rng.gen_range(1..4) {
+        // Goods in demand at the destination are asked for more often
+        let item_type = TRADE_GOODS
+            .choose_weighted(rng, |item| {
+                destination_market.map_or(1.0, |m| m.demand_for(item))
+            })
+            .map_or_else(|_| ItemType::choose(), |item| *item);
+        *goods.entry(item_type).or_insert(0) += rng.gen_range(1..11);
+    }
+    goods
+}
+
+pub fn contract_payment(
+    goods_required: &HashMap<ItemType, u32>,
+    destination_market: Option<&Market>,
+    distance: f32,
+    risk: u32,
+) -> u32 {
+    let weighted_tons = goods_required
+        .iter()
+        .map(|(item, amount)| {
+            *amount as f32 * destination_market.map_or(1.0, |m| m.demand_for(item))
+        })
+        .sum::<f32>();
+
+    let payment = weighted_tons
+        * (BASE_PAYMENT_PER_TON + PAYMENT_PER_TON_PER_DISTANCE * distance)
+        * (1.0 + RISK_PREMIUM * risk as f32);
+
+    round_payment(payment)
+}
+
+// Round to the nearest £10
+fn round_payment(payment: f32) -> u32 {
+    ((payment / 10.0).round() as u32).max(1) * 10
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::{Cargo, Contract, ItemType, OwnedBy};
+    use crate::components::{Cargo, Contract, ContractKind, DropOff, ItemType, OwnedBy};
     use amethyst::{core::WithNamed, ecs::Entity, prelude::*, Result};
     use amethyst_test::prelude::*;
     use chrono::{TimeZone, Utc};
     use std::collections::HashMap;
 
+    fn register_game_event_reader(world: &mut World) {
+        let reader_id = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        world.insert(reader_id);
+    }
+
+    fn game_events(world: &World) -> Vec<GameEvent> {
+        let channel = world.fetch::<EventChannel<GameEvent>>();
+        let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+        channel.read(&mut reader_id).cloned().collect()
+    }
+
     #[test]
     fn accepting_contract_sends_ui_update_event() -> Result<()> {
         AmethystApplication::blank()
             .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
             .with_effect(|world| {
-                let port = world
-                    .create_entity()
-                    .named("Portsmouth")
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
-                    .build();
+                let port = world.create_entity().named("Portsmouth").build();
                 let destination = world.create_entity().build();
 
                 let contract = world
@@ -325,13 +1296,7 @@ mod tests {
         AmethystApplication::blank()
             .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
             .with_effect(|world| {
-                let port = world
-                    .create_entity()
-                    .named("Portsmouth")
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
-                    .build();
+                let port = world.create_entity().named("Portsmouth").build();
                 let destination = world.create_entity().build();
 
                 let contract = world
@@ -375,7 +1340,7 @@ mod tests {
     }
 
     #[test]
-    fn accepted_contract_goods_in_port_cargo() -> Result<()> {
+    fn accepted_contract_goods_reserved_for_contract() -> Result<()> {
         let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
             .iter()
             .cloned()
@@ -386,15 +1351,7 @@ mod tests {
         AmethystApplication::blank()
             .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
             .with_effect(move |world| {
-                let port = world
-                    .create_entity()
-                    .named("Portsmouth")
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
-                    .build();
-
-                world.insert(EffectReturn(port));
+                let port = world.create_entity().named("Portsmouth").build();
 
                 let destination = world.create_entity().build();
 
@@ -404,6 +1361,8 @@ mod tests {
                     .with(OwnedBy { entity: port })
                     .build();
 
+                world.insert(EffectReturn((port, contract)));
+
                 let ui_entity = world
                     .create_entity()
                     .with(OwnedBy { entity: contract })
@@ -422,49 +1381,103 @@ mod tests {
                 });
             })
             .with_assertion(move |world| {
-                let port_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
-                let cargos = world.read_storage::<Cargo>();
-                let port_cargo = cargos.get(port_entity).unwrap();
-
-                for (k, v) in goods_required_2.iter() {
-                    assert!(
-                        port_cargo.items.contains_key(&k),
-                        format!("Cargo should contain item type {}", k)
-                    );
-                    assert_eq!(
-                        *v,
-                        port_cargo.items[&k],
-                        "{}",
-                        format!("Number of {} in cargo", k)
-                    );
-                }
+                let (port, contract) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+                let contracts = world.read_storage::<Contract>();
+                let contract = contracts.get(contract).unwrap();
+                assert_eq!(goods_required_2, contract.reserved, "Reserved goods");
+                assert_eq!(None, contract.carrier, "Carrier");
+                assert_eq!(Some(port), contract.origin, "Origin");
             })
             .run()
     }
 
     #[test]
-    fn accepted_contract_sends_notification() -> Result<()> {
-        const PORT: &str = "Portsmouth";
-
-        let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
-            .iter()
-            .cloned()
-            .collect();
-
+    fn accepted_contract_goods_reserved_for_selected_ship() -> Result<()> {
         AmethystApplication::blank()
             .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
             .with_effect(move |world| {
-                let port = world
+                let port = world.create_entity().named("Portsmouth").build();
+
+                let ship = world
                     .create_entity()
-                    .named(PORT)
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
+                    .named("Mary Rose")
+                    .with(Controllable)
+                    .with(Selected)
                     .build();
 
-                world.insert(EffectReturn(port));
+                let destination = world.create_entity().build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract::new(
+                        0,
+                        destination,
+                        [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                    ))
+                    .with(OwnedBy { entity: port })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn((port, destination, ship, contract)));
+
+                let ui_entity = world
+                    .create_entity()
+                    .with(OwnedBy { entity: contract })
+                    .build();
+
+                let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
+                channel.single_write(UiEvent {
+                    event_type: UiEventType::ClickStop,
+                    target: ui_entity,
+                });
+            })
+            .with_assertion(move |world| {
+                let (port, destination, ship, contract) = world
+                    .read_resource::<EffectReturn<(Entity, Entity, Entity, Entity)>>()
+                    .0;
+                let contracts = world.read_storage::<Contract>();
+                assert_eq!(
+                    Some(ship),
+                    contracts.get(contract).unwrap().carrier,
+                    "Carrier"
+                );
+
+                assert_eq!(
+                    vec![GameEvent::ContractAccepted {
+                        port,
+                        destination,
+                        kind: ContractKind::Delivery,
+                        carrier: Some(ship),
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        deposit: 0,
+                        expires: None,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn accepted_contract_sends_game_event() -> Result<()> {
+        const PORT: &str = "Portsmouth";
+
+        let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+            .iter()
+            .cloned()
+            .collect();
+        let goods_required_2 = goods_required.clone();
+
+        AmethystApplication::blank()
+            .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
+            .with_effect(move |world| {
+                let port = world.create_entity().named(PORT).build();
 
                 let destination = world.create_entity().build();
+                world.insert(EffectReturn((port, destination)));
 
                 let contract = world
                     .create_entity()
@@ -477,11 +1490,7 @@ mod tests {
                     .with(OwnedBy { entity: contract })
                     .build();
 
-                let reader_id = world
-                    .fetch_mut::<EventChannel<UiUpdateEvent>>()
-                    .register_reader();
-
-                world.insert(reader_id);
+                register_game_event_reader(world);
 
                 let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
                 channel.single_write(UiEvent {
@@ -490,25 +1499,27 @@ mod tests {
                 });
             })
             .with_assertion(move |world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
+                let (port, destination) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
                 assert_eq!(
-                    &format!(
-                        "Contract accepted. 5 tons of Rum, 10 tons of Sugar ready to be loaded at {}.",
-                        PORT
-                    ),
-                    notifications.front().unwrap(),
-                    "Notification"
+                    vec![GameEvent::ContractAccepted {
+                        port,
+                        destination,
+                        kind: ContractKind::Delivery,
+                        carrier: None,
+                        goods: goods_required_2,
+                        deposit: 0,
+                        expires: None,
+                    }],
+                    game_events(world)
                 );
             })
             .run()
     }
 
     #[test]
-    fn accepted_contract_expiration_sends_notification() -> Result<()> {
+    fn accepted_contract_event_includes_expiration() -> Result<()> {
         const PORT: &str = "Portsmouth";
         let expiration_date = Utc.ymd(1680, 1, 1);
-        let expiration_date_cloned = expiration_date.clone();
 
         let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
             .iter()
@@ -518,15 +1529,7 @@ mod tests {
         AmethystApplication::blank()
             .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
             .with_effect(move |world| {
-                let port = world
-                    .create_entity()
-                    .named(PORT)
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
-                    .build();
-
-                world.insert(EffectReturn(port));
+                let port = world.create_entity().named(PORT).build();
 
                 let destination = world.create_entity().build();
 
@@ -534,7 +1537,10 @@ mod tests {
                     .create_entity()
                     .with(Contract::new(0, destination, goods_required))
                     .with(OwnedBy { entity: port })
-                    .with(Expiration { expired: false, expiration_date: expiration_date})
+                    .with(Expiration {
+                        expired: false,
+                        expiration_date: expiration_date,
+                    })
                     .build();
 
                 let ui_entity = world
@@ -542,11 +1548,7 @@ mod tests {
                     .with(OwnedBy { entity: contract })
                     .build();
 
-                let reader_id = world
-                    .fetch_mut::<EventChannel<UiUpdateEvent>>()
-                    .register_reader();
-
-                world.insert(reader_id);
+                register_game_event_reader(world);
 
                 let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
                 channel.single_write(UiEvent {
@@ -555,17 +1557,14 @@ mod tests {
                 });
             })
             .with_assertion(move |world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
-                assert_eq!(
-                    &format!(
-                        "Contract accepted. 5 tons of Rum, 10 tons of Sugar ready to be loaded at {}. It will expire on {}.",
-                        PORT,
-                        expiration_date_cloned.format("%e %B %Y")
-                    ),
-                    notifications.front().unwrap(),
-                    "Notification"
-                );
+                let events = game_events(world);
+                assert_eq!(1, events.len(), "Number of events");
+                match &events[0] {
+                    GameEvent::ContractAccepted { expires, .. } => {
+                        assert_eq!(Some(expiration_date), *expires, "Expires")
+                    }
+                    event => panic!("Expected `ContractAccepted`, got {:?}", event),
+                }
             })
             .run()
     }
@@ -589,9 +1588,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -600,7 +1600,10 @@ mod tests {
 
                 let contract = world
                     .create_entity()
-                    .with(Contract::new(0, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(0, port, goods_required.clone())
+                    })
                     .build();
 
                 let reader_id = world
@@ -639,9 +1642,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -650,7 +1654,10 @@ mod tests {
 
                 let contract = world
                     .create_entity()
-                    .with(Contract::new(0, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(0, port, goods_required.clone())
+                    })
                     .build();
 
                 let reader_id = world
@@ -691,9 +1698,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -702,7 +1710,10 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(0, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(0, port, goods_required.clone())
+                    })
                     .build();
 
                 let reader_id = world
@@ -741,9 +1752,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -752,12 +1764,26 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(PAYMENT, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
                     .build();
             })
             .with_assertion(|world| {
                 let player_status = world.fetch::<PlayerStatus>();
                 assert_eq!(PAYMENT as i32, player_status.money);
+
+                let ledger = world.fetch::<Ledger>();
+                let transactions = ledger
+                    .transactions
+                    .iter()
+                    .map(|t| (t.kind, t.amount))
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    vec![(TransactionKind::ContractIncome, PAYMENT as i32)],
+                    transactions
+                );
             })
             .run()
     }
@@ -798,6 +1824,7 @@ mod tests {
                 let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: original_goods_on_ship.clone(),
                     })
@@ -806,7 +1833,10 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(0, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(0, port, goods_required.clone())
+                    })
                     .build();
 
                 world.insert(EffectReturn(ship));
@@ -833,13 +1863,14 @@ mod tests {
     }
 
     #[test]
-    fn fulfilling_contract_sends_notification() -> Result<()> {
+    fn fulfilling_contract_sends_game_event() -> Result<()> {
         const PAYMENT: u32 = 100;
         const PORT: &str = "London";
         let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
             .iter()
             .cloned()
             .collect();
+        let goods_required_2 = goods_required.clone();
 
         AmethystApplication::blank()
             .with_system(FulfillContractSystem, "fulfill_contract", &[])
@@ -853,9 +1884,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -864,19 +1896,26 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(PAYMENT, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
                     .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn(port));
             })
             .with_assertion(move |world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
                 assert_eq!(
-                    &format!(
-                        "Completed contract for £{} at {}. 5 tons of Rum, 10 tons of Sugar removed from cargo.",
-                        PAYMENT, PORT
-                    ),
-                    notifications.front().unwrap(),
-                    "Notification"
+                    vec![GameEvent::ContractFulfilled {
+                        port,
+                        kind: ContractKind::Delivery,
+                        goods: goods_required_2,
+                        earned: PAYMENT,
+                        deposit: 0,
+                    }],
+                    game_events(world)
                 );
             })
             .run()
@@ -903,9 +1942,10 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
                         items: goods_required.clone(),
                     })
@@ -920,7 +1960,10 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(PAYMENT, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
                     .with(OwnedBy { entity })
                     .build();
             })
@@ -952,11 +1995,12 @@ mod tests {
                     .with(Transform::default())
                     .build();
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Cargo {
-                        items: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        items: [(ItemType::Whiskey, 10)].iter().cloned().collect(),
                     })
                     .with(Transform::default())
                     .build();
@@ -967,8 +2011,131 @@ mod tests {
 
                 world
                     .create_entity()
-                    .with(Contract::new(PAYMENT, port, goods_required.clone()))
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
+                    .build();
+            })
+            .with_assertion(|world| {
+                let player_status = world.fetch::<PlayerStatus>();
+                assert_eq!(ORIGINAL_MONEY, player_status.money);
+            })
+            .run()
+    }
+
+    #[test]
+    fn contracts_only_take_goods_from_their_own_carrier() -> Result<()> {
+        let sugar = |amount: u32| -> HashMap<ItemType, u32> {
+            [(ItemType::Sugar, amount)].iter().cloned().collect()
+        };
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                // The first carrier has enough sugar for both contracts
+                let first_carrier = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo { items: sugar(20) })
+                    .with(Transform::default())
+                    .build();
+                let second_carrier = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo { items: sugar(10) })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(first_carrier),
+                        ..Contract::new(100, port, sugar(10))
+                    })
+                    .build();
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(second_carrier),
+                        ..Contract::new(100, port, sugar(10))
+                    })
+                    .build();
+
+                world.insert(EffectReturn((first_carrier, second_carrier)));
+            })
+            .with_assertion(move |world| {
+                let (first_carrier, second_carrier) =
+                    world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+                assert_eq!(200, world.fetch::<PlayerStatus>().money, "Money");
+
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    sugar(10),
+                    cargos.get(first_carrier).unwrap().items,
+                    "Cargo on first carrier"
+                );
+                assert_eq!(
+                    sugar(0),
+                    cargos.get(second_carrier).unwrap().items,
+                    "Cargo on second carrier"
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn contract_not_fulfilled_while_ship_unloading() -> Result<()> {
+        const ORIGINAL_MONEY: i32 = 10;
+
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Sugar, 10)].iter().cloned().collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract::new(30, port, goods_required.clone()))
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: goods_required.clone(),
+                    })
+                    .with(CargoTransfer {
+                        contracts: vec![contract],
+                        unloading: true,
+                        complete_at: 1000.0,
+                    })
+                    .with(Transform::default())
                     .build();
+                world
+                    .write_storage::<Contract>()
+                    .get_mut(contract)
+                    .unwrap()
+                    .carrier = Some(ship);
+
+                world.insert(PlayerStatus {
+                    money: ORIGINAL_MONEY,
+                });
             })
             .with_assertion(|world| {
                 let player_status = world.fetch::<PlayerStatus>();
@@ -1138,7 +2305,7 @@ mod tests {
     }
 
     #[test]
-    fn player_expired_contract_sends_notification() -> Result<()> {
+    fn player_expired_contract_sends_game_event() -> Result<()> {
         AmethystApplication::blank()
             .with_system(ExpireContractSystem, "expire_contract", &[])
             .with_effect(|world| {
@@ -1160,23 +2327,1181 @@ mod tests {
                     })
                     .build();
 
+                register_game_event_reader(world);
                 world.insert(EffectReturn(contract));
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let contract = world.read_resource::<EffectReturn<Entity>>().0;
+                assert!(!world.entities().is_alive(contract), "Contract removed");
 
-                let reader_id = world
-                    .fetch_mut::<EventChannel<UiUpdateEvent>>()
-                    .register_reader();
+                assert_eq!(
+                    vec![GameEvent::ContractExpired {
+                        reclaimed: HashMap::new(),
+                        reclaimed_by: None,
+                        penalty: 0,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
 
-                world.insert(reader_id);
+    #[test]
+    fn passenger_contract_fulfilled_when_passengers_aboard() -> Result<()> {
+        const PAYMENT: u32 = 150;
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Passengers, 12)].iter().cloned().collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .named("London")
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: goods_required.clone(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        kind: ContractKind::Passengers,
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn(port));
             })
             .with_assertion(|world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
-                let notification = notifications.front().unwrap();
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+                let player_status = world.fetch::<PlayerStatus>();
+                assert_eq!(PAYMENT as i32, player_status.money);
+
                 assert_eq!(
-                    "A contract you have accepted has expired", notification,
-                    "Notification message"
+                    vec![GameEvent::ContractFulfilled {
+                        port,
+                        kind: ContractKind::Passengers,
+                        goods: [(ItemType::Passengers, 12)].iter().cloned().collect(),
+                        earned: PAYMENT,
+                        deposit: 0,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    fn create_escort_contract(world: &mut World, player_nearby: bool) -> (Entity, Entity) {
+        let port = world
+            .create_entity()
+            .named("London")
+            .with(Cargo::default())
+            .with(Transform::default())
+            .build();
+
+        let escortee = world
+            .create_entity()
+            .named("Mary Rose")
+            .with(Ship { base_speed: 1.0 })
+            .with(Cargo::default())
+            .with(Transform::default())
+            .build();
+
+        let mut player_transform = Transform::default();
+        if !player_nearby {
+            player_transform.set_translation_xyz(100.0, 0.0, 0.0);
+        }
+
+        world
+            .create_entity()
+            .with(Ship { base_speed: 1.0 })
+            .with(Controllable)
+            .with(Cargo::default())
+            .with(player_transform)
+            .build();
+
+        let contract = world
+            .create_entity()
+            .with(Contract {
+                kind: ContractKind::Escort { escortee },
+                ..Contract::new(100, port, HashMap::new())
+            })
+            .build();
+
+        (contract, escortee)
+    }
+
+    #[test]
+    fn escort_contract_fulfilled_when_escorted_ship_and_player_arrive() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                let (contract, _) = create_escort_contract(world, true);
+                world.insert(EffectReturn(contract));
+            })
+            .with_assertion(|world| {
+                let contract_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let contracts = world.read_storage::<Contract>();
+                assert!(
+                    contracts.get(contract_entity).unwrap().fulfilled,
+                    "Contract fulfilled"
                 );
+                assert_eq!(100, world.fetch::<PlayerStatus>().money, "Money");
             })
             .run()
     }
+
+    #[test]
+    fn escort_contract_not_fulfilled_without_player_ship() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                let (contract, _) = create_escort_contract(world, false);
+                world.insert(EffectReturn(contract));
+            })
+            .with_assertion(|world| {
+                let contract_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let contracts = world.read_storage::<Contract>();
+                assert!(
+                    !contracts.get(contract_entity).unwrap().fulfilled,
+                    "Contract fulfilled"
+                );
+                assert_eq!(0, world.fetch::<PlayerStatus>().money, "Money");
+            })
+            .run()
+    }
+
+    #[test]
+    fn escort_contract_fails_if_escorted_ship_is_lost() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                let (contract, escortee) = create_escort_contract(world, true);
+                world.delete_entity(escortee).unwrap();
+                register_game_event_reader(world);
+                world.insert(EffectReturn(contract));
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let contract_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                assert!(
+                    !world.entities().is_alive(contract_entity),
+                    "Contract deleted"
+                );
+                assert_eq!(0, world.fetch::<PlayerStatus>().money, "Money");
+
+                assert_eq!(
+                    vec![GameEvent::EscortLost { penalty: 0 }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn accepting_escort_contract_sets_course_for_escorted_ship() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("Portsmouth").build();
+
+                let mut destination_transform = Transform::default();
+                destination_transform.set_translation_xyz(100.0, 50.0, 0.0);
+                let destination = world
+                    .create_entity()
+                    .named("London")
+                    .with(destination_transform)
+                    .build();
+
+                let escortee = world
+                    .create_entity()
+                    .named("Mary Rose")
+                    .with(Transform::default())
+                    .build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        kind: ContractKind::Escort { escortee },
+                        ..Contract::new(100, destination, HashMap::new())
+                    })
+                    .with(OwnedBy { entity: port })
+                    .build();
+
+                let ui_entity = world
+                    .create_entity()
+                    .with(OwnedBy { entity: contract })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn((port, destination, escortee)));
+
+                let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
+                channel.single_write(UiEvent {
+                    event_type: UiEventType::ClickStop,
+                    target: ui_entity,
+                });
+            })
+            .with_assertion(|world| {
+                let (port, destination, escortee) = world
+                    .read_resource::<EffectReturn<(Entity, Entity, Entity)>>()
+                    .0;
+                let courses = world.read_storage::<Course>();
+                let course = courses
+                    .get(escortee)
+                    .expect("Escorted ship should have a course");
+                assert_eq!(
+                    VecDeque::from(vec![Point2::new(100.0, 50.0)]),
+                    course.waypoints,
+                    "Waypoints"
+                );
+
+                assert_eq!(
+                    vec![GameEvent::ContractAccepted {
+                        port,
+                        destination,
+                        kind: ContractKind::Escort { escortee },
+                        carrier: None,
+                        goods: HashMap::new(),
+                        deposit: 0,
+                        expires: None,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn escorted_ship_sails_around_land_between_ports() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
+            .with_effect(|world| {
+                world.insert(Map::new(vec![vec![
+                    Point2::new(50, 0),
+                    Point2::new(100, 25),
+                    Point2::new(100, -25),
+                ]]));
+
+                let port = world.create_entity().build();
+
+                let mut destination_transform = Transform::default();
+                destination_transform.set_translation_xyz(120.0, 0.0, 0.0);
+                let destination = world.create_entity().with(destination_transform).build();
+
+                let escortee = world.create_entity().with(Transform::default()).build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        kind: ContractKind::Escort { escortee },
+                        ..Contract::new(100, destination, HashMap::new())
+                    })
+                    .with(OwnedBy { entity: port })
+                    .build();
+
+                let ui_entity = world
+                    .create_entity()
+                    .with(OwnedBy { entity: contract })
+                    .build();
+
+                world.insert(EffectReturn(escortee));
+
+                let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
+                channel.single_write(UiEvent {
+                    event_type: UiEventType::ClickStop,
+                    target: ui_entity,
+                });
+            })
+            .with_assertion(|world| {
+                let escortee = world.read_resource::<EffectReturn<Entity>>().0;
+                let map = world.read_resource::<Map>();
+                let courses = world.read_storage::<Course>();
+                let course = courses
+                    .get(escortee)
+                    .expect("Escorted ship should have a course");
+
+                assert_eq!(2, course.waypoints.len(), "Waypoints");
+                assert_eq!(
+                    Some(&Point2::new(120.0, 0.0)),
+                    course.waypoints.back(),
+                    "Last waypoint"
+                );
+
+                let mut from = Point2::new(0.0, 0.0);
+                for &to in &course.waypoints {
+                    assert_eq!(
+                        None,
+                        map.closest_point_of_line_on_edge(from, to - from, true),
+                        "Leg from {:?} to {:?} crosses land",
+                        from,
+                        to
+                    );
+                    from = to;
+                }
+            })
+            .run()
+    }
+
+    fn create_smuggling_contract(world: &mut World, inspection_chance: f32) -> (Entity, Entity) {
+        let port = world
+            .create_entity()
+            .named("London")
+            .with(Transform::default())
+            .build();
+
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Contraband, 5)].iter().cloned().collect();
+
+        let ship = world
+            .create_entity()
+            .with(Ship { base_speed: 1.0 })
+            .with(Docked { port })
+            .with(Cargo {
+                items: goods_required.clone(),
+            })
+            .with(Transform::default())
+            .build();
+
+        world
+            .create_entity()
+            .with(Contract {
+                carrier: Some(ship),
+                kind: ContractKind::Smuggling { inspection_chance },
+                ..Contract::new(200, port, goods_required)
+            })
+            .build();
+
+        (port, ship)
+    }
+
+    #[test]
+    fn smuggled_goods_seized_and_fined_when_inspected() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                let (port, ship) = create_smuggling_contract(world, 1.0);
+                register_game_event_reader(world);
+                world.insert(EffectReturn((port, ship)));
+            })
+            .with_assertion(|world| {
+                let (port, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    0,
+                    cargos.get(ship).unwrap().items[&ItemType::Contraband],
+                    "Contraband on ship"
+                );
+                assert_eq!(-100, world.fetch::<PlayerStatus>().money, "Money");
+
+                assert_eq!(
+                    vec![GameEvent::GoodsSeized {
+                        port,
+                        goods: [(ItemType::Contraband, 5)].iter().cloned().collect(),
+                        fine: 100,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn smuggling_contract_paid_when_not_inspected() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                create_smuggling_contract(world, 0.0);
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<UiUpdateEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+            })
+            .with_assertion(|world| {
+                assert_eq!(200, world.fetch::<PlayerStatus>().money, "Money");
+
+                let channel = world.fetch_mut::<EventChannel<UiUpdateEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<UiUpdateEvent>>();
+                assert!(
+                    channel
+                        .read(&mut reader_id)
+                        .any(|event| *event == UiUpdateEvent::Journal),
+                    "Journal updated"
+                );
+            })
+            .run()
+    }
+
+    fn create_bulk_contract(world: &mut World, ship_goods: HashMap<ItemType, u32>) -> Entity {
+        let first_port = world
+            .create_entity()
+            .named("London")
+            .with(Cargo::default())
+            .with(Transform::default())
+            .build();
+
+        let mut second_port_transform = Transform::default();
+        second_port_transform.set_translation_xyz(100.0, 0.0, 0.0);
+        let second_port = world
+            .create_entity()
+            .named("Liverpool")
+            .with(Cargo::default())
+            .with(second_port_transform)
+            .build();
+
+        let ship = world
+            .create_entity()
+            .with(Ship { base_speed: 1.0 })
+            .with(Docked { port: first_port })
+            .with(Cargo { items: ship_goods })
+            .with(Transform::default())
+            .build();
+
+        let drops = vec![
+            DropOff {
+                destination: first_port,
+                goods: [(ItemType::Rum, 5)].iter().cloned().collect(),
+                payment: 50,
+                delivered: false,
+            },
+            DropOff {
+                destination: second_port,
+                goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                payment: 100,
+                delivered: false,
+            },
+        ];
+
+        world
+            .create_entity()
+            .with(Contract {
+                carrier: Some(ship),
+                kind: ContractKind::Bulk { drops },
+                ..Contract::new(
+                    150,
+                    second_port,
+                    [(ItemType::Rum, 5), (ItemType::Sugar, 10)]
+                        .iter()
+                        .cloned()
+                        .collect(),
+                )
+            })
+            .build()
+    }
+
+    #[test]
+    fn bulk_contract_pays_per_drop_and_stays_open() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                let contract = create_bulk_contract(
+                    world,
+                    [(ItemType::Rum, 5), (ItemType::Sugar, 10)]
+                        .iter()
+                        .cloned()
+                        .collect(),
+                );
+                world.insert(EffectReturn(contract));
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let contract_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                assert!(
+                    world.entities().is_alive(contract_entity),
+                    "Contract still open"
+                );
+                assert_eq!(50, world.fetch::<PlayerStatus>().money, "Money");
+
+                let contracts = world.read_storage::<Contract>();
+                let contract = contracts.get(contract_entity).unwrap();
+                assert!(!contract.fulfilled, "Contract fulfilled");
+                match &contract.kind {
+                    ContractKind::Bulk { drops } => {
+                        assert!(drops[0].delivered, "First drop delivered");
+                        assert!(!drops[1].delivered, "Second drop delivered");
+                    }
+                    _ => panic!("Expected a bulk contract"),
+                }
+            })
+            .run()
+    }
+
+    #[test]
+    fn bulk_contract_not_paid_for_drop_without_goods() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(|world| {
+                create_bulk_contract(world, [(ItemType::Sugar, 10)].iter().cloned().collect());
+            })
+            .with_assertion(|world| {
+                assert_eq!(0, world.fetch::<PlayerStatus>().money, "Money");
+            })
+            .run()
+    }
+
+    #[test]
+    fn partial_delivery_pays_pro_rata_and_keeps_contract_open() -> Result<()> {
+        const PAYMENT: u32 = 30;
+        const ORIGINAL_MONEY: i32 = 10;
+
+        let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+            .iter()
+            .cloned()
+            .collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .named("London")
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(PlayerStatus {
+                    money: ORIGINAL_MONEY,
+                });
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(PAYMENT, port, goods_required.clone())
+                    })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn((port, contract)));
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let (port, contract_entity) =
+                    world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+                assert!(
+                    world.entities().is_alive(contract_entity),
+                    "Contract still open"
+                );
+
+                let contracts = world.read_storage::<Contract>();
+                let contract = contracts.get(contract_entity).unwrap();
+                assert!(!contract.fulfilled, "Contract fulfilled");
+                assert_eq!(
+                    [(ItemType::Rum, 5)]
+                        .iter()
+                        .cloned()
+                        .collect::<HashMap<_, _>>(),
+                    contract.remaining_goods(),
+                    "Remaining goods"
+                );
+
+                let player_status = world.fetch::<PlayerStatus>();
+                assert_eq!(ORIGINAL_MONEY + 20, player_status.money);
+
+                assert_eq!(
+                    vec![GameEvent::GoodsDelivered {
+                        port,
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        payment: 20,
+                        still_required: [(ItemType::Rum, 5)].iter().cloned().collect(),
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn completing_contract_returns_deposit() -> Result<()> {
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Sugar, 10)].iter().cloned().collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .named("London")
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: goods_required.clone(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        deposit: 20,
+                        ..Contract::new(100, port, goods_required.clone())
+                    })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+                assert_eq!(120, world.fetch::<PlayerStatus>().money, "Money");
+
+                assert_eq!(
+                    vec![GameEvent::ContractFulfilled {
+                        port,
+                        kind: ContractKind::Delivery,
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        earned: 100,
+                        deposit: 20,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn completed_contract_is_recorded_in_history() -> Result<()> {
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Sugar, 10)].iter().cloned().collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .named("London")
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: goods_required.clone(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        ..Contract::new(100, port, goods_required.clone())
+                    })
+                    .build();
+            })
+            .with_assertion(|world| {
+                let history = world.read_resource::<ContractHistory>();
+                assert_eq!(1, history.records.len(), "Records");
+
+                let record = &history.records[0];
+                assert_eq!("London", record.destination, "Destination");
+                assert_eq!("10 tons of Sugar", record.goods, "Goods");
+                assert!(record.completed, "Completed");
+                assert_eq!(100, record.earnings, "Earnings");
+            })
+            .run()
+    }
+
+    #[test]
+    fn late_delivery_pays_less() -> Result<()> {
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Sugar, 10)].iter().cloned().collect();
+
+        AmethystApplication::blank()
+            .with_system(FulfillContractSystem, "fulfill_contract", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .named("London")
+                    .with(Cargo::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: goods_required.clone(),
+                    })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        carrier: Some(ship),
+                        due_date: Some(Utc.ymd(1680, 1, 1)),
+                        ..Contract::new(100, port, goods_required.clone())
+                    })
+                    .build();
+
+                world.insert(Date {
+                    time_elapsed: 2.0 * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+            })
+            .with_assertion(|world| {
+                assert_eq!(80, world.fetch::<PlayerStatus>().money, "Money");
+            })
+            .run()
+    }
+
+    #[test]
+    fn late_payment_multiplier_decays_with_days_late() {
+        let due_date = Utc.ymd(1680, 1, 10);
+
+        assert_eq!(1.0, late_payment_multiplier(None, Utc.ymd(1680, 2, 1)));
+        assert_eq!(
+            1.0,
+            late_payment_multiplier(Some(due_date), Utc.ymd(1680, 1, 5))
+        );
+        assert_eq!(1.0, late_payment_multiplier(Some(due_date), due_date));
+        assert!(late_payment_multiplier(Some(due_date), Utc.ymd(1680, 1, 12)) < 1.0);
+        assert_eq!(
+            MIN_LATE_PAYMENT_MULTIPLIER,
+            late_payment_multiplier(Some(due_date), Utc.ymd(1680, 6, 1))
+        );
+    }
+
+    #[test]
+    fn accepting_contract_takes_deposit() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("Portsmouth").build();
+                let destination = world.create_entity().build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        deposit: 30,
+                        ..Contract::new(
+                            300,
+                            destination,
+                            [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        )
+                    })
+                    .with(OwnedBy { entity: port })
+                    .build();
+
+                let ui_entity = world
+                    .create_entity()
+                    .with(OwnedBy { entity: contract })
+                    .build();
+
+                world.insert(PlayerStatus { money: 100 });
+                register_game_event_reader(world);
+                world.insert(EffectReturn((contract, port, destination)));
+
+                let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
+                channel.single_write(UiEvent {
+                    event_type: UiEventType::ClickStop,
+                    target: ui_entity,
+                });
+            })
+            .with_assertion(|world| {
+                let (contract, port, destination) = world
+                    .read_resource::<EffectReturn<(Entity, Entity, Entity)>>()
+                    .0;
+                assert_eq!(70, world.fetch::<PlayerStatus>().money, "Money");
+
+                let contracts = world.read_storage::<Contract>();
+                assert_eq!(
+                    Some(port),
+                    contracts.get(contract).unwrap().origin,
+                    "Origin"
+                );
+
+                assert_eq!(
+                    vec![GameEvent::ContractAccepted {
+                        port,
+                        destination,
+                        kind: ContractKind::Delivery,
+                        carrier: None,
+                        goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        deposit: 30,
+                        expires: None,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn contract_not_accepted_without_money_for_deposit() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(AcceptContractSystemDesc, "accept_contract", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().named("Portsmouth").build();
+                let destination = world.create_entity().build();
+
+                let contract = world
+                    .create_entity()
+                    .with(Contract {
+                        deposit: 30,
+                        ..Contract::new(
+                            300,
+                            destination,
+                            [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                        )
+                    })
+                    .with(OwnedBy { entity: port })
+                    .build();
+
+                let ui_entity = world
+                    .create_entity()
+                    .with(OwnedBy { entity: contract })
+                    .build();
+
+                world.insert(PlayerStatus { money: 10 });
+                register_game_event_reader(world);
+                world.insert(EffectReturn((contract, port)));
+
+                let mut channel = world.fetch_mut::<EventChannel<UiEvent>>();
+                channel.single_write(UiEvent {
+                    event_type: UiEventType::ClickStop,
+                    target: ui_entity,
+                });
+            })
+            .with_assertion(|world| {
+                let (contract, port) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+                assert_eq!(10, world.fetch::<PlayerStatus>().money, "Money");
+
+                let owned_bys = world.read_storage::<OwnedBy>();
+                assert_eq!(
+                    port,
+                    owned_bys.get(contract).unwrap().entity,
+                    "Still owned by port"
+                );
+
+                let contracts = world.read_storage::<Contract>();
+                assert!(
+                    contracts.get(contract).unwrap().reserved.is_empty(),
+                    "No goods reserved"
+                );
+
+                assert_eq!(
+                    vec![GameEvent::DepositUnaffordable { deposit: 30 }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn player_expired_contract_charges_penalty_and_reclaims_goods() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(ExpireContractSystem, "expire_contract", &[])
+            .with_effect(|world| {
+                let origin = world
+                    .create_entity()
+                    .named("Portsmouth")
+                    .with(Cargo::default())
+                    .build();
+                let destination = world.create_entity().build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Cargo {
+                        items: [(ItemType::Sugar, 15)].iter().cloned().collect(),
+                    })
+                    .build();
+                // Sugar aboard another ship isn't the contract's to take
+                let other_ship = world
+                    .create_entity()
+                    .with(Cargo {
+                        items: [(ItemType::Sugar, 15)].iter().cloned().collect(),
+                    })
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Contract {
+                        penalty: 50,
+                        origin: Some(origin),
+                        carrier: Some(ship),
+                        reserved: [(ItemType::Rum, 5)].iter().cloned().collect(),
+                        ..Contract::new(
+                            0,
+                            destination,
+                            [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+                                .iter()
+                                .cloned()
+                                .collect(),
+                        )
+                    })
+                    .with(Expiration {
+                        expiration_date: Utc.ymd(1680, 1, 1),
+                        expired: true,
+                    })
+                    .build();
+
+                register_game_event_reader(world);
+                world.insert(EffectReturn((origin, ship, other_ship)));
+            })
+            .with_assertion(|world| {
+                let (origin, ship, other_ship) = world
+                    .read_resource::<EffectReturn<(Entity, Entity, Entity)>>()
+                    .0;
+                assert_eq!(-50, world.fetch::<PlayerStatus>().money, "Money");
+
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    5,
+                    cargos.get(ship).unwrap().items[&ItemType::Sugar],
+                    "Sugar on ship"
+                );
+                assert_eq!(
+                    15,
+                    cargos.get(other_ship).unwrap().items[&ItemType::Sugar],
+                    "Sugar on other ship"
+                );
+
+                assert_eq!(
+                    vec![GameEvent::ContractExpired {
+                        reclaimed: [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+                            .iter()
+                            .cloned()
+                            .collect(),
+                        reclaimed_by: Some(origin),
+                        penalty: 50,
+                    }],
+                    game_events(world)
+                );
+            })
+            .run()
+    }
+
+    fn create_port_with_market(world: &mut World, size: u32, x: f32) -> Entity {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, 0.0, 0.0);
+
+        world
+            .create_entity()
+            .with(Port::default())
+            .with(Market {
+                size,
+                demand: HashMap::new(),
+            })
+            .with(transform)
+            .build()
+    }
+
+    #[test]
+    fn contracts_generated_for_port_over_time() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(ContractGenerationSystemDesc, "contract_generation", &[])
+            .with_effect(|world| {
+                let port = create_port_with_market(world, 1, 0.0);
+                create_port_with_market(world, 1, 100.0);
+
+                world.insert(Date {
+                    time_elapsed: 3.0 * DAYS_PER_CONTRACT * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let contracts = world.read_storage::<Contract>();
+                let owned_bys = world.read_storage::<OwnedBy>();
+                let contracts_in_port = (&contracts, &owned_bys)
+                    .join()
+                    .filter(|(_, o)| o.entity == port)
+                    .count();
+                assert_eq!(3, contracts_in_port, "Number of contracts in port");
+            })
+            .run()
+    }
+
+    #[test]
+    fn contracts_generated_are_capped_per_port() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(ContractGenerationSystemDesc, "contract_generation", &[])
+            .with_effect(|world| {
+                let port = create_port_with_market(world, 100, 0.0);
+                create_port_with_market(world, 1, 100.0);
+
+                world.insert(Date {
+                    time_elapsed: DAYS_PER_CONTRACT * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let contracts = world.read_storage::<Contract>();
+                let owned_bys = world.read_storage::<OwnedBy>();
+                let contracts_in_port = (&contracts, &owned_bys)
+                    .join()
+                    .filter(|(_, o)| o.entity == port)
+                    .count();
+                assert_eq!(
+                    MAX_OPEN_CONTRACTS_PER_PORT, contracts_in_port,
+                    "Number of contracts in port"
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn generated_contracts_are_for_another_port_and_expire() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(ContractGenerationSystemDesc, "contract_generation", &[])
+            .with_effect(|world| {
+                let port = create_port_with_market(world, 1, 0.0);
+                let other_port = create_port_with_market(world, 1, 100.0);
+
+                world.insert(Date {
+                    time_elapsed: DAYS_PER_CONTRACT * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+
+                world.insert(EffectReturn((port, other_port)));
+            })
+            .with_assertion(|world| {
+                let (port, other_port) = world
+                    .read_resource::<EffectReturn<(Entity, Entity)>>()
+                    .0
+                    .clone();
+                let contracts = world.read_storage::<Contract>();
+                let owned_bys = world.read_storage::<OwnedBy>();
+                let expirations = world.read_storage::<Expiration>();
+
+                let (contract, _, expiration) = (&contracts, &owned_bys, &expirations)
+                    .join()
+                    .find(|(_, o, _)| o.entity == port)
+                    .expect("Contract should be generated for port");
+                assert_eq!(other_port, contract.destination, "Contract destination");
+                assert!(!expiration.expired, "Contract not expired");
+            })
+            .run()
+    }
+
+    #[test]
+    fn contracts_not_generated_for_port_without_market() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(ContractGenerationSystemDesc, "contract_generation", &[])
+            .with_effect(|world| {
+                world
+                    .create_entity()
+                    .with(Port::default())
+                    .with(Transform::default())
+                    .build();
+                create_port_with_market(world, 0, 100.0);
+
+                world.insert(Date {
+                    time_elapsed: 10.0 * DAYS_PER_CONTRACT * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+            })
+            .with_assertion(|world| {
+                let contracts = world.read_storage::<Contract>();
+                assert_eq!(0, contracts.join().count(), "Number of contracts");
+            })
+            .run()
+    }
+
+    #[test]
+    fn sailing_fleet_brings_extra_contracts() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(ContractGenerationSystemDesc, "contract_generation", &[])
+            .with_effect(|world| {
+                let port = create_port_with_market(world, 1, 0.0);
+                create_port_with_market(world, 1, 100.0);
+
+                world.insert(Date {
+                    time_elapsed: 0.1 * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::FleetSailing { port });
+
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0.clone();
+                let contracts = world.read_storage::<Contract>();
+                let owned_bys = world.read_storage::<OwnedBy>();
+                let contracts_in_port = (&contracts, &owned_bys)
+                    .join()
+                    .filter(|(_, o)| o.entity == port)
+                    .count();
+                assert_eq!(2, contracts_in_port, "Number of contracts in port");
+            })
+            .run()
+    }
+
+    #[test]
+    fn contract_payment_increases_with_distance_and_risk() {
+        let goods_required: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+            .iter()
+            .cloned()
+            .collect();
+
+        let near = contract_payment(&goods_required, None, 50.0, 0);
+        let far = contract_payment(&goods_required, None, 200.0, 0);
+        let far_and_risky = contract_payment(&goods_required, None, 200.0, 2);
+
+        assert!(far > near, "Further destination pays more");
+        assert!(far_and_risky > far, "Riskier route pays more");
+    }
+
+    #[test]
+    fn contract_payment_increases_with_demand() {
+        let goods_required: HashMap<ItemType, u32> =
+            [(ItemType::Sugar, 10)].iter().cloned().collect();
+
+        let market = Market {
+            size: 1,
+            demand: [(ItemType::Sugar, 2.0)].iter().cloned().collect(),
+        };
+
+        let without_demand = contract_payment(&goods_required, None, 100.0, 0);
+        let with_demand = contract_payment(&goods_required, Some(&market), 100.0, 0);
+
+        assert!(with_demand > without_demand, "Goods in demand pay more");
+    }
 }
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    core::Named,
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, World, Write, WriteStorage},
+    prelude::SystemDesc,
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::{
+    age_of_sail::{Date, PlayerStatus, SECONDS_IN_DAY},
+    components::{Controllable, Crew, Docked, LOW_MORALE},
+    event::{CalendarEvent, GameEvent, UiUpdateEvent},
+    finance::{Ledger, TransactionKind},
+};
+
+// Paid to each sailor every payday
+pub const WAGE: u32 = 3;
+// Changes in morale on payday
+const PAID_MORALE: f32 = 10.0;
+const UNPAID_MORALE: f32 = 25.0;
+// Changes in morale for each day at sea or in port
+const VOYAGE_MORALE_PER_DAY: f32 = 0.5;
+const LONG_VOYAGE_MORALE_PER_DAY: f32 = 2.0;
+const STARVING_MORALE_PER_DAY: f32 = 15.0;
+const PORT_MORALE_PER_DAY: f32 = 5.0;
+// Voyages longer than this wear the crew down faster
+const LONG_VOYAGE_DAYS: f32 = 30.0;
+
+// Pays, feeds and keeps the mood of the crews of the player's ships
+pub struct CrewSystem {
+    reader_id: ReaderId<CalendarEvent>,
+    last_time: Option<f64>,
+}
+
+impl CrewSystem {
+    fn new(reader_id: ReaderId<CalendarEvent>) -> Self {
+        CrewSystem {
+            reader_id,
+            last_time: None,
+        }
+    }
+}
+
+impl<'s> System<'s> for CrewSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Named>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Docked>,
+        WriteStorage<'s, Crew>,
+        Read<'s, Date>,
+        Read<'s, EventChannel<CalendarEvent>>,
+        Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            names,
+            controllables,
+            dockeds,
+            mut crews,
+            date,
+            calendar_channel,
+            mut player_status,
+            mut ledger,
+            mut game_events,
+            mut update_channel,
+        ): Self::SystemData,
+    ) {
+        // Morale can be knocked down on payday as well as at sea
+        let morale_before = (&entities, &crews)
+            .join()
+            .map(|(e, crew)| (e, crew.morale))
+            .collect::<HashMap<_, _>>();
+
+        let paydays = calendar_channel
+            .read(&mut self.reader_id)
+            .filter(|event| **event == CalendarEvent::Payday)
+            .count();
+
+        for _ in 0..paydays {
+            let wages = (&crews, &controllables)
+                .join()
+                .map(|(crew, _)| crew.sailors * WAGE)
+                .sum::<u32>();
+            if wages == 0 {
+                continue;
+            }
+
+            // Either every crew is paid or none are
+            let paid = player_status.money >= wages as i32;
+            if paid {
+                ledger.record(
+                    &mut player_status,
+                    date.current_date(),
+                    TransactionKind::Wages,
+                    -(wages as i32),
+                );
+                update_channel.single_write(UiUpdateEvent::PlayerStatus);
+                game_events.single_write(GameEvent::WagesPaid { amount: wages });
+            } else {
+                game_events.single_write(GameEvent::WagesUnpaid { amount: wages });
+            }
+
+            for (crew, _) in (&mut crews, &controllables).join() {
+                crew.morale += if paid { PAID_MORALE } else { -UNPAID_MORALE };
+            }
+        }
+
+        let elapsed = date.time_elapsed - self.last_time.unwrap_or(date.time_elapsed);
+        self.last_time = Some(date.time_elapsed);
+        let days = (elapsed / SECONDS_IN_DAY).max(0.0) as f32;
+
+        for (e, crew, _) in (&entities, &mut crews, &controllables).join() {
+            if dockeds.get(e).is_some() {
+                crew.days_at_sea = 0.0;
+                crew.morale += PORT_MORALE_PER_DAY * days;
+            } else if crew.sailors > 0 {
+                let had_food = crew.provisions > 0.0;
+                crew.days_at_sea += days;
+                crew.provisions = (crew.provisions - crew.sailors as f32 * days).max(0.0);
+                if had_food && crew.provisions == 0.0 {
+                    game_events.single_write(GameEvent::ProvisionsExhausted { ship: e });
+                }
+
+                let per_day = if crew.provisions == 0.0 {
+                    STARVING_MORALE_PER_DAY
+                } else if crew.days_at_sea > LONG_VOYAGE_DAYS {
+                    LONG_VOYAGE_MORALE_PER_DAY
+                } else {
+                    VOYAGE_MORALE_PER_DAY
+                };
+                crew.morale -= per_day * days;
+            }
+            crew.morale = crew.morale.max(0.0).min(100.0);
+
+            if crew.sailors > 0 && crew.morale == 0.0 {
+                let name = names
+                    .get(e)
+                    .map_or("???".to_string(), |n| n.name.to_string());
+                entities.delete(e).unwrap();
+                game_events.single_write(GameEvent::Mutiny { name });
+            } else if morale_before[&e] >= LOW_MORALE && crew.morale < LOW_MORALE {
+                game_events.single_write(GameEvent::MoraleLow { ship: e });
+            }
+        }
+    }
+}
+
+pub struct CrewSystemDesc;
+
+impl Default for CrewSystemDesc {
+    fn default() -> Self {
+        CrewSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, CrewSystem> for CrewSystemDesc {
+    fn build(self, world: &mut World) -> CrewSystem {
+        <CrewSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<CalendarEvent>>()
+            .register_reader();
+
+        CrewSystem::new(reader_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::{core::WithNamed, ecs::Entity, prelude::*, Result};
+    use amethyst_test::prelude::*;
+
+    fn crew(sailors: u32, morale: f32, provisions: f32) -> Crew {
+        Crew {
+            sailors,
+            berths: 20,
+            morale,
+            provisions,
+            ..Crew::default()
+        }
+    }
+
+    #[test]
+    fn wages_are_paid_on_payday() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CrewSystemDesc, "crew", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 100 });
+                let port = world.create_entity().build();
+                let ship = world
+                    .create_entity()
+                    .with(crew(10, 50.0, 0.0))
+                    .with(Controllable)
+                    .with(Docked { port })
+                    .build();
+
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::Payday);
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(
+                    100 - 10 * WAGE as i32,
+                    world.read_resource::<PlayerStatus>().money
+                );
+                assert_eq!(60.0, world.read_storage::<Crew>().get(ship).unwrap().morale);
+            })
+            .run()
+    }
+
+    #[test]
+    fn unpaid_crews_lose_heart() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CrewSystemDesc, "crew", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 10 });
+                let port = world.create_entity().build();
+                let ship = world
+                    .create_entity()
+                    .with(crew(10, 50.0, 0.0))
+                    .with(Controllable)
+                    .with(Docked { port })
+                    .build();
+
+                world
+                    .fetch_mut::<EventChannel<CalendarEvent>>()
+                    .single_write(CalendarEvent::Payday);
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(10, world.read_resource::<PlayerStatus>().money);
+                assert_eq!(25.0, world.read_storage::<Crew>().get(ship).unwrap().morale);
+            })
+            .run()
+    }
+
+    #[test]
+    fn crews_at_sea_eat_their_provisions() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CrewSystemDesc, "crew", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(crew(10, 50.0, 100.0))
+                    .with(Controllable)
+                    .build();
+                world.insert(EffectReturn(ship));
+            })
+            .with_effect(|world| {
+                world.write_resource::<Date>().time_elapsed = 2.0 * SECONDS_IN_DAY;
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let crews = world.read_storage::<Crew>();
+                let crew = crews.get(ship).unwrap();
+                assert_eq!(80.0, crew.provisions, "Provisions");
+                assert_eq!(2.0, crew.days_at_sea, "Days at sea");
+                assert_eq!(49.0, crew.morale, "Morale");
+            })
+            .run()
+    }
+
+    #[test]
+    fn starving_crews_mutiny() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(CrewSystemDesc, "crew", &[])
+            .with_effect(|world| {
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                let ship = world
+                    .create_entity()
+                    .with(crew(10, 20.0, 0.0))
+                    .with(Controllable)
+                    .named("Bounty")
+                    .build();
+                world.insert(EffectReturn(ship));
+            })
+            .with_effect(|world| {
+                world.write_resource::<Date>().time_elapsed = 2.0 * SECONDS_IN_DAY;
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+                assert!(!world.entities().is_alive(ship), "Ship taken by crew");
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![GameEvent::Mutiny {
+                        name: "Bounty".to_string()
+                    }],
+                    events
+                );
+            })
+            .run()
+    }
+}
@@ -0,0 +1,280 @@
+use amethyst::{
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, World, Write},
+    prelude::SystemDesc,
+    shrev::{EventChannel, ReaderId},
+};
+use chrono::Datelike;
+
+use crate::{
+    age_of_sail::{Date, PlayerStatus},
+    components::{Bank, Controllable, Shipyard},
+    event::{CalendarEvent, GameEvent, GameOverReason, UiUpdateEvent},
+    finance::Ledger,
+};
+
+// Months in a row the player can begin in debt before going bankrupt
+const MONTHS_TO_BANKRUPTCY: u32 = 3;
+
+// Charges interest on loans, draws up last month's accounts and ends the game once the player is
+// ruined
+pub struct FinanceSystem {
+    reader_id: ReaderId<CalendarEvent>,
+}
+
+impl FinanceSystem {
+    fn new(reader_id: ReaderId<CalendarEvent>) -> Self {
+        FinanceSystem { reader_id }
+    }
+}
+
+impl<'s> System<'s> for FinanceSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Shipyard>,
+        ReadStorage<'s, Bank>,
+        Read<'s, EventChannel<CalendarEvent>>,
+        Write<'s, Date>,
+        Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            controllables,
+            shipyards,
+            banks,
+            calendar_channel,
+            mut date,
+            mut player_status,
+            mut ledger,
+            mut game_events,
+            mut update_channel,
+        ): Self::SystemData,
+    ) {
+        let months = calendar_channel
+            .read(&mut self.reader_id)
+            .filter(|event| **event == CalendarEvent::MonthBegins)
+            .count();
+
+        let today = date.current_date();
+        let last_month = today.with_day(1).unwrap().pred();
+
+        for _ in 0..months {
+            if date.game_over {
+                return;
+            }
+
+            let interest = ledger.charge_interest(&mut player_status, today);
+            if interest > 0 {
+                update_channel.single_write(UiUpdateEvent::PlayerStatus);
+                game_events.single_write(GameEvent::InterestCharged { amount: interest });
+            }
+
+            game_events.single_write(GameEvent::MonthlyReport {
+                report: ledger.profit_and_loss(last_month.year(), last_month.month()),
+            });
+
+            let reason = if player_status.money < 0 {
+                ledger.months_in_debt += 1;
+                if ledger.months_in_debt >= MONTHS_TO_BANKRUPTCY {
+                    Some(GameOverReason::Bankrupt)
+                } else {
+                    game_events.single_write(GameEvent::InDebt {
+                        months_left: MONTHS_TO_BANKRUPTCY - ledger.months_in_debt,
+                    });
+                    None
+                }
+            } else {
+                ledger.months_in_debt = 0;
+                if (&entities, &controllables).join().next().is_none() {
+                    // Whatever the banks would still lend counts towards another ship
+                    let credit = (&entities, &banks)
+                        .join()
+                        .map(|(port, bank)| bank.limit.saturating_sub(ledger.owed_to(port)))
+                        .sum::<u32>();
+                    let cheapest = (&shipyards)
+                        .join()
+                        .flat_map(|shipyard| shipyard.ships_for_sale.iter())
+                        .map(|ship| ship.price)
+                        .min();
+
+                    match cheapest {
+                        Some(price) if price <= player_status.money as u32 + credit => None,
+                        _ => Some(GameOverReason::Shipless),
+                    }
+                } else {
+                    None
+                }
+            };
+
+            if let Some(reason) = reason {
+                date.game_over = true;
+                game_events.single_write(GameEvent::GameOver { reason });
+            }
+        }
+    }
+}
+
+pub struct FinanceSystemDesc;
+
+impl Default for FinanceSystemDesc {
+    fn default() -> Self {
+        FinanceSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, FinanceSystem> for FinanceSystemDesc {
+    fn build(self, world: &mut World) -> FinanceSystem {
+        <FinanceSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<CalendarEvent>>()
+            .register_reader();
+
+        FinanceSystem::new(reader_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        age_of_sail::SECONDS_IN_DAY,
+        finance::{ProfitAndLoss, TransactionKind},
+    };
+    use amethyst::{prelude::*, Result};
+    use amethyst_test::prelude::*;
+    use chrono::{TimeZone, Utc};
+
+    fn begin_months(world: &mut World, months: usize) {
+        let reader_id = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        world.insert(reader_id);
+
+        for _ in 0..months {
+            world
+                .fetch_mut::<EventChannel<CalendarEvent>>()
+                .single_write(CalendarEvent::MonthBegins);
+        }
+    }
+
+    #[test]
+    fn interest_is_charged_and_last_month_reported() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(FinanceSystemDesc, "finance", &[])
+            .with_effect(|world| {
+                world.create_entity().with(Controllable).build();
+                let bank = world.create_entity().build();
+
+                let mut player_status = PlayerStatus { money: 100 };
+                let mut ledger = Ledger::default();
+                let january = Utc.ymd(1680, 1, 10);
+                ledger.borrow(&mut player_status, january, bank, 1000, 0.05);
+                ledger.record(
+                    &mut player_status,
+                    january,
+                    TransactionKind::ContractIncome,
+                    300,
+                );
+                ledger.record(&mut player_status, january, TransactionKind::Wages, -60);
+                world.insert(player_status);
+                world.insert(ledger);
+                world.insert(Date {
+                    time_elapsed: 31.0 * SECONDS_IN_DAY,
+                    ..Date::default()
+                });
+
+                begin_months(world, 1);
+            })
+            .with_assertion(|world| {
+                assert_eq!(1290, world.read_resource::<PlayerStatus>().money);
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![
+                        GameEvent::InterestCharged { amount: 50 },
+                        GameEvent::MonthlyReport {
+                            report: ProfitAndLoss {
+                                year: 1680,
+                                month: 1,
+                                lines: vec![
+                                    (TransactionKind::ContractIncome, 300),
+                                    (TransactionKind::Wages, -60),
+                                ],
+                            }
+                        },
+                    ],
+                    events
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn staying_in_debt_ends_in_bankruptcy() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(FinanceSystemDesc, "finance", &[])
+            .with_effect(|world| {
+                world.create_entity().with(Controllable).build();
+                world.insert(PlayerStatus { money: -10 });
+
+                begin_months(world, 4);
+            })
+            .with_assertion(|world| {
+                assert!(world.read_resource::<Date>().game_over, "Game over");
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                let warnings = events
+                    .iter()
+                    .filter_map(|event| match event {
+                        GameEvent::InDebt { months_left } => Some(*months_left),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(vec![2, 1], warnings, "Warnings");
+                assert_eq!(
+                    Some(&GameEvent::GameOver {
+                        reason: GameOverReason::Bankrupt
+                    }),
+                    events.last()
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn game_is_lost_without_ships_or_the_means_to_buy_one() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(FinanceSystemDesc, "finance", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 100 });
+                world.create_entity().with(Shipyard::default()).build();
+
+                begin_months(world, 1);
+            })
+            .with_assertion(|world| {
+                assert!(world.read_resource::<Date>().game_over, "Game over");
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    Some(&GameEvent::GameOver {
+                        reason: GameOverReason::Shipless
+                    }),
+                    events.last()
+                );
+            })
+            .run()
+    }
+}
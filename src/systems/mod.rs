@@ -1,22 +1,42 @@
 pub use self::ai::AiSystem;
+pub use self::calendar::{CalendarSystem, WorldEventSystemDesc};
 pub use self::camera::PanningSystem;
+pub use self::cargo::CargoTransferSystemDesc;
 pub use self::collision::{CollisionSystem, DestroySystemDesc};
-pub use self::contract::{AcceptContractSystemDesc, ExpireContractSystem, FulfillContractSystem};
+pub use self::contract::{
+    AcceptContractSystemDesc, ContractGenerationSystemDesc, ExpireContractSystem,
+    FulfillContractSystem,
+};
+pub use self::crew::CrewSystemDesc;
+pub use self::finance::FinanceSystemDesc;
 pub use self::move_ships::{
     ChaseSystem, DockingSystem, MoveShipsSystem, PatrolSystem, PlotCourseSystem,
 };
+pub use self::notifications::EventNotificationSystemDesc;
+pub use self::port::PortServicesSystemDesc;
 pub use self::select::{SelectPortSystem, SelectShipSystem, SelectSystem};
 pub use self::time::{ExpirationSystem, UpdateTimeSystem};
 pub use self::ui::{
-    ContractPanelSystemDesc, GameSpeedSystemDesc, NotificationSystem, PlayerStatusSystemDesc,
-    PortPanelSystemDesc, ShipPanelSystemDesc,
+    ContractPanelSystemDesc, GameSpeedSystemDesc, KeyBindingsSystemDesc, MinimapSystemDesc,
+    NotificationLogSystemDesc, NotificationSystem, PlayerStatusSystemDesc, PortPanelSystemDesc,
+    ShipPanelSystemDesc,
 };
+pub use self::waypoints::{CourseDisplaySystem, EditWaypointsSystem};
+pub use self::weather::{StormDamageSystem, WeatherDisplaySystem, WeatherSystem};
 
 mod ai;
+mod calendar;
 mod camera;
+mod cargo;
 mod collision;
 mod contract;
+mod crew;
+mod finance;
 mod move_ships;
+mod notifications;
+mod port;
 mod select;
 mod time;
 mod ui;
+mod waypoints;
+mod weather;
@@ -1,94 +1,326 @@
 use std::cmp::Ordering;
-use itertools::Itertools;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::PI;
 
 use amethyst::{
     core::{
         alga::linear::EuclideanSpace,
-        math::{Point2, Vector2},
-        Time, Transform, Named
+        math::{Point2, Rotation2, Vector2},
+        Time, Transform,
     },
     derive::SystemDesc,
-    ecs::{Entities, Join, Read, ReadExpect, Write, ReadStorage, System, SystemData, WriteStorage},
-    input::{InputHandler, StringBindings, VirtualKeyCode},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, SystemData, Write, WriteStorage},
+    input::{InputHandler, StringBindings},
     window::ScreenDimensions,
-    winit::MouseButton,
     renderer::Camera,
+    shrev::EventChannel,
 };
 
 use crate::{
-    age_of_sail::{Notifications, point_mouse_to_world, DISTANCE_THRESHOLD},
-    components::{Action, Ai, Cargo, Controllable, Course, Patrol, Port, Selected, Ship},
+    age_of_sail::{point_mouse_to_world, CameraView, FormationMode, DISTANCE_THRESHOLD},
+    components::{
+        Action, Ai, Approaching, Controllable, Course, Crew, Docked, Formation, Helm, Patrol, Pirate,
+        Port, Selected, Ship,
+    },
+    event::{DockEvent, GameEvent, UndockEvent},
     map::Map,
+    weather::Weather,
 };
 
 pub const SNAP_THRESHOLD: f32 = 5.0;
+// Ships making for a port stop here to wait if every berth is taken
+pub const APPROACH_RADIUS: f32 = 20.0;
+// Ships look this far ahead for land to steer around, with a minimum distance for slow ships
+pub const LAND_LOOKAHEAD_SECONDS: f32 = 1.0;
+pub const MIN_LAND_LOOKAHEAD: f32 = 2.0;
+// Headings either side of the course tried when steering around land, up to a right angle
+const AVOIDANCE_ANGLE: f32 = PI / 12.0;
+const AVOIDANCE_STEPS: usize = 6;
+// Ships steer apart when closer than this
+pub const SEPARATION_RADIUS: f32 = 10.0;
+// Ships closer than this to either side of the course are in the way
+pub const SHIP_CLEARANCE: f32 = 6.0;
 
 #[derive(SystemDesc)]
 pub struct MoveShipsSystem;
 
 impl<'s> System<'s> for MoveShipsSystem {
     type SystemData = (
+        Entities<'s>,
         ReadStorage<'s, Ship>,
+        WriteStorage<'s, Formation>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Crew>,
         WriteStorage<'s, Course>,
         WriteStorage<'s, Transform>,
+        WriteStorage<'s, Helm>,
         Read<'s, Map>,
+        Read<'s, Weather>,
         Read<'s, Time>,
     );
 
-    fn run(&mut self, (ships, mut courses, mut locals, map, time): Self::SystemData) {
-        for (ship, course, local) in (&ships, &mut courses, &mut locals).join() {
-            let ship_x = local.translation().x;
-            let ship_y = local.translation().y;
-
-            let ship_location = Point2::new(ship_x, ship_y);
-
-            if let Some(next_waypoint) = course.waypoints.front() {
-                let graph = map.nodes_and_edges_connected(vec![ship_location, *next_waypoint]); 
-                        
-                let mut points = VecDeque::from(graph.a_star(graph.nodes.len()-2, graph.nodes.len()-1));
-                       
-                if points.len() > 2 {
-                    // Remove first and last point
-                    points.pop_front();
-                    points.pop_back();
-                    while let Some(point) = points.pop_back() {
-                       course.waypoints.push_front(point); 
-                    }
-                }
+    fn run(&mut self, (entities, ships, mut formations, controllables, crews, mut courses, mut locals, mut helms, map, weather, time): Self::SystemData) {
+        let ship_locations = (&entities, &ships, &locals)
+            .join()
+            .map(|(e, _, l)| (e, Point2::new(l.translation().x, l.translation().y)))
+            .collect::<Vec<_>>();
+
+        for (e, ship, course, local, helm) in (&entities, &ships, &mut courses, &mut locals, (&mut helms).maybe()).join() {
+            let ship_location = Point2::new(local.translation().x, local.translation().y);
+            let nearby_ships = ship_locations
+                .iter()
+                .filter(|(other_e, other_location)| *other_e != e && ship_location.distance(other_location) < SEPARATION_RADIUS)
+                .map(|(_, other_location)| *other_location)
+                .collect::<Vec<_>>();
+
+            // Ships in formation keep to the pace of the slowest
+            let speed = formations.get(e).map_or(ship.base_speed, |f| ship.base_speed.min(f.speed));
+            let speed = speed * weather.speed_multiplier(ship_location);
+            // Short-handed or unhappy crews sail the ship slower
+            let speed = speed * crews.get(e).map_or(1.0, Crew::speed_multiplier);
+
+            // The player plots their own way through bad weather
+            let storms = if controllables.get(e).is_some() { None } else { Some(&*weather) };
+
+            plot_route(course, ship_location, &map, storms);
+            sail(speed, helm, course, local, &map, &nearby_ships, &time);
+
+            // The formation is kept only for the order it was given with. Ships only dock once
+            // their course is empty, so this also breaks it up on docking
+            if course.waypoints.is_empty() {
+                formations.remove(e);
             }
+        }
+    }
+}
 
+// Adds waypoints in front of the next one to go round land, and storms if given the weather
+fn plot_route(course: &mut Course, ship_location: Point2<f32>, map: &Map, storms: Option<&Weather>) {
+    if let Some(next_waypoint) = course.waypoints.front() {
+        // Ships keeping out of storms can also go by points around any storm in the way
+        let mut points = storms.map_or(vec![], |weather| weather.detour_points(ship_location, *next_waypoint));
+        points.retain(|p| !map.on_land(*p));
+        points.push(ship_location);
+        points.push(*next_waypoint);
+        let graph = map.nodes_and_edges_connected(points);
+
+        let (start, end) = (graph.nodes.len()-2, graph.nodes.len()-1);
+        let mut points = VecDeque::from(match storms {
+            Some(weather) => graph.a_star_with_penalty(start, end, |from, to| weather.route_penalty(from, to)),
+            None => graph.a_star(start, end),
+        });
+               
+        if points.len() > 2 {
+            // Remove first and last point
+            points.pop_front();
+            points.pop_back();
+
+            // Skip corners the ship is already at or that are the next waypoint
+            let next_waypoint = *next_waypoint;
+            points.retain(|p| p.distance(&ship_location) >= DISTANCE_THRESHOLD && p.distance(&next_waypoint) >= DISTANCE_THRESHOLD);
+
+            while let Some(point) = points.pop_back() {
+               course.waypoints.push_front(point); 
+            }
+        }
+    }
+}
 
-            if let Some(next_waypoint) = course.waypoints.front() {
-                if ship_location.distance(next_waypoint)
-                    < DISTANCE_THRESHOLD
-                {
-                    course.waypoints.pop_front();
-                } else {
-                    let direction =
-                        Vector2::new(next_waypoint.x - ship_x, next_waypoint.y - ship_y).normalize();
-
-                    let distance = ship_location.distance(&next_waypoint);
-                    
-                    let closeness_modifier = if distance < DISTANCE_THRESHOLD * time.time_scale() {
-                        distance / (time.time_scale() + f32::EPSILON)
-                    } else {
-                        1.0
-                    };
+fn sail(
+    speed: f32,
+    helm: Option<&mut Helm>,
+    course: &mut Course,
+    local: &mut Transform,
+    map: &Map,
+    nearby_ships: &[Point2<f32>],
+    time: &Time,
+) {
+    let ship_x = local.translation().x;
+    let ship_y = local.translation().y;
+
+    let ship_location = Point2::new(ship_x, ship_y);
+
+    if let Some(helm) = helm {
+        steer(speed, helm, course, local, map, nearby_ships, time.delta_seconds());
+        return;
+    }
 
-                    local.prepend_translation_x(
-                        closeness_modifier * ship.base_speed * direction.x * time.delta_seconds(),
-                    );
-                    local.prepend_translation_y(
-                        closeness_modifier * ship.base_speed * direction.y * time.delta_seconds(),
-                    ); 
-             
-                }
+    if let Some(next_waypoint) = course.waypoints.front() {
+        if ship_location.distance(next_waypoint)
+            < DISTANCE_THRESHOLD
+        {
+            course.waypoints.pop_front();
+        } else {
+            let direction =
+                Vector2::new(next_waypoint.x - ship_x, next_waypoint.y - ship_y).normalize();
+
+            let distance = ship_location.distance(&next_waypoint);
+            
+            let closeness_modifier = if distance < DISTANCE_THRESHOLD * time.time_scale() {
+                distance / (time.time_scale() + f32::EPSILON)
+            } else {
+                1.0
+            };
+
+            let direction = avoid_ships(ship_location, direction, nearby_ships, distance);
+            let direction = avoid_land(map, ship_location, direction, land_lookahead(speed, distance));
+            let step = direction * (closeness_modifier * speed * time.delta_seconds()).min(distance);
+
+            if safe_to_sail(map, ship_location, ship_location + step) {
+                local.prepend_translation_x(step.x);
+                local.prepend_translation_y(step.y);
             }
         }
     }
 }
 
+// Turns the ship towards its next waypoint no faster than the helm allows, slowing for sharp
+// turns and to stop at the end of the course. Waypoints are passed once the ship is close enough
+// to start turning for the next one so it arcs around corners
+fn steer(
+    speed: f32,
+    helm: &mut Helm,
+    course: &mut Course,
+    local: &mut Transform,
+    map: &Map,
+    nearby_ships: &[Point2<f32>],
+    delta_seconds: f32,
+) {
+    let ship_location = Point2::new(local.translation().x, local.translation().y);
+
+    if let (Some(&next_waypoint), Some(&waypoint_after)) = (course.waypoints.get(0), course.waypoints.get(1)) {
+        let outgoing = waypoint_after - next_waypoint;
+        let turn = angle_difference(helm.heading, outgoing.y.atan2(outgoing.x)).abs();
+        let lead_distance = (helm.turning_radius() * (turn * 0.5).tan()).min(outgoing.magnitude() * 0.5);
+
+        // Only cut the corner if it doesn't mean cutting across land
+        if ship_location.distance(&next_waypoint) < lead_distance.max(DISTANCE_THRESHOLD)
+            && map.closest_point_of_line_on_edge(ship_location, waypoint_after - ship_location, true).is_none()
+        {
+            course.waypoints.pop_front();
+        }
+    }
+
+    let next_waypoint = match course.waypoints.front() {
+        Some(next_waypoint) => *next_waypoint,
+        None => {
+            helm.speed = 0.0;
+            return;
+        }
+    };
+
+    let distance = ship_location.distance(&next_waypoint);
+    if distance < DISTANCE_THRESHOLD {
+        course.waypoints.pop_front();
+        if course.waypoints.is_empty() {
+            helm.speed = 0.0;
+        }
+        return;
+    }
+
+    let direction = avoid_ships(ship_location, (next_waypoint - ship_location).normalize(), nearby_ships, distance);
+    let direction = avoid_land(map, ship_location, direction, land_lookahead(helm.speed, distance));
+    let bearing = direction.y.atan2(direction.x);
+    let max_turn = helm.max_turn_rate * delta_seconds;
+    let heading_error = angle_difference(helm.heading, bearing);
+    helm.heading = wrap_angle(helm.heading + heading_error.max(-max_turn).min(max_turn));
+    local.set_rotation_2d(helm.heading);
+
+    let final_waypoint = course.waypoints.len() == 1;
+    let target_speed = if final_waypoint && distance <= helm.stopping_distance() {
+        0.0
+    } else {
+        // Slow down to come about instead of sailing in circles around the waypoint
+        speed * angle_difference(helm.heading, bearing).cos().max(0.0)
+    };
+
+    helm.speed = if helm.speed < target_speed {
+        (helm.speed + helm.acceleration * delta_seconds).min(target_speed)
+    } else {
+        (helm.speed - helm.deceleration * delta_seconds).max(target_speed)
+    };
+
+    let step = helm.speed * delta_seconds;
+    let new_location = if step >= distance {
+        next_waypoint
+    } else {
+        ship_location + Vector2::new(helm.heading.cos(), helm.heading.sin()) * step
+    };
+
+    if safe_to_sail(map, ship_location, new_location) {
+        local.set_translation_x(new_location.x);
+        local.set_translation_y(new_location.y);
+    } else {
+        // Run out of room to turn, stop and come about
+        helm.speed = 0.0;
+    }
+}
+
+// Bends the direction away from nearby ships, harder the closer they are, and gives way to starboard
+// when one is dead ahead. Eases off near the waypoint so ships can still reach a crowded port
+fn avoid_ships(
+    location: Point2<f32>,
+    direction: Vector2<f32>,
+    nearby_ships: &[Point2<f32>],
+    distance_to_waypoint: f32,
+) -> Vector2<f32> {
+    let weight = (distance_to_waypoint / SEPARATION_RADIUS).min(1.0);
+    let starboard = Vector2::new(direction.y, -direction.x);
+    let mut steering = direction;
+
+    for &other_location in nearby_ships {
+        let away = location - other_location;
+        let distance = away.magnitude();
+        if distance == 0.0 || distance >= SEPARATION_RADIUS {
+            continue;
+        }
+
+        let closeness = weight * (SEPARATION_RADIUS - distance) / SEPARATION_RADIUS;
+        steering += away / distance * closeness;
+
+        let ahead = -away.dot(&direction);
+        let abeam = away.dot(&starboard).abs();
+        if ahead > 0.0 && abeam < SHIP_CLEARANCE {
+            steering += starboard * closeness;
+        }
+    }
+
+    if steering.magnitude() > f32::EPSILON {
+        steering.normalize()
+    } else {
+        direction
+    }
+}
+
+// How far ahead to look for land, never past the next waypoint as that may be a port on the coast
+fn land_lookahead(speed: f32, distance_to_waypoint: f32) -> f32 {
+    (speed * LAND_LOOKAHEAD_SECONDS).max(MIN_LAND_LOOKAHEAD).min(distance_to_waypoint)
+}
+
+// Direction closest to the one given that has no land ahead within the lookahead distance
+fn avoid_land(map: &Map, location: Point2<f32>, direction: Vector2<f32>, lookahead: f32) -> Vector2<f32> {
+    (0..=AVOIDANCE_STEPS)
+        .flat_map(|i| vec![i as f32 * AVOIDANCE_ANGLE, -(i as f32) * AVOIDANCE_ANGLE])
+        .map(|angle| Rotation2::new(angle) * direction)
+        .find(|&candidate| map.closest_point_of_line_on_edge(location, candidate * lookahead, true).is_none())
+        .unwrap_or(direction)
+}
+
+// Ships can't finish a move on land or pass through it. One already aground is let off
+fn safe_to_sail(map: &Map, from: Point2<f32>, to: Point2<f32>) -> bool {
+    map.on_land(from)
+        || (!map.on_land(to) && map.closest_point_of_line_on_edge(from, to - from, true).is_none())
+}
+
+// Signed angle to turn through to get from one heading to another, between -PI and PI
+fn angle_difference(from: f32, to: f32) -> f32 {
+    wrap_angle(to - from)
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
 #[derive(SystemDesc)]
 pub struct PatrolSystem;
 
@@ -194,7 +426,10 @@ impl<'s> System<'s> for ChaseSystem {
     }
 }
 
-pub struct PlotCourseSystem;
+#[derive(Default)]
+pub struct PlotCourseSystem {
+    formation_key_down: bool,
+}
 
 impl<'s> System<'s> for PlotCourseSystem {
     type SystemData = (
@@ -205,61 +440,124 @@ impl<'s> System<'s> for PlotCourseSystem {
         ReadStorage<'s, Selected>,
         ReadStorage<'s, Controllable>,
         WriteStorage<'s, Course>,
+        WriteStorage<'s, Formation>,
         Read<'s, InputHandler<StringBindings>>,
         Read<'s, Map>,
         ReadExpect<'s, ScreenDimensions>,
+        Read<'s, CameraView>,
+        Write<'s, FormationMode>,
+        Write<'s, EventChannel<GameEvent>>,
     );
 
     fn run(
         &mut self,
-        (entities, cameras, ships, locals, selecteds, controllables, mut courses, input, map, screen_dimensions): Self::SystemData,
+        (
+            entities,
+            cameras,
+            ships,
+            locals,
+            selecteds,
+            controllables,
+            mut courses,
+            mut formations,
+            input,
+            map,
+            screen_dimensions,
+            camera_view,
+            mut formation_mode,
+            mut game_events,
+        ): Self::SystemData,
     ) {
+        let formation_key_down = input.action_is_down("formation").unwrap_or(false);
+        if formation_key_down && !self.formation_key_down {
+            formation_mode.enabled = !formation_mode.enabled;
+            game_events.single_write(GameEvent::FormationChanged { enabled: formation_mode.enabled });
+        }
+        self.formation_key_down = formation_key_down;
+
+        let queue_waypoint = input.action_is_down("append").unwrap_or(false);
+
         for (_, camera_local) in (&cameras, &locals).join() {
-            for (e, _, _, _, _) in (&entities, &locals, &ships, &selecteds, &controllables).join() {
-                if let Some((mouse_x, mouse_y)) = input.mouse_position() {
-                    if input.mouse_button_is_down(MouseButton::Right) {
-                        let point_in_world =
-                            point_mouse_to_world(mouse_x, mouse_y, &*screen_dimensions, camera_local.translation());
-
-                        // Snap to any entity if close enough
-                        let point = &locals
-                            .join()
-                            .map(|l| Point2::new(l.translation().x, l.translation().y))
-                            .filter(|p| point_in_world.distance(p) < SNAP_THRESHOLD)
-                            .next()
-                            .unwrap_or(point_in_world);
+            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                if input.action_is_down("order").unwrap_or(false) {
+                    let point_in_world = point_mouse_to_world(
+                        mouse_x,
+                        mouse_y,
+                        &*screen_dimensions,
+                        camera_local.translation(),
+                        camera_view.zoom,
+                    );
+
+                    // Snap to any entity if close enough
+                    let point = locals
+                        .join()
+                        .map(|l| Point2::new(l.translation().x, l.translation().y))
+                        .filter(|p| point_in_world.distance(p) < SNAP_THRESHOLD)
+                        .next()
+                        .unwrap_or(point_in_world);
+
+                    // Queued waypoints are placed relative to where each ship will be by then
+                    let group = (&entities, &locals, &ships, &selecteds, &controllables)
+                        .join()
+                        .map(|(e, l, ship, _, _)| {
+                            let location = courses
+                                .get(e)
+                                .and_then(|c| c.waypoints.back().cloned())
+                                .filter(|_| queue_waypoint)
+                                .unwrap_or_else(|| Point2::new(l.translation().x, l.translation().y));
+                            (e, location, ship.base_speed)
+                        })
+                        .collect::<Vec<_>>();
+
+                    let in_formation = formation_mode.enabled && group.len() > 1;
+                    let destinations = if in_formation {
+                        formation_destinations(
+                            point,
+                            &group.iter().map(|(e, location, _)| (*e, *location)).collect::<Vec<_>>(),
+                        )
+                    } else {
+                        group.iter().map(|(e, _, _)| (*e, point)).collect()
+                    };
+                    let formation_speed = group.iter().map(|(_, _, speed)| *speed).fold(f32::MAX, f32::min);
 
+                    for (e, destination) in destinations {
                         // Snap to edge of land if on land
-                        let point = if map.on_land(*point) {
-                            map.closest_point_on_edge(*point)
+                        let destination = if map.on_land(destination) {
+                            map.closest_point_on_edge(destination)
                         } else {
-                            *point
+                            destination
                         };
 
-                        if !input.key_is_down(VirtualKeyCode::LShift) {
+                        if !queue_waypoint {
                             courses
                                 .insert(
                                     e,
                                     Course {
-                                        waypoints: VecDeque::from(vec![point]),
+                                        waypoints: VecDeque::from(vec![destination]),
                                     },
                                 )
                                 .unwrap();
                         } else {
                             match courses.get_mut(e) {
-                                Some(c) => c.waypoints.push_back(point),
+                                Some(c) => c.waypoints.push_back(destination),
                                 None => {
                                     courses
                                         .insert(
                                             e,
                                             Course {
-                                                waypoints: VecDeque::from(vec![point]),
+                                                waypoints: VecDeque::from(vec![destination]),
                                             },
                                         )
                                         .unwrap();
                                 }
                             }
                         }
+
+                        if in_formation {
+                            formations.insert(e, Formation { speed: formation_speed }).unwrap();
+                        } else {
+                            formations.remove(e);
+                        }
                     }
                 }
             }
@@ -267,57 +565,138 @@ impl<'s> System<'s> for PlotCourseSystem {
     }
 }
 
+// Moves the group's centre to the destination, keeping each ship where it is relative to it
+fn formation_destinations(destination: Point2<f32>, ships: &[(Entity, Point2<f32>)]) -> Vec<(Entity, Point2<f32>)> {
+    let centre = ships
+        .iter()
+        .fold(Vector2::zeros(), |sum, (_, location)| sum + location.coords)
+        / ships.len() as f32;
+
+    ships
+        .iter()
+        .map(|(e, location)| (*e, destination + (location.coords - centre)))
+        .collect()
+}
+
 pub struct DockingSystem;
 
 impl<'s> System<'s> for DockingSystem {
     type SystemData = (
         Entities<'s>,
         ReadStorage<'s, Ship>,
+        ReadStorage<'s, Pirate>,
         ReadStorage<'s, Port>,
-        ReadStorage<'s, Named>,
-        WriteStorage<'s, Cargo>,
-        WriteStorage<'s, Transform>,
-        Write<'s, Notifications>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Controllable>,
+        WriteStorage<'s, Course>,
+        WriteStorage<'s, Docked>,
+        WriteStorage<'s, Approaching>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, EventChannel<DockEvent>>,
+        Write<'s, EventChannel<UndockEvent>>,
     );
 
-    fn run(&mut self, (entities, ships, ports, names, mut cargos, locals, mut notifications): Self::SystemData) {
-        for (p, _, port_local) in (&entities, &ports, &locals).join() {
-            let port_location = Point2::new(port_local.translation().x, port_local.translation().y);
+    fn run(
+        &mut self,
+        (
+            entities,
+            ships,
+            pirates,
+            ports,
+            locals,
+            controllables,
+            mut courses,
+            mut dockeds,
+            mut approachings,
+            mut game_events,
+            mut dock_channel,
+            mut undock_channel,
+        ): Self::SystemData,
+    ) {
+        let location_of = |e: Entity| locals.get(e).map(|l| Point2::new(l.translation().x, l.translation().y));
 
-            // If a ship is nearby prepare to load ship
-            let suitable_ship = (&entities, &ships, &locals)
-                .join()
-                .filter(|(_, _, l)| {
-                    let ship_location = Point2::new(l.translation().x, l.translation().y);
-                    ship_location.distance(&port_location) < DISTANCE_THRESHOLD
-                })
-                .map(|(e, _, _)| e)
-                .next();
-
-            if let Some(ship) = suitable_ship {
-                let port_cargo = cargos.get(p).unwrap().items.clone();
-                let ship_cargo = cargos.get_mut(ship).unwrap();
-                for (item, amount) in &port_cargo {
-                    *ship_cargo.items.entry(*item).or_insert(0) += amount;
+        let mut occupied_berths = HashMap::new();
+        for docked in (&dockeds).join() {
+            *occupied_berths.entry(docked.port).or_insert(0) += 1;
+        }
+
+        // Pirates never put into port
+        for (e, _, _, local) in (&entities, &ships, !&pirates, &locals).join() {
+            let ship_location = Point2::new(local.translation().x, local.translation().y);
+            let under_way = courses.get(e).map_or(false, |c| !c.waypoints.is_empty());
+
+            if let Some(port) = dockeds.get(e).map(|d| d.port) {
+                let at_port = location_of(port).map_or(false, |p| ship_location.distance(&p) < DISTANCE_THRESHOLD);
+
+                if under_way || !at_port {
+                    dockeds.remove(e);
+                    *occupied_berths.entry(port).or_insert(1) -= 1;
+                    undock_channel.single_write(UndockEvent { ship: e, port });
                 }
+                continue;
+            }
 
-                cargos.get_mut(p).unwrap().items.clear();
-                if !port_cargo.is_empty() {
-                    let items_notification = port_cargo
-                        .iter()
-                        .sorted_by_key(|(&item, _)| item)
-                        .map(|(item, amount)| format!("{} tons of {}", amount, item))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    
+            // The port at the end of the ship's course, or the one it is holding outside of
+            let heading_for = courses
+                .get(e)
+                .and_then(|c| c.waypoints.back().copied())
+                .unwrap_or(ship_location);
+            let port = approachings
+                .get(e)
+                .map(|a| a.port)
+                .filter(|_| !under_way)
+                .or_else(|| {
+                    (&entities, &ports, &locals)
+                        .join()
+                        .filter(|(_, _, l)| {
+                            let port_location = Point2::new(l.translation().x, l.translation().y);
+                            heading_for.distance(&port_location) < DISTANCE_THRESHOLD
+                        })
+                        .map(|(p, _, _)| p)
+                        .next()
+                });
 
-                    notifications.push_back(format!(
-                        "{} loaded onto ship at {}.",
-                        items_notification,
-                        names.get(p).unwrap().name.to_string()
-                    ));
+            let (port, port_location) = match port.and_then(|p| location_of(p).map(|l| (p, l))) {
+                Some((port, port_location)) if ship_location.distance(&port_location) < APPROACH_RADIUS => {
+                    (port, port_location)
                 }
+                _ => {
+                    approachings.remove(e);
+                    continue;
+                }
+            };
+
+            let berth_free = *occupied_berths.get(&port).unwrap_or(&0) < ports.get(port).unwrap().berths;
+            let at_port = ship_location.distance(&port_location) < DISTANCE_THRESHOLD;
 
+            if berth_free && at_port && !under_way {
+                approachings.remove(e);
+                dockeds.insert(e, Docked { port }).unwrap();
+                *occupied_berths.entry(port).or_insert(0) += 1;
+                dock_channel.single_write(DockEvent { ship: e, port });
+                continue;
+            }
+
+            if berth_free && !under_way {
+                // A berth has come free for a ship holding outside
+                courses
+                    .insert(
+                        e,
+                        Course {
+                            waypoints: VecDeque::from(vec![port_location]),
+                        },
+                    )
+                    .unwrap();
+            } else if !berth_free && under_way {
+                courses.get_mut(e).unwrap().waypoints.clear();
+
+                if controllables.get(e).is_some() {
+                    game_events.single_write(GameEvent::WaitingForBerth { ship: e, port });
+                }
+            }
+
+            if approachings.get(e).map_or(true, |a| a.port != port) {
+                approachings.insert(e, Approaching { port }).unwrap();
             }
         }
     }
@@ -326,10 +705,15 @@ impl<'s> System<'s> for DockingSystem {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::{AiState, Cargo, ItemType};
-    use amethyst::{ecs::Entity, prelude::*, Result};    
+    use crate::{
+        age_of_sail::distance_to_segment,
+        components::AiState,
+        weather::{WeatherCell, WeatherKind},
+    };
+    use amethyst::{ecs::Entity, prelude::*, shrev::ReaderId, Result};    
     use amethyst_test::prelude::*;
     use std::collections::HashMap;
+    use test_case::test_case;
 
     #[test]
     fn moves_ships_adds_new_waypoints_if_choosing_the_current_one_passes_through_land() -> Result<()> {
@@ -532,6 +916,145 @@ mod tests {
             .run()
     }
 
+    #[test]
+    fn moves_ships_slower_in_calm_patch() -> Result<()> {
+        let current_waypoint = Point2::new(0.0, 50.0);
+        let waypoints = VecDeque::from(vec![current_waypoint]);
+
+        AmethystApplication::blank()
+            .with_setup(|world| {
+                world.insert(Weather {
+                    cells: vec![WeatherCell {
+                        kind: WeatherKind::Calm,
+                        centre: Point2::new(100.0, 0.0),
+                        radius: 20.0,
+                        velocity: Vector2::new(0.0, 0.0),
+                        expires: f64::MAX,
+                    }],
+                })
+            })
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(move |world| {
+                let mut becalmed_local = Transform::default();
+                becalmed_local.set_translation_xyz(100.0, 0.0, 0.0);
+                let becalmed_ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 50.0)]) })
+                    .with(becalmed_local)
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Course { waypoints: waypoints.clone() })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn((becalmed_ship, ship)));
+            })
+            .with_assertion(move |world| {
+                let locals = world.read_storage::<Transform>();
+                let (becalmed_ship, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let becalmed_travelled = locals.get(becalmed_ship).unwrap().translation().y;
+                let travelled = locals.get(ship).unwrap().translation().y;
+
+                assert!(becalmed_travelled < travelled, "Becalmed ship travelled less");
+            })
+            .run()
+    }
+
+    #[test]
+    fn short_handed_ships_sail_slower() -> Result<()> {
+        let waypoints = VecDeque::from(vec![Point2::new(0.0, 50.0)]);
+
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(move |world| {
+                let short_handed_ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Crew { sailors: 2, berths: 20, ..Crew::default() })
+                    .with(Course { waypoints: waypoints.clone() })
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Crew { sailors: 12, berths: 20, ..Crew::default() })
+                    .with(Course { waypoints: waypoints.clone() })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn((short_handed_ship, ship)));
+            })
+            .with_assertion(move |world| {
+                let locals = world.read_storage::<Transform>();
+                let (short_handed_ship, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let short_handed_travelled = locals.get(short_handed_ship).unwrap().translation().y;
+                let travelled = locals.get(ship).unwrap().translation().y;
+
+                assert!(short_handed_travelled < travelled, "Short-handed ship travelled less");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ai_ships_route_around_storms_and_player_ships_do_not() -> Result<()> {
+        let destination = Point2::new(100.0, 0.0);
+
+        AmethystApplication::blank()
+            .with_setup(|world| {
+                world.insert(Weather {
+                    cells: vec![WeatherCell {
+                        kind: WeatherKind::Storm,
+                        centre: Point2::new(50.0, 0.0),
+                        radius: 20.0,
+                        velocity: Vector2::new(0.0, 0.0),
+                        expires: f64::MAX,
+                    }],
+                })
+            })
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(move |world| {
+                let ai_ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![destination]) })
+                    .with(Transform::default())
+                    .build();
+
+                let player_ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Controllable)
+                    .with(Course { waypoints: VecDeque::from(vec![destination]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn((ai_ship, player_ship)));
+            })
+            .with_assertion(move |world| {
+                let courses = world.read_storage::<Course>();
+                let (ai_ship, player_ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let ai_course = courses.get(ai_ship).unwrap();
+                assert!(ai_course.waypoints.len() > 1, "AI ship has a detour");
+                let mut previous = Point2::new(0.0, 0.0);
+                for waypoint in &ai_course.waypoints {
+                    assert!(distance_to_segment(Point2::new(50.0, 0.0), previous, *waypoint) > 19.9, "Course clear of storm");
+                    previous = *waypoint;
+                }
+
+                let player_course = courses.get(player_ship).unwrap();
+                assert_eq!(vec![destination], player_course.waypoints.iter().cloned().collect::<Vec<_>>(), "Player course");
+            })
+            .run()
+    }
+
     #[test] 
     fn moves_ships_does_not_move_if_no_next_waypoint() -> Result<()> {
         let waypoints = VecDeque::from(vec![]);
@@ -590,6 +1113,362 @@ mod tests {
             .run()
     }
 
+    #[test]
+    fn ship_with_helm_turns_gradually_towards_waypoint() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Helm { max_turn_rate: 0.01, ..Helm::default() })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(0.0, 10.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let helms = world.read_storage::<Helm>();
+                let helm = helms.get(ship_entity).unwrap();
+                assert!(helm.heading >= 0.0 && helm.heading < 0.1, "Heading turned towards waypoint");
+
+                let locals = world.read_storage::<Transform>();
+                let rotation = locals.get(ship_entity).unwrap().rotation().euler_angles().2;
+                assert!((rotation - helm.heading).abs() < 0.0001, "Sprite rotated to heading");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ship_with_helm_accelerates_gradually() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 10.0 })
+                    .with(Helm { acceleration: 0.01, ..Helm::default() })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let helms = world.read_storage::<Helm>();
+                let speed = helms.get(ship_entity).unwrap().speed;
+                assert!(speed > 0.0 && speed < 1.0, "Speed");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ship_with_helm_slows_down_approaching_end_of_course() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 10.0 })
+                    .with(Helm { speed: 10.0, deceleration: 1.0, ..Helm::default() })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(5.0, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let helms = world.read_storage::<Helm>();
+                assert!(helms.get(ship_entity).unwrap().speed < 10.0, "Speed");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ship_with_helm_starts_turning_before_reaching_corner() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                // Turning radius of 10 so the turn starts 10 before the corner
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 10.0 })
+                    .with(Helm { speed: 10.0, max_turn_rate: 1.0, ..Helm::default() })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(3.0, 0.0), Point2::new(3.0, 20.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let courses = world.read_storage::<Course>();
+                let course = courses.get(ship_entity).unwrap();
+                assert_eq!(Point2::new(3.0, 20.0), *course.waypoints.front().unwrap(), "Next waypoint");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ship_with_helm_keeps_corner_waypoint_if_far_away() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 10.0 })
+                    .with(Helm { speed: 10.0, max_turn_rate: 1.0, ..Helm::default() })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(30.0, 0.0), Point2::new(30.0, 20.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let courses = world.read_storage::<Course>();
+                let course = courses.get(ship_entity).unwrap();
+                assert_eq!(Point2::new(30.0, 0.0), *course.waypoints.front().unwrap(), "Next waypoint");
+            })
+            .run()
+    }
+
+    #[test]
+    fn angle_difference_takes_shortest_way_round() {
+        assert!((angle_difference(0.1, -0.1) + 0.2).abs() < 0.0001);
+        assert!((angle_difference(PI - 0.1, -PI + 0.1) - 0.2).abs() < 0.0001);
+        assert!((angle_difference(-PI + 0.1, PI - 0.1) + 0.2).abs() < 0.0001);
+    }
+
+    fn map_from_file() -> Map {
+        let map_data_reader = std::fs::File::open(&"assets/map.ron").expect("Failed opening map file");
+        let map_data: Vec<Vec<Point2<i32>>> = ron::de::from_reader(map_data_reader).unwrap();
+        Map::new(map_data)
+    }
+
+    // Sails a tick at a time until the course is finished, failing if the ship ever ends a tick on land
+    fn sail_course(map: &Map, start: Point2<f32>, destination: Point2<f32>, mut helm: Option<Helm>, delta_seconds: f32) -> Point2<f32> {
+        let mut course = Course { waypoints: VecDeque::from(vec![destination]) };
+        let mut local = Transform::default();
+        local.set_translation_xyz(start.x, start.y, 0.0);
+
+        let mut time = Time::default();
+        time.set_delta_seconds(delta_seconds);
+
+        for _ in 0..(600.0 / delta_seconds) as usize {
+            let location = Point2::new(local.translation().x, local.translation().y);
+            plot_route(&mut course, location, map, None);
+            sail(10.0, helm.as_mut(), &mut course, &mut local, map, &[], &time);
+
+            let location = Point2::new(local.translation().x, local.translation().y);
+            assert!(!map.on_land(location), "Ship on land at {:?}", location);
+
+            if course.waypoints.is_empty() {
+                return location;
+            }
+        }
+
+        panic!("Ship did not finish its course");
+    }
+
+    #[test_case(Point2::new(150.0, 40.0), Point2::new(140.0, 275.0) ; "portsmouth to liverpool")]
+    #[test_case(Point2::new(140.0, 275.0), Point2::new(276.0, 110.0) ; "liverpool to london")]
+    #[test_case(Point2::new(340.0, 110.0), Point2::new(276.0, 110.0) ; "up the thames estuary")]
+    #[test_case(Point2::new(100.0, 220.0), Point2::new(60.0, 450.0) ; "north through the channel between islands")]
+    #[test_case(Point2::new(20.0, 200.0), Point2::new(80.0, 420.0) ; "across the channel between islands")]
+    fn ship_sails_through_channels_without_touching_land(start: Point2<f32>, destination: Point2<f32>) {
+        let map = map_from_file();
+
+        for delta_seconds in [0.05, 0.5].iter() {
+            let end = sail_course(&map, start, destination, None, *delta_seconds);
+            assert!(end.distance(&destination) < DISTANCE_THRESHOLD, "Ship at destination");
+        }
+    }
+
+    #[test_case(Point2::new(150.0, 40.0), Point2::new(140.0, 275.0) ; "portsmouth to liverpool")]
+    #[test_case(Point2::new(140.0, 275.0), Point2::new(276.0, 110.0) ; "liverpool to london")]
+    #[test_case(Point2::new(340.0, 110.0), Point2::new(276.0, 110.0) ; "up the thames estuary")]
+    #[test_case(Point2::new(100.0, 220.0), Point2::new(60.0, 450.0) ; "north through the channel between islands")]
+    #[test_case(Point2::new(20.0, 200.0), Point2::new(80.0, 420.0) ; "across the channel between islands")]
+    fn ship_with_helm_sails_through_channels_without_touching_land(start: Point2<f32>, destination: Point2<f32>) {
+        let map = map_from_file();
+
+        for delta_seconds in [0.05, 0.5].iter() {
+            // Starts facing the wrong way so has to come about first
+            let helm = Helm { heading: PI, ..Helm::default() };
+            let end = sail_course(&map, start, destination, Some(helm), *delta_seconds);
+            assert!(end.distance(&destination) < DISTANCE_THRESHOLD, "Ship at destination");
+        }
+    }
+
+    #[test]
+    fn ships_cannot_sail_onto_or_through_land() {
+        let map = Map::new(vec![vec![Point2::new(50, -50), Point2::new(50, 50), Point2::new(100, 0)]]);
+
+        assert!(safe_to_sail(&map, Point2::new(45.0, 0.0), Point2::new(45.0, 10.0)), "Sailing along the coast");
+        assert!(!safe_to_sail(&map, Point2::new(45.0, 0.0), Point2::new(55.0, 0.0)), "Sailing onto land");
+        assert!(!safe_to_sail(&map, Point2::new(45.0, 0.0), Point2::new(110.0, 0.0)), "Sailing through land");
+        assert!(safe_to_sail(&map, Point2::new(60.0, 0.0), Point2::new(40.0, 0.0)), "Sailing off land");
+    }
+
+    #[test]
+    fn moves_ships_steers_clear_of_ship_ahead() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                let mut other_local = Transform::default();
+                other_local.set_translation_xyz(5.0, 0.0, 0.0);
+
+                world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(other_local)
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let locals = world.read_storage::<Transform>();
+                let ship_local = locals.get(ship_entity).unwrap();
+                assert!(ship_local.translation().y < 0.0, "Ship turned to starboard");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ships_in_formation_keep_to_pace_of_slowest() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 2.0 })
+                    .with(Formation { speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                let mut slower_local = Transform::default();
+                slower_local.set_translation_xyz(0.0, 50.0, 0.0);
+
+                let slower_ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Formation { speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 50.0)]) })
+                    .with(slower_local)
+                    .build();
+
+                world.insert(EffectReturn((ship, slower_ship)));
+            })
+            .with_assertion(|world| {
+                let (ship_entity, slower_ship_entity) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.clone();
+
+                let locals = world.read_storage::<Transform>();
+                let distance = locals.get(ship_entity).unwrap().translation().x;
+                let slower_distance = locals.get(slower_ship_entity).unwrap().translation().x;
+                assert!(distance > 0.0, "Ship moved");
+                assert!((distance - slower_distance).abs() < 0.0001, "Ships moved the same distance");
+            })
+            .run()
+    }
+
+    #[test]
+    fn formation_broken_up_when_course_ends() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(MoveShipsSystem, "move_ships", &[])
+            .with_effect(|world| {
+                let arrived = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Formation { speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(0.1, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                let under_way = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Formation { speed: 1.0 })
+                    .with(Course { waypoints: VecDeque::from(vec![Point2::new(100.0, 0.0)]) })
+                    .with(Transform::default())
+                    .build();
+
+                world.insert(EffectReturn((arrived, under_way)));
+            })
+            .with_assertion(|world| {
+                let (arrived, under_way) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let formations = world.read_storage::<Formation>();
+                assert!(formations.get(arrived).is_none(), "Arrived ship left formation");
+                assert!(formations.get(under_way).is_some(), "Ship under way kept formation");
+            })
+            .run()
+    }
+
+    #[test]
+    fn avoid_ships_keeps_direction_with_no_ships_nearby() {
+        let direction = Vector2::new(1.0, 0.0);
+
+        assert_eq!(direction, avoid_ships(Point2::origin(), direction, &[], 50.0));
+    }
+
+    #[test]
+    fn avoid_ships_turns_away_from_ship_alongside() {
+        let direction = avoid_ships(Point2::origin(), Vector2::new(1.0, 0.0), &[Point2::new(0.0, 3.0)], 50.0);
+
+        assert!(direction.y < 0.0, "Turned away");
+    }
+
+    #[test]
+    fn avoid_ships_eases_off_at_waypoint() {
+        let direction = Vector2::new(1.0, 0.0);
+
+        assert_eq!(direction, avoid_ships(Point2::origin(), direction, &[Point2::new(2.0, 0.0)], 0.0));
+    }
+
+    #[test]
+    fn formation_destinations_keep_ships_relative_positions() {
+        let mut world = World::new();
+        let ships = (0..3).map(|_| world.create_entity().build()).collect::<Vec<_>>();
+
+        let destinations = formation_destinations(
+            Point2::new(100.0, 100.0),
+            &[
+                (ships[0], Point2::new(0.0, 0.0)),
+                (ships[1], Point2::new(10.0, 0.0)),
+                (ships[2], Point2::new(5.0, 15.0)),
+            ],
+        );
+
+        assert_eq!(
+            vec![
+                (ships[0], Point2::new(95.0, 95.0)),
+                (ships[1], Point2::new(105.0, 95.0)),
+                (ships[2], Point2::new(100.0, 110.0)),
+            ],
+            destinations
+        );
+    }
+
     #[test]
     fn ai_chooses_nearest_waypoint_if_previously_was_not_patrolling() -> Result<()> {
         let target_location = Point2::new(1.0, 5.0);
@@ -969,36 +1848,13 @@ mod tests {
     }   
 
     #[test]
-    fn cargo_transferred_if_ship_nearby() -> Result<()> {
-        let goods_in_port: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
-            .iter()
-            .cloned()
-            .collect();
-
-        let original_goods_on_ship: HashMap<ItemType, u32> = [
-            (ItemType::Sugar, 10),
-            (ItemType::Whiskey, 5),
-            (ItemType::Rum, 10),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let mut expected_goods_on_ship = original_goods_on_ship.clone();
-        for (k, v) in goods_in_port.iter() {
-            *expected_goods_on_ship.entry(*k).or_insert(0) += v;
-        }
-
+    fn ship_docks_when_stopped_at_port() -> Result<()> {
         AmethystApplication::blank()
             .with_system(DockingSystem, "docking", &[])
             .with_effect(move |world| {
                 let port = world
                     .create_entity()
-                    .with(Port)
-                    .named("A port")
-                    .with(Cargo {
-                        items: goods_in_port.clone(),
-                    })
+                    .with(Port::default())
                     .with(Transform::default())
                     .build();
 
@@ -1008,109 +1864,145 @@ mod tests {
                 let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
-                    .with(Cargo {
-                        items: original_goods_on_ship.clone(),
-                    })
                     .with(ship_transform)
                     .build();
 
+                let reader_id = world.fetch_mut::<EventChannel<DockEvent>>().register_reader();
+                world.insert(reader_id);
+
                 world.insert(EffectReturn((ship, port)));
             })
             .with_assertion(move |world| {
                 let ship_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.0.clone();
                 let port_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.1.clone();
-                
-                let cargos = world.read_storage::<Cargo>();
-                
-                let port_cargo = cargos.get(port_entity).unwrap();
-                assert_eq!(HashMap::new(), port_cargo.items, "Cargo on port");
 
-                let ship_cargo = cargos.get(ship_entity).unwrap();
-                assert_eq!(expected_goods_on_ship, ship_cargo.items, "Cargon on ship");
+                let dockeds = world.read_storage::<Docked>();
+                assert_eq!(Some(port_entity), dockeds.get(ship_entity).map(|d| d.port), "Docked at port");
+
+                let channel = world.fetch_mut::<EventChannel<DockEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<DockEvent>>();
+                let event = channel.read(&mut reader_id).next().unwrap();
+                assert_eq!(ship_entity, event.ship, "Docked ship");
+                assert_eq!(port_entity, event.port, "Docked port");
             })
             .run()
     }
 
     #[test]
-    fn notification_sent_if_cargo_loaded() -> Result<()> {
-        const PORT: &str = "London";
-        let goods_in_port: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
-            .iter()
-            .cloned()
-            .collect();
-
+    fn ship_does_not_dock_if_not_nearby() -> Result<()> {
         AmethystApplication::blank()
             .with_system(DockingSystem, "docking", &[])
             .with_effect(move |world| {
                 world
                     .create_entity()
-                    .with(Port)
-                    .named(PORT)
-                    .with(Cargo {
-                        items: goods_in_port.clone(),
-                    })
+                    .with(Port::default())
                     .with(Transform::default())
                     .build();
 
                 let mut ship_transform = Transform::default();
-                ship_transform.set_translation_xyz(0.0001, 0.0002, 0.0);
+                ship_transform.set_translation_xyz(10.0, 0.0, 0.0);
 
-                world
+                let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
-                    .with(Cargo {
-                        items: HashMap::new(),
-                    })
                     .with(ship_transform)
                     .build();
 
+                world.insert(EffectReturn(ship));
             })
             .with_assertion(move |world| {
-                let notifications = world.read_resource::<Notifications>().clone();
-                assert_eq!(1, notifications.len(), "Number of notifications");
-                assert_eq!(
-                    &format!(
-                        "5 tons of Rum, 10 tons of Sugar loaded onto ship at {}.",
-                        PORT
-                    ),
-                    notifications.front().unwrap(),
-                    "Notification"
-                );
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
 
+                let dockeds = world.read_storage::<Docked>();
+                assert!(dockeds.get(ship_entity).is_none(), "Ship not docked");
             })
             .run()
     }
 
     #[test]
-    fn cargo_not_transferred_if_ship_not_nearby() -> Result<()> {
-        let goods_in_port: HashMap<ItemType, u32> = [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
-            .iter()
-            .cloned()
-            .collect();
-
-        let goods_in_port_cloned = goods_in_port.clone();
-
-        let goods_on_ship: HashMap<ItemType, u32> = [
-            (ItemType::Sugar, 10),
-            (ItemType::Whiskey, 5),
-            (ItemType::Rum, 10),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+    fn pirate_does_not_dock() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(DockingSystem, "docking", &[])
+            .with_effect(move |world| {
+                world
+                    .create_entity()
+                    .with(Port::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Pirate)
+                    .with(Transform::default())
+                    .build();
 
-        let goods_on_ship_cloned = goods_on_ship.clone();
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(move |world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let dockeds = world.read_storage::<Docked>();
+                assert!(dockeds.get(ship_entity).is_none(), "Pirate not docked");
+            })
+            .run()
+    }
 
+    #[test]
+    fn ship_under_way_undocks() -> Result<()> {
         AmethystApplication::blank()
             .with_system(DockingSystem, "docking", &[])
             .with_effect(move |world| {
                 let port = world
                     .create_entity()
-                    .with(Port)
-                    .named("A port")
-                    .with(Cargo {
-                        items: goods_in_port.clone(),
-                    })
+                    .with(Port::default())
+                    .with(Transform::default())
+                    .build();
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Course {waypoints: VecDeque::from(vec![Point2::new(20.0, -5.0)])})
+                    .with(Docked { port })
+                    .with(Transform::default())
+                    .build();
+
+                let reader_id = world.fetch_mut::<EventChannel<UndockEvent>>().register_reader();
+                world.insert(reader_id);
+
+                world.insert(EffectReturn((ship, port)));
+            })
+            .with_assertion(move |world| {
+                let ship_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.0.clone();
+                let port_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.1.clone();
+
+                let dockeds = world.read_storage::<Docked>();
+                assert!(dockeds.get(ship_entity).is_none(), "Ship not docked");
+
+                let channel = world.fetch_mut::<EventChannel<UndockEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<UndockEvent>>();
+                let event = channel.read(&mut reader_id).next().unwrap();
+                assert_eq!(ship_entity, event.ship, "Undocked ship");
+                assert_eq!(port_entity, event.port, "Undocked port");
+            })
+            .run()
+    }
+
+    #[test]
+    fn ship_holds_outside_port_with_no_free_berth() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(DockingSystem, "docking", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .with(Port { berths: 1 })
+                    .with(Transform::default())
+                    .build();
+
+                world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Docked { port })
                     .with(Transform::default())
                     .build();
 
@@ -1120,9 +2012,7 @@ mod tests {
                 let ship = world
                     .create_entity()
                     .with(Ship { base_speed: 1.0 })
-                    .with(Cargo {
-                        items: goods_on_ship.clone(),
-                    })
+                    .with(Course {waypoints: VecDeque::from(vec![Point2::new(0.0, 0.0)])})
                     .with(ship_transform)
                     .build();
 
@@ -1131,16 +2021,46 @@ mod tests {
             .with_assertion(move |world| {
                 let ship_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.0.clone();
                 let port_entity = world.read_resource::<EffectReturn<(Entity, Entity)>>().0.1.clone();
-                
-                let cargos = world.read_storage::<Cargo>();
-                
-                let port_cargo = cargos.get(port_entity).unwrap();
-                assert_eq!(goods_in_port_cloned, port_cargo.items, "Cargo on port");
 
-                let ship_cargo = cargos.get(ship_entity).unwrap();
-                assert_eq!(goods_on_ship_cloned, ship_cargo.items, "Cargo on ship");
+                let courses = world.read_storage::<Course>();
+                assert!(courses.get(ship_entity).unwrap().waypoints.is_empty(), "Ship stopped");
+
+                let approachings = world.read_storage::<Approaching>();
+                assert_eq!(Some(port_entity), approachings.get(ship_entity).map(|a| a.port), "Approaching port");
             })
             .run()
     }
 
+    #[test]
+    fn holding_ship_makes_for_port_when_berth_is_free() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(DockingSystem, "docking", &[])
+            .with_effect(move |world| {
+                let port = world
+                    .create_entity()
+                    .with(Port { berths: 1 })
+                    .with(Transform::default())
+                    .build();
+
+                let mut ship_transform = Transform::default();
+                ship_transform.set_translation_xyz(10.0, 0.0, 0.0);
+
+                let ship = world
+                    .create_entity()
+                    .with(Ship { base_speed: 1.0 })
+                    .with(Approaching { port })
+                    .with(ship_transform)
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(move |world| {
+                let ship_entity = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                let courses = world.read_storage::<Course>();
+                let course = courses.get(ship_entity).unwrap();
+                assert_eq!(VecDeque::from(vec![Point2::new(0.0, 0.0)]), course.waypoints, "Course to port");
+            })
+            .run()
+    }
 }
@@ -0,0 +1,849 @@
+use amethyst::{
+    core::Named,
+    ecs::{Entity, Read, ReadStorage, System, SystemData, World, Write},
+    prelude::SystemDesc,
+    shrev::{EventChannel, ReaderId},
+};
+
+use crate::{
+    age_of_sail::{Notification, NotificationCategory, Notifications},
+    components::ContractKind,
+    event::{CalendarEvent, GameEvent, GameOverReason, PortRefusal},
+    locale::Locale,
+};
+
+// Turns game and calendar events into notifications for the player
+pub struct EventNotificationSystem {
+    reader_id: ReaderId<GameEvent>,
+    calendar_reader_id: ReaderId<CalendarEvent>,
+}
+
+impl EventNotificationSystem {
+    fn new(reader_id: ReaderId<GameEvent>, calendar_reader_id: ReaderId<CalendarEvent>) -> Self {
+        EventNotificationSystem {
+            reader_id,
+            calendar_reader_id,
+        }
+    }
+}
+
+impl<'s> System<'s> for EventNotificationSystem {
+    type SystemData = (
+        ReadStorage<'s, Named>,
+        Read<'s, EventChannel<GameEvent>>,
+        Read<'s, EventChannel<CalendarEvent>>,
+        Read<'s, Locale>,
+        Write<'s, Notifications>,
+    );
+
+    fn run(
+        &mut self,
+        (names, channel, calendar_channel, locale, mut notifications): Self::SystemData,
+    ) {
+        for event in channel.read(&mut self.reader_id) {
+            notifications.push_back(notification_for(event, &names, &locale));
+        }
+        for event in calendar_channel.read(&mut self.calendar_reader_id) {
+            notifications.extend(calendar_notification_for(event, &names, &locale));
+        }
+    }
+}
+
+pub struct EventNotificationSystemDesc;
+
+impl Default for EventNotificationSystemDesc {
+    fn default() -> Self {
+        EventNotificationSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, EventNotificationSystem> for EventNotificationSystemDesc {
+    fn build(self, world: &mut World) -> EventNotificationSystem {
+        <EventNotificationSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<GameEvent>>()
+            .register_reader();
+        let calendar_reader_id = world
+            .fetch_mut::<EventChannel<CalendarEvent>>()
+            .register_reader();
+
+        EventNotificationSystem::new(reader_id, calendar_reader_id)
+    }
+}
+
+pub fn notification_for<'a>(
+    event: &GameEvent,
+    names: &ReadStorage<'a, Named>,
+    locale: &Locale,
+) -> Notification {
+    let name_of = |e: Entity| {
+        names
+            .get(e)
+            .map_or("???".to_string(), |n| n.name.to_string())
+    };
+
+    match event {
+        GameEvent::DepositUnaffordable { deposit } => Notification::new(
+            NotificationCategory::Contracts,
+            locale.text(
+                "deposit_unaffordable",
+                &[("deposit", &locale.money(*deposit))],
+            ),
+        )
+        .warning(),
+        GameEvent::ContractAccepted {
+            port,
+            destination,
+            kind,
+            carrier,
+            goods,
+            deposit,
+            expires,
+        } => {
+            let mut message = match (kind, carrier) {
+                (ContractKind::Escort { escortee }, _) => locale.text(
+                    "contract_accepted_escort",
+                    &[
+                        ("escortee", &name_of(*escortee)),
+                        ("destination", &name_of(*destination)),
+                    ],
+                ),
+                (_, Some(carrier)) => locale.text(
+                    "contract_accepted_carrier",
+                    &[
+                        ("goods", &locale.goods_list(goods)),
+                        ("carrier", &name_of(*carrier)),
+                        ("port", &name_of(*port)),
+                    ],
+                ),
+                (_, None) => locale.text(
+                    "contract_accepted",
+                    &[
+                        ("goods", &locale.goods_list(goods)),
+                        ("port", &name_of(*port)),
+                    ],
+                ),
+            };
+            if *deposit > 0 {
+                message += &locale.text("deposit_paid", &[("deposit", &locale.money(*deposit))]);
+            }
+            if let Some(expires) = expires {
+                message += &locale.text("will_expire", &[("date", &locale.date(*expires))]);
+            }
+            Notification::new(NotificationCategory::Contracts, message).about(*port)
+        }
+        GameEvent::EscortLost { penalty } => Notification::new(
+            NotificationCategory::Contracts,
+            locale.text("escort_lost", &[]) + &penalty_charged(*penalty, locale),
+        )
+        .critical(),
+        GameEvent::GoodsDelivered {
+            port,
+            goods,
+            payment,
+            still_required,
+        } => {
+            let mut message = locale.text(
+                "goods_delivered",
+                &[
+                    ("goods", &locale.goods_list(goods)),
+                    ("port", &name_of(*port)),
+                    ("payment", &locale.money(*payment)),
+                ],
+            );
+            if !still_required.is_empty() {
+                message += &locale.text(
+                    "still_to_deliver",
+                    &[("goods", &locale.goods_list(still_required))],
+                );
+            }
+            Notification::new(NotificationCategory::Contracts, message).about(*port)
+        }
+        GameEvent::ContractFulfilled {
+            port,
+            kind,
+            goods,
+            earned,
+            deposit,
+        } => {
+            let earned = locale.money(*earned);
+            let message = match kind {
+                ContractKind::Bulk { .. } => locale.text("bulk_completed", &[("earned", &earned)]),
+                ContractKind::Escort { escortee } => locale.text(
+                    "escort_completed",
+                    &[
+                        ("earned", &earned),
+                        ("port", &name_of(*port)),
+                        ("escortee", &name_of(*escortee)),
+                    ],
+                ),
+                ContractKind::Smuggling { .. } => locale.text(
+                    "smuggling_completed",
+                    &[
+                        ("earned", &earned),
+                        ("port", &name_of(*port)),
+                        ("goods", &locale.goods_list(goods)),
+                    ],
+                ),
+                _ => locale.text(
+                    "contract_completed",
+                    &[
+                        ("earned", &earned),
+                        ("port", &name_of(*port)),
+                        ("goods", &locale.goods_list(goods)),
+                    ],
+                ),
+            };
+            Notification::new(
+                NotificationCategory::Contracts,
+                message + &deposit_returned(*deposit, locale),
+            )
+            .about(*port)
+        }
+        GameEvent::GoodsSeized { port, goods, fine } => Notification::new(
+            NotificationCategory::Contracts,
+            locale.text(
+                "goods_seized",
+                &[
+                    ("port", &name_of(*port)),
+                    ("goods", &locale.goods_list(goods)),
+                    ("fine", &locale.money(*fine)),
+                ],
+            ),
+        )
+        .about(*port)
+        .warning(),
+        GameEvent::ContractExpired {
+            reclaimed,
+            reclaimed_by,
+            penalty,
+        } => {
+            let message = if reclaimed.is_empty() && *penalty == 0 {
+                locale.text("contract_expired_quietly", &[])
+            } else {
+                let reclaimed_message = if reclaimed.is_empty() {
+                    String::new()
+                } else {
+                    locale.text(
+                        "goods_reclaimed",
+                        &[
+                            ("goods", &locale.goods_list(reclaimed)),
+                            ("port", &reclaimed_by.map_or("???".to_string(), name_of)),
+                        ],
+                    )
+                };
+                locale.text("contract_expired", &[])
+                    + &reclaimed_message
+                    + &penalty_charged(*penalty, locale)
+            };
+            Notification::new(NotificationCategory::Contracts, message).warning()
+        }
+        GameEvent::TransferInterrupted {
+            ship,
+            port,
+            unloading,
+        } => Notification::new(
+            NotificationCategory::Cargo,
+            locale.text(
+                if *unloading {
+                    "left_before_unloading"
+                } else {
+                    "left_before_loading"
+                },
+                &[("ship", &name_of(*ship)), ("port", &name_of(*port))],
+            ),
+        )
+        .about(*ship)
+        .warning(),
+        GameEvent::CargoLoaded { ship, port, goods } => Notification::new(
+            NotificationCategory::Cargo,
+            locale.text(
+                "cargo_loaded",
+                &[
+                    ("goods", &locale.goods_list(goods)),
+                    ("ship", &name_of(*ship)),
+                    ("port", &name_of(*port)),
+                ],
+            ),
+        )
+        .about(*ship),
+        GameEvent::ShipDestroyed { .. } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("ship_destroyed", &[]),
+        )
+        .critical(),
+        GameEvent::CaughtInStorm { ship } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("caught_in_storm", &[("ship", &name_of(*ship))]),
+        )
+        .about(*ship)
+        .warning(),
+        GameEvent::ShipFoundered { name } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("ship_foundered", &[("ship", name)]),
+        )
+        .critical(),
+        GameEvent::WagesPaid { amount } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("wages_paid", &[("amount", &locale.money(*amount))]),
+        ),
+        GameEvent::WagesUnpaid { amount } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("wages_unpaid", &[("amount", &locale.money(*amount))]),
+        )
+        .critical(),
+        GameEvent::ProvisionsExhausted { ship } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("provisions_exhausted", &[("ship", &name_of(*ship))]),
+        )
+        .about(*ship)
+        .critical(),
+        GameEvent::MoraleLow { ship } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("morale_low", &[("ship", &name_of(*ship))]),
+        )
+        .about(*ship)
+        .warning(),
+        GameEvent::Mutiny { name } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("mutiny", &[("ship", name)]),
+        )
+        .critical(),
+        GameEvent::PiratesFoughtOff { ship } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("pirates_fought_off", &[("ship", &name_of(*ship))]),
+        )
+        .about(*ship),
+        GameEvent::MonthlyReport { report } => Notification::new(
+            NotificationCategory::Trade,
+            locale.text(
+                "monthly_report",
+                &[
+                    ("month", &locale.month(report.year, report.month)),
+                    ("income", &locale.money(report.income())),
+                    ("expenses", &locale.money(report.expenses())),
+                    ("net", &locale.money(report.net())),
+                ],
+            ),
+        ),
+        GameEvent::InterestCharged { amount } => Notification::new(
+            NotificationCategory::Trade,
+            locale.text("interest_charged", &[("amount", &locale.money(*amount))]),
+        ),
+        GameEvent::InDebt { months_left } => Notification::new(
+            NotificationCategory::General,
+            locale.plural("in_debt", *months_left, &[]),
+        )
+        .critical(),
+        GameEvent::GameOver { reason } => Notification::new(
+            NotificationCategory::General,
+            locale.text(
+                match reason {
+                    GameOverReason::Bankrupt => "game_over_bankrupt",
+                    GameOverReason::Shipless => "game_over_shipless",
+                },
+                &[],
+            ),
+        )
+        .critical(),
+        GameEvent::WaitingForBerth { ship, port } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text(
+                "waiting_for_berth",
+                &[("ship", &name_of(*ship)), ("port", &name_of(*port))],
+            ),
+        )
+        .about(*ship),
+        GameEvent::FormationChanged { enabled } => Notification::new(
+            NotificationCategory::General,
+            locale.text(
+                if *enabled {
+                    "sailing_in_formation"
+                } else {
+                    "sailing_independently"
+                },
+                &[],
+            ),
+        ),
+        GameEvent::ShipPurchased { name, port } => Notification::new(
+            NotificationCategory::Trade,
+            locale.text(
+                "ship_purchased",
+                &[("ship", name), ("port", &name_of(*port))],
+            ),
+        )
+        .about(*port),
+        GameEvent::PortActionRefused { port, reason } => Notification::new(
+            NotificationCategory::Trade,
+            describe_refusal(reason, locale),
+        )
+        .about(*port)
+        .warning(),
+        GameEvent::KeyRebound { action, binding } => Notification::new(
+            NotificationCategory::General,
+            locale.text("key_bound", &[("action", action), ("button", binding)]),
+        ),
+        GameEvent::KeyNotRebound { action, error } => Notification::new(
+            NotificationCategory::General,
+            locale.text("key_not_bound", &[("action", action), ("error", error)]),
+        )
+        .warning(),
+        GameEvent::KeyBindingsNotSaved { error } => Notification::new(
+            NotificationCategory::General,
+            locale.text("key_bindings_not_saved", &[("error", error)]),
+        )
+        .warning(),
+    }
+}
+
+pub fn calendar_notification_for<'a>(
+    event: &CalendarEvent,
+    names: &ReadStorage<'a, Named>,
+    locale: &Locale,
+) -> Option<Notification> {
+    let name_of = |e: Entity| {
+        names
+            .get(e)
+            .map_or("???".to_string(), |n| n.name.to_string())
+    };
+
+    let notification = match event {
+        CalendarEvent::SeasonBegins(season) => Notification::new(
+            NotificationCategory::General,
+            locale.text("season_begins", &[("season", &locale.season(*season))]),
+        ),
+        CalendarEvent::TradeFair { port, open } => Notification::new(
+            NotificationCategory::Trade,
+            locale.text(
+                if *open {
+                    "trade_fair_opens"
+                } else {
+                    "trade_fair_closes"
+                },
+                &[("port", &name_of(*port))],
+            ),
+        )
+        .about(*port),
+        CalendarEvent::Harvest { port, item, begins } => Notification::new(
+            NotificationCategory::Trade,
+            locale.text(
+                if *begins {
+                    "harvest_begins"
+                } else {
+                    "harvest_ends"
+                },
+                &[("port", &name_of(*port)), ("item", &locale.item(*item))],
+            ),
+        )
+        .about(*port),
+        CalendarEvent::StormSeason { begins: true } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("storm_season_begins", &[]),
+        )
+        .warning(),
+        CalendarEvent::StormSeason { begins: false } => Notification::new(
+            NotificationCategory::Ships,
+            locale.text("storm_season_ends", &[]),
+        ),
+        CalendarEvent::FleetSailing { port } => Notification::new(
+            NotificationCategory::Contracts,
+            locale.text("fleet_sailing", &[("port", &name_of(*port))]),
+        )
+        .about(*port),
+        // The crew system reports whether the wages were paid
+        CalendarEvent::Payday => return None,
+        // As does the finance system for the month's accounts
+        CalendarEvent::MonthBegins => return None,
+    };
+    Some(notification)
+}
+
+fn describe_refusal(reason: &PortRefusal, locale: &Locale) -> String {
+    match reason {
+        PortRefusal::CannotAfford(price) => {
+            locale.text("refusal_cannot_afford", &[("price", &locale.money(*price))])
+        }
+        PortRefusal::CannotAffordRepairs => locale.text("refusal_cannot_afford_repairs", &[]),
+        PortRefusal::NoRepairsNeeded => locale.text("refusal_no_repairs_needed", &[]),
+        PortRefusal::NoShipyard => locale.text("refusal_no_shipyard", &[]),
+        PortRefusal::NoSailors => locale.text("refusal_no_sailors", &[]),
+        PortRefusal::NoBerths => locale.text("refusal_no_berths", &[]),
+        PortRefusal::NothingStored => locale.text("refusal_nothing_stored", &[]),
+        PortRefusal::NotEnough(item) => {
+            locale.text("refusal_not_enough", &[("item", &locale.item(*item))])
+        }
+        PortRefusal::AlreadySold => locale.text("refusal_already_sold", &[]),
+        PortRefusal::NoShipDocked => locale.text("refusal_no_ship_docked", &[]),
+        PortRefusal::NoBank => locale.text("refusal_no_bank", &[]),
+        PortRefusal::LoanLimitReached => locale.text("refusal_loan_limit_reached", &[]),
+        PortRefusal::NothingOwed => locale.text("refusal_nothing_owed", &[]),
+    }
+}
+
+fn deposit_returned(deposit: u32, locale: &Locale) -> String {
+    if deposit > 0 {
+        locale.text("deposit_returned", &[("deposit", &locale.money(deposit))])
+    } else {
+        String::new()
+    }
+}
+
+fn penalty_charged(penalty: u32, locale: &Locale) -> String {
+    if penalty > 0 {
+        locale.text("penalty_charged", &[("penalty", &locale.money(penalty))])
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::ItemType;
+    use crate::{
+        age_of_sail::Severity,
+        finance::{ProfitAndLoss, TransactionKind},
+    };
+    use amethyst::{core::WithNamed, prelude::*};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn message_for(world: &World, event: GameEvent) -> String {
+        notification_for(&event, &world.read_storage::<Named>(), &Locale::default()).message
+    }
+
+    #[test]
+    fn refused_port_actions_are_warnings() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().build();
+
+        let notification = notification_for(
+            &GameEvent::PortActionRefused {
+                port,
+                reason: PortRefusal::CannotAfford(40),
+            },
+            &world.read_storage::<Named>(),
+            &Locale::default(),
+        );
+
+        assert_eq!("You cannot afford the £40.", notification.message);
+        assert_eq!(Some(port), notification.entity);
+        assert_eq!(Severity::Warning, notification.severity);
+    }
+
+    #[test]
+    fn expired_contract_without_consequences_is_one_sentence() {
+        let mut world = World::new();
+        world.register::<Named>();
+
+        let notification = notification_for(
+            &GameEvent::ContractExpired {
+                reclaimed: HashMap::new(),
+                reclaimed_by: None,
+                penalty: 0,
+            },
+            &world.read_storage::<Named>(),
+            &Locale::default(),
+        );
+
+        assert_eq!(
+            "A contract you have accepted has expired",
+            notification.message
+        );
+    }
+
+    #[test]
+    fn monthly_report_sums_up_the_accounts() {
+        let mut world = World::new();
+        world.register::<Named>();
+
+        let notification = notification_for(
+            &GameEvent::MonthlyReport {
+                report: ProfitAndLoss {
+                    year: 1680,
+                    month: 3,
+                    lines: vec![
+                        (TransactionKind::ContractIncome, 1200),
+                        (TransactionKind::Wages, -90),
+                        (TransactionKind::Repairs, -150),
+                    ],
+                },
+            },
+            &world.read_storage::<Named>(),
+            &Locale::default(),
+        );
+
+        assert_eq!(
+            "Accounts for March 1680: income £1,200, expenses £240, profit £960.",
+            notification.message
+        );
+    }
+
+    #[test]
+    fn accepted_contract_names_the_carrier() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("Portsmouth").build();
+        let destination = world.create_entity().named("London").build();
+        let carrier = world.create_entity().named("Mary Rose").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractAccepted {
+                port,
+                destination,
+                kind: ContractKind::Delivery,
+                carrier: Some(carrier),
+                goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                deposit: 0,
+                expires: None,
+            },
+        );
+
+        assert_eq!(
+            "Contract accepted. 10 tons of Sugar ready to be loaded onto Mary Rose at Portsmouth.",
+            message
+        );
+    }
+
+    #[test]
+    fn accepted_contract_mentions_deposit_and_expiry() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("Portsmouth").build();
+        let destination = world.create_entity().named("London").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractAccepted {
+                port,
+                destination,
+                kind: ContractKind::Delivery,
+                carrier: None,
+                goods: [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+                    .iter()
+                    .cloned()
+                    .collect(),
+                deposit: 30,
+                expires: Some(Utc.ymd(1680, 1, 1)),
+            },
+        );
+
+        assert_eq!(
+            "Contract accepted. 5 tons of Rum, 10 tons of Sugar ready to be loaded at Portsmouth. A deposit of £30 has been paid. It will expire on  1 January 1680.",
+            message
+        );
+    }
+
+    #[test]
+    fn accepted_escort_contract_names_the_escorted_ship() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("Portsmouth").build();
+        let destination = world.create_entity().named("London").build();
+        let escortee = world.create_entity().named("Mary Rose").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractAccepted {
+                port,
+                destination,
+                kind: ContractKind::Escort { escortee },
+                carrier: None,
+                goods: HashMap::new(),
+                deposit: 0,
+                expires: None,
+            },
+        );
+
+        assert_eq!("Contract accepted. Escort Mary Rose to London.", message);
+    }
+
+    #[test]
+    fn fulfilled_contract_returns_deposit() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("London").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractFulfilled {
+                port,
+                kind: ContractKind::Delivery,
+                goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                earned: 100,
+                deposit: 20,
+            },
+        );
+
+        assert_eq!(
+            "Completed contract for £100 at London. 10 tons of Sugar removed from cargo. Your deposit of £20 has been returned.",
+            message
+        );
+    }
+
+    #[test]
+    fn fulfilled_passenger_contract_counts_passengers() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("London").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractFulfilled {
+                port,
+                kind: ContractKind::Passengers,
+                goods: [(ItemType::Passengers, 12)].iter().cloned().collect(),
+                earned: 150,
+                deposit: 0,
+            },
+        );
+
+        assert_eq!(
+            "Completed contract for £150 at London. 12 passengers removed from cargo.",
+            message
+        );
+    }
+
+    #[test]
+    fn partial_delivery_lists_goods_still_required() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("London").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::GoodsDelivered {
+                port,
+                goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                payment: 20,
+                still_required: [(ItemType::Rum, 5)].iter().cloned().collect(),
+            },
+        );
+
+        assert_eq!(
+            "Delivered 10 tons of Sugar to London for £20. 5 tons of Rum still to be delivered.",
+            message
+        );
+    }
+
+    #[test]
+    fn seized_goods_mention_the_fine() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("London").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::GoodsSeized {
+                port,
+                goods: [(ItemType::Contraband, 5)].iter().cloned().collect(),
+                fine: 100,
+            },
+        );
+
+        assert_eq!(
+            "Customs at London seized 5 tons of Contraband. You were fined £100.",
+            message
+        );
+    }
+
+    #[test]
+    fn lost_escort_fails_the_contract() {
+        let mut world = World::new();
+        world.register::<Named>();
+
+        let message = message_for(&world, GameEvent::EscortLost { penalty: 0 });
+
+        assert_eq!(
+            "The ship you were escorting has been lost. Contract failed.",
+            message
+        );
+    }
+
+    #[test]
+    fn expired_contract_lists_reclaimed_goods_and_penalty() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let origin = world.create_entity().named("Portsmouth").build();
+
+        let message = message_for(
+            &world,
+            GameEvent::ContractExpired {
+                reclaimed: [(ItemType::Sugar, 10), (ItemType::Rum, 5)]
+                    .iter()
+                    .cloned()
+                    .collect(),
+                reclaimed_by: Some(origin),
+                penalty: 50,
+            },
+        );
+
+        assert_eq!(
+            "A contract you have accepted has expired. 5 tons of Rum, 10 tons of Sugar reclaimed by Portsmouth. A penalty of £50 has been charged.",
+            message
+        );
+    }
+
+    #[test]
+    fn cargo_messages_name_ship_and_port() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let port = world.create_entity().named("London").build();
+        let ship = world.create_entity().named("Mary Rose").build();
+
+        assert_eq!(
+            "10 tons of Sugar loaded onto Mary Rose at London.",
+            message_for(
+                &world,
+                GameEvent::CargoLoaded {
+                    ship,
+                    port,
+                    goods: [(ItemType::Sugar, 10)].iter().cloned().collect(),
+                },
+            )
+        );
+        assert_eq!(
+            "Mary Rose left London before loading finished.",
+            message_for(
+                &world,
+                GameEvent::TransferInterrupted {
+                    ship,
+                    port,
+                    unloading: false,
+                },
+            )
+        );
+    }
+
+    #[test]
+    fn destroyed_ship_blames_pirates() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let ship = world.create_entity().build();
+
+        assert_eq!(
+            "Ship destroyed by pirate",
+            message_for(&world, GameEvent::ShipDestroyed { ship })
+        );
+    }
+
+    #[test]
+    fn rebound_key_names_action_and_binding() {
+        let mut world = World::new();
+        world.register::<Named>();
+
+        assert_eq!(
+            "pause bound to P",
+            message_for(
+                &world,
+                GameEvent::KeyRebound {
+                    action: "pause".to_string(),
+                    binding: "P".to_string(),
+                },
+            )
+        );
+    }
+}
@@ -0,0 +1,702 @@
+use amethyst::{
+    core::{math::Point2, Transform, WithNamed},
+    ecs::{
+        Entities, Entity, LazyUpdate, Read, ReadStorage, System, SystemData, World, Write,
+        WriteStorage,
+    },
+    prelude::{Builder, SystemDesc},
+    renderer::SpriteRender,
+    shrev::{EventChannel, ReaderId},
+};
+use chrono::Utc;
+use std::collections::HashMap;
+
+use crate::{
+    age_of_sail::{Date, PlayerStatus},
+    components::{
+        Affiliation, Bank, BoundingBox, Cargo, CollisionShape, Controllable, Crew, Docked, Helm,
+        Hull, ItemType, Market, Ship, Shipyard, Tavern, Warehouse,
+    },
+    event::{GameEvent, PortAction, PortActionEvent, PortRefusal, UiUpdateEvent},
+    finance::{Ledger, TransactionKind},
+};
+
+// Goods are bought, sold and moved in and out of warehouses in lots of this many tons
+pub const TRADE_LOT: u32 = 5;
+// Provisions are bought in lots of this many days of food for one sailor
+pub const PROVISIONS_LOT: u32 = 100;
+pub const PROVISIONS_PRICE: u32 = 20;
+// Banks lend and are paid back this much at a time
+pub const LOAN_LOT: u32 = 500;
+const PLAYER_SHIP_SPRITE: usize = 1;
+
+pub struct PortServicesSystem {
+    reader_id: ReaderId<PortActionEvent>,
+}
+
+impl PortServicesSystem {
+    fn new(reader_id: ReaderId<PortActionEvent>) -> Self {
+        PortServicesSystem { reader_id }
+    }
+}
+
+impl<'s> System<'s> for PortServicesSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Docked>,
+        ReadStorage<'s, Market>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, SpriteRender>,
+        ReadStorage<'s, Bank>,
+        WriteStorage<'s, Cargo>,
+        WriteStorage<'s, Warehouse>,
+        WriteStorage<'s, Hull>,
+        WriteStorage<'s, Crew>,
+        WriteStorage<'s, Tavern>,
+        WriteStorage<'s, Shipyard>,
+        Read<'s, EventChannel<PortActionEvent>>,
+        Read<'s, LazyUpdate>,
+        Read<'s, Date>,
+        Write<'s, PlayerStatus>,
+        Write<'s, Ledger>,
+        Write<'s, EventChannel<GameEvent>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            dockeds,
+            markets,
+            locals,
+            sprite_renders,
+            banks,
+            mut cargos,
+            mut warehouses,
+            mut hulls,
+            mut crews,
+            mut taverns,
+            mut shipyards,
+            channel,
+            lazy,
+            date,
+            mut player_status,
+            mut ledger,
+            mut game_events,
+            mut update_channel,
+        ): Self::SystemData,
+    ) {
+        let today = date.current_date();
+
+        for event in channel.read(&mut self.reader_id) {
+            let port = event.port;
+            // Everything but buying a ship or dealing with the bank needs one of the player's ships
+            // alongside
+            let ship = event
+                .ship
+                .filter(|ship| dockeds.get(*ship).map_or(false, |d| d.port == port));
+
+            let result = match (&event.action, ship) {
+                (PortAction::Buy(item), Some(ship)) => {
+                    let price = markets.get(port).map_or(0, |m| m.buy_price(item)) * TRADE_LOT;
+                    pay(
+                        &mut player_status,
+                        &mut ledger,
+                        today,
+                        price,
+                        TransactionKind::Purchases,
+                    )
+                    .map(|_| add_items(&mut cargos.get_mut(ship).unwrap().items, *item, TRADE_LOT))
+                }
+                (PortAction::Sell(item), Some(ship)) => {
+                    let price = markets.get(port).map_or(0, |m| m.sell_price(item)) * TRADE_LOT;
+                    take_items(&mut cargos.get_mut(ship).unwrap().items, *item, TRADE_LOT).map(
+                        |_| {
+                            ledger.record(
+                                &mut player_status,
+                                today,
+                                TransactionKind::Sales,
+                                price as i32,
+                            )
+                        },
+                    )
+                }
+                (PortAction::Store(item), Some(ship)) => {
+                    take_items(&mut cargos.get_mut(ship).unwrap().items, *item, TRADE_LOT).map(
+                        |_| {
+                            let warehouse = warehouses
+                                .entry(port)
+                                .unwrap()
+                                .or_insert_with(Warehouse::default);
+                            add_items(&mut warehouse.items, *item, TRADE_LOT)
+                        },
+                    )
+                }
+                (PortAction::Retrieve(item), Some(ship)) => warehouses
+                    .get_mut(port)
+                    .ok_or(PortRefusal::NothingStored)
+                    .and_then(|warehouse| take_items(&mut warehouse.items, *item, TRADE_LOT))
+                    .map(|_| add_items(&mut cargos.get_mut(ship).unwrap().items, *item, TRADE_LOT)),
+                (PortAction::Repair, Some(ship)) => {
+                    match (shipyards.get(port), hulls.get_mut(ship)) {
+                        (Some(shipyard), Some(hull)) if hull.condition < 100 => {
+                            let affordable = if shipyard.repair_price == 0 {
+                                100
+                            } else {
+                                player_status.money.max(0) as u32 / shipyard.repair_price
+                            };
+                            let repaired = (100 - hull.condition).min(affordable);
+                            pay(
+                                &mut player_status,
+                                &mut ledger,
+                                today,
+                                repaired * shipyard.repair_price,
+                                TransactionKind::Repairs,
+                            )
+                            .and_then(|_| {
+                                if repaired == 0 {
+                                    Err(PortRefusal::CannotAffordRepairs)
+                                } else {
+                                    hull.condition += repaired;
+                                    Ok(())
+                                }
+                            })
+                        }
+                        (Some(_), Some(_)) => Err(PortRefusal::NoRepairsNeeded),
+                        _ => Err(PortRefusal::NoShipyard),
+                    }
+                }
+                (PortAction::Hire, Some(ship)) => {
+                    match (taverns.get_mut(port), crews.get_mut(ship)) {
+                        (Some(tavern), Some(crew)) => {
+                            if tavern.sailors == 0 {
+                                Err(PortRefusal::NoSailors)
+                            } else if crew.sailors >= crew.berths {
+                                Err(PortRefusal::NoBerths)
+                            } else {
+                                pay(
+                                    &mut player_status,
+                                    &mut ledger,
+                                    today,
+                                    tavern.signing_fee,
+                                    TransactionKind::Wages,
+                                )
+                                .map(|_| {
+                                    tavern.sailors -= 1;
+                                    crew.sailors += 1;
+                                })
+                            }
+                        }
+                        _ => Err(PortRefusal::NoSailors),
+                    }
+                }
+                (PortAction::BuyProvisions, Some(ship)) => match crews.get_mut(ship) {
+                    Some(crew) => pay(
+                        &mut player_status,
+                        &mut ledger,
+                        today,
+                        PROVISIONS_PRICE,
+                        TransactionKind::Purchases,
+                    )
+                    .map(|_| crew.provisions += PROVISIONS_LOT as f32),
+                    None => Err(PortRefusal::NoShipDocked),
+                },
+                (PortAction::BuyShip(index), _) => {
+                    let for_sale = shipyards
+                        .get(port)
+                        .and_then(|shipyard| shipyard.ships_for_sale.get(*index).cloned());
+                    match (for_sale, locals.get(port)) {
+                        (Some(for_sale), Some(port_local)) => pay(
+                            &mut player_status,
+                            &mut ledger,
+                            today,
+                            for_sale.price,
+                            TransactionKind::Purchases,
+                        )
+                        .map(|_| {
+                            shipyards
+                                .get_mut(port)
+                                .unwrap()
+                                .ships_for_sale
+                                .remove(*index);
+
+                            let mut sprite_render = sprite_renders.get(port).cloned();
+                            if let Some(sprite_render) = sprite_render.as_mut() {
+                                sprite_render.sprite_number = PLAYER_SHIP_SPRITE;
+                            }
+                            spawn_ship(
+                                &lazy,
+                                &entities,
+                                &for_sale.name,
+                                for_sale.base_speed,
+                                for_sale.berths,
+                                port_local.clone(),
+                                sprite_render,
+                            );
+                            game_events.single_write(GameEvent::ShipPurchased {
+                                name: for_sale.name.clone(),
+                                port,
+                            });
+                        }),
+                        _ => Err(PortRefusal::AlreadySold),
+                    }
+                }
+                (PortAction::TakeLoan, _) => match banks.get(port) {
+                    Some(bank) if ledger.owed_to(port) + LOAN_LOT > bank.limit => {
+                        Err(PortRefusal::LoanLimitReached)
+                    }
+                    Some(bank) => {
+                        ledger.borrow(&mut player_status, today, port, LOAN_LOT, bank.monthly_rate);
+                        Ok(())
+                    }
+                    None => Err(PortRefusal::NoBank),
+                },
+                (PortAction::RepayLoan, _) => match ledger.owed_to(port).min(LOAN_LOT) {
+                    0 => Err(PortRefusal::NothingOwed),
+                    amount if player_status.money < amount as i32 => {
+                        Err(PortRefusal::CannotAfford(amount))
+                    }
+                    amount => {
+                        ledger.repay(&mut player_status, today, port, amount);
+                        Ok(())
+                    }
+                },
+                (_, None) => Err(PortRefusal::NoShipDocked),
+            };
+
+            match result {
+                Ok(_) => {
+                    update_channel.single_write(UiUpdateEvent::Target(port));
+                    update_channel.single_write(UiUpdateEvent::PlayerStatus);
+                }
+                Err(reason) => {
+                    game_events.single_write(GameEvent::PortActionRefused { port, reason })
+                }
+            }
+        }
+    }
+}
+
+pub struct PortServicesSystemDesc;
+
+impl Default for PortServicesSystemDesc {
+    fn default() -> Self {
+        PortServicesSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, PortServicesSystem> for PortServicesSystemDesc {
+    fn build(self, world: &mut World) -> PortServicesSystem {
+        <PortServicesSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<PortActionEvent>>()
+            .register_reader();
+
+        PortServicesSystem::new(reader_id)
+    }
+}
+
+fn pay(
+    player_status: &mut PlayerStatus,
+    ledger: &mut Ledger,
+    date: chrono::Date<Utc>,
+    price: u32,
+    kind: TransactionKind,
+) -> Result<(), PortRefusal> {
+    if player_status.money < price as i32 {
+        Err(PortRefusal::CannotAfford(price))
+    } else {
+        ledger.record(player_status, date, kind, -(price as i32));
+        Ok(())
+    }
+}
+
+fn add_items(items: &mut HashMap<ItemType, u32>, item: ItemType, amount: u32) {
+    *items.entry(item).or_insert(0) += amount;
+}
+
+fn take_items(
+    items: &mut HashMap<ItemType, u32>,
+    item: ItemType,
+    amount: u32,
+) -> Result<(), PortRefusal> {
+    match items.get_mut(&item) {
+        Some(held) if *held >= amount => {
+            *held -= amount;
+            if *held == 0 {
+                items.remove(&item);
+            }
+            Ok(())
+        }
+        _ => Err(PortRefusal::NotEnough(item)),
+    }
+}
+
+// Purchased ships are launched at the port and dock there straight away
+fn spawn_ship(
+    lazy: &LazyUpdate,
+    entities: &Entities,
+    name: &str,
+    base_speed: f32,
+    berths: u32,
+    transform: Transform,
+    sprite_render: Option<SpriteRender>,
+) -> Entity {
+    let mut builder = lazy
+        .create_entity(entities)
+        .with(Ship { base_speed })
+        .named(name.to_string())
+        .with(Affiliation {
+            name: "You".to_string(),
+        })
+        .with(Controllable)
+        .with(Cargo::default())
+        .with(Hull::default())
+        .with(Crew {
+            sailors: 0,
+            berths,
+            ..Crew::default()
+        })
+        .with(transform)
+        .with(BoundingBox {
+            width: 8.0,
+            origin: Point2::new(0.0, 0.0),
+        })
+        .with(CollisionShape::Capsule {
+            length: 4.0,
+            radius: 2.0,
+        })
+        .with(Helm::default());
+    if let Some(sprite_render) = sprite_render {
+        builder = builder.with(sprite_render);
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::{ecs::Entity, prelude::*, Result};
+    use amethyst_test::prelude::*;
+
+    fn send(world: &mut World, port: Entity, ship: Option<Entity>, action: PortAction) {
+        world
+            .fetch_mut::<EventChannel<PortActionEvent>>()
+            .single_write(PortActionEvent { port, ship, action });
+    }
+
+    #[test]
+    fn buying_goods_loads_them_and_takes_payment() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 100 });
+                let port = world
+                    .create_entity()
+                    .with(Market {
+                        size: 1,
+                        demand: HashMap::new(),
+                    })
+                    .build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .build();
+
+                send(world, port, Some(ship), PortAction::Buy(ItemType::Sugar));
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    Some(&TRADE_LOT),
+                    cargos.get(ship).unwrap().items.get(&ItemType::Sugar)
+                );
+                assert_eq!(
+                    100 - (ItemType::Sugar.base_price() * TRADE_LOT) as i32,
+                    world.read_resource::<PlayerStatus>().money
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn goods_can_not_be_bought_without_the_money() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 10 });
+                let port = world
+                    .create_entity()
+                    .with(Market {
+                        size: 1,
+                        demand: HashMap::new(),
+                    })
+                    .build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Cargo::default())
+                    .build();
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                send(world, port, Some(ship), PortAction::Buy(ItemType::Rum));
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let cargos = world.read_storage::<Cargo>();
+                assert!(cargos.get(ship).unwrap().items.is_empty());
+                assert_eq!(10, world.read_resource::<PlayerStatus>().money);
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let refused = channel.read(&mut reader_id).any(|event| {
+                    matches!(
+                        event,
+                        GameEvent::PortActionRefused {
+                            reason: PortRefusal::CannotAfford(_),
+                            ..
+                        }
+                    )
+                });
+                assert!(refused, "Purchase refused");
+            })
+            .run()
+    }
+
+    #[test]
+    fn stored_goods_move_from_ship_to_warehouse() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                let port = world.create_entity().build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Cargo {
+                        items: [(ItemType::Whiskey, 12)].iter().cloned().collect(),
+                    })
+                    .build();
+
+                send(
+                    world,
+                    port,
+                    Some(ship),
+                    PortAction::Store(ItemType::Whiskey),
+                );
+                world.insert(EffectReturn((port, ship)));
+            })
+            .with_assertion(|world| {
+                let (port, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let cargos = world.read_storage::<Cargo>();
+                assert_eq!(
+                    Some(&(12 - TRADE_LOT)),
+                    cargos.get(ship).unwrap().items.get(&ItemType::Whiskey)
+                );
+                let warehouses = world.read_storage::<Warehouse>();
+                assert_eq!(
+                    Some(&TRADE_LOT),
+                    warehouses.get(port).unwrap().items.get(&ItemType::Whiskey)
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn repairs_stop_when_the_money_runs_out() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 25 });
+                let port = world
+                    .create_entity()
+                    .with(Shipyard {
+                        ships_for_sale: Vec::new(),
+                        repair_price: 2,
+                    })
+                    .build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Hull { condition: 60 })
+                    .build();
+
+                send(world, port, Some(ship), PortAction::Repair);
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(
+                    72,
+                    world.read_storage::<Hull>().get(ship).unwrap().condition
+                );
+                assert_eq!(1, world.read_resource::<PlayerStatus>().money);
+            })
+            .run()
+    }
+
+    #[test]
+    fn hired_sailors_leave_the_tavern() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 50 });
+                let port = world
+                    .create_entity()
+                    .with(Tavern {
+                        sailors: 3,
+                        signing_fee: 5,
+                    })
+                    .build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Crew {
+                        sailors: 10,
+                        berths: 20,
+                        ..Crew::default()
+                    })
+                    .build();
+
+                send(world, port, Some(ship), PortAction::Hire);
+                world.insert(EffectReturn((port, ship)));
+            })
+            .with_assertion(|world| {
+                let (port, ship) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                assert_eq!(11, world.read_storage::<Crew>().get(ship).unwrap().sailors);
+                assert_eq!(2, world.read_storage::<Tavern>().get(port).unwrap().sailors);
+                assert_eq!(45, world.read_resource::<PlayerStatus>().money);
+            })
+            .run()
+    }
+
+    #[test]
+    fn provisions_are_bought_for_the_docked_ship() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 50 });
+                let port = world.create_entity().build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port })
+                    .with(Crew {
+                        sailors: 10,
+                        berths: 20,
+                        provisions: 5.0,
+                        ..Crew::default()
+                    })
+                    .build();
+
+                send(world, port, Some(ship), PortAction::BuyProvisions);
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(
+                    105.0,
+                    world.read_storage::<Crew>().get(ship).unwrap().provisions
+                );
+                assert_eq!(30, world.read_resource::<PlayerStatus>().money);
+                assert_eq!(
+                    TransactionKind::Purchases,
+                    world.read_resource::<Ledger>().transactions[0].kind
+                );
+            })
+            .run()
+    }
+
+    #[test]
+    fn banks_lend_up_to_their_limit() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                let port = world
+                    .create_entity()
+                    .with(Bank {
+                        limit: 2 * LOAN_LOT,
+                        monthly_rate: 0.05,
+                    })
+                    .build();
+
+                for _ in 0..3 {
+                    send(world, port, None, PortAction::TakeLoan);
+                }
+                world.insert(EffectReturn(port));
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(
+                    2 * LOAN_LOT as i32,
+                    world.read_resource::<PlayerStatus>().money
+                );
+                assert_eq!(2 * LOAN_LOT, world.read_resource::<Ledger>().owed_to(port));
+            })
+            .with_effect(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+                world.write_resource::<PlayerStatus>().money = LOAN_LOT as i32 + 100;
+                send(world, port, None, PortAction::RepayLoan);
+                send(world, port, None, PortAction::RepayLoan);
+            })
+            .with_assertion(|world| {
+                let port = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert_eq!(100, world.read_resource::<PlayerStatus>().money);
+                assert_eq!(LOAN_LOT, world.read_resource::<Ledger>().owed_to(port));
+            })
+            .run()
+    }
+
+    #[test]
+    fn actions_need_a_ship_docked_at_the_port() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system_desc(PortServicesSystemDesc, "port_services", &[])
+            .with_effect(|world| {
+                world.insert(PlayerStatus { money: 100 });
+                let port = world
+                    .create_entity()
+                    .with(Market {
+                        size: 1,
+                        demand: HashMap::new(),
+                    })
+                    .build();
+                let other_port = world.create_entity().build();
+                let ship = world
+                    .create_entity()
+                    .with(Docked { port: other_port })
+                    .with(Cargo::default())
+                    .build();
+
+                send(world, port, Some(ship), PortAction::Buy(ItemType::Sugar));
+                world.insert(EffectReturn(ship));
+            })
+            .with_assertion(|world| {
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+
+                assert!(world
+                    .read_storage::<Cargo>()
+                    .get(ship)
+                    .unwrap()
+                    .items
+                    .is_empty());
+                assert_eq!(100, world.read_resource::<PlayerStatus>().money);
+            })
+            .run()
+    }
+}
@@ -1,22 +1,31 @@
 use amethyst::{
-    core::Transform,
-    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    core::{math::Point2, Transform},
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
     input::{InputHandler, StringBindings},
     shrev::EventChannel,
     renderer::Camera,
     window::ScreenDimensions,
-    winit::MouseButton,
 };
+use std::collections::HashMap;
 
 use crate::{
-    age_of_sail::{point_in_rect, point_mouse_to_world},
-    components::{Port, Ship, Selected},
+    age_of_sail::{point_in_rect, point_mouse_to_world, CameraView, CourseEditing},
+    components::{Controllable, Port, Ship, Selected},
 };
 use crate::{components::bounding_box::BoundingBox, event::UiUpdateEvent};
 
+// Drags shorter than this in world units are treated as a click
+const DRAG_THRESHOLD: f32 = 2.0;
+
+const CONTROL_GROUP_ACTIONS: [&str; 9] = [
+    "group_1", "group_2", "group_3", "group_4", "group_5", "group_6", "group_7", "group_8", "group_9",
+];
+
 #[derive(Default)]
 pub struct SelectSystem {
-    currently_selecting: bool,
+    drag_start: Option<Point2<f32>>,
+    control_groups: HashMap<usize, Vec<Entity>>,
+    control_group_key_down: Option<usize>,
 }
 
 impl<'s> System<'s> for SelectSystem {
@@ -25,9 +34,13 @@ impl<'s> System<'s> for SelectSystem {
         ReadStorage<'s, Camera>,
         ReadStorage<'s, BoundingBox>,
         ReadStorage<'s, Transform>,
+        ReadStorage<'s, Ship>,
+        ReadStorage<'s, Controllable>,
         WriteStorage<'s, Selected>,
         Read<'s, InputHandler<StringBindings>>,
         ReadExpect<'s, ScreenDimensions>,
+        Read<'s, CameraView>,
+        Read<'s, CourseEditing>,
         Write<'s, EventChannel<UiUpdateEvent>>,
     );
 
@@ -38,48 +51,108 @@ impl<'s> System<'s> for SelectSystem {
             cameras,
             bounding_boxes,
             locals,
+            ships,
+            controllables,
             mut selecteds,
             input,
             screen_dimensions,
+            camera_view,
+            editing,
             mut channel,
         ): Self::SystemData,
     ) {
-        if input.mouse_button_is_down(MouseButton::Left) {
-            if self.currently_selecting {
-                return;
-            } else {
-                for (e, _) in (&entities, &selecteds).join() {
-                    channel.single_write(UiUpdateEvent::Deselected(e));    
+        let adding = input.action_is_down("append").unwrap_or(false);
+
+        // Pressing a group while holding assign_group stores the selected player ships, pressing it alone selects them again
+        let control_group_key = CONTROL_GROUP_ACTIONS
+            .iter()
+            .position(|action| input.action_is_down(*action).unwrap_or(false));
+        if control_group_key != self.control_group_key_down {
+            if let Some(group) = control_group_key {
+                if input.action_is_down("assign_group").unwrap_or(false) {
+                    let members = (&entities, &controllables, &selecteds).join().map(|(e, _, _)| e).collect();
+                    self.control_groups.insert(group, members);
+                } else if let Some(members) = self.control_groups.get_mut(&group) {
+                    members.retain(|e| entities.is_alive(*e));
+                    if !adding {
+                        deselect_all(&entities, &mut selecteds, &mut channel);
+                    }
+                    for e in members.iter() {
+                        selecteds.insert(*e, Selected::default()).unwrap();
+                    }
                 }
-                selecteds.clear();
             }
         }
+        self.control_group_key_down = control_group_key;
 
-        if !input.mouse_button_is_down(MouseButton::Left) {
-            self.currently_selecting = false;
+        let mut mouse_world = None;
+        for (_, camera_local) in (&cameras, &locals).join() {
+            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                mouse_world = Some(point_mouse_to_world(
+                    mouse_x,
+                    mouse_y,
+                    &*screen_dimensions,
+                    camera_local.translation(),
+                    camera_view.zoom,
+                ));
+            }
         }
 
-        for (_, camera_local) in (&cameras, &locals).join() {
-            for (e, bounding_box, local) in (&entities, &bounding_boxes, &locals).join() {
-                if let Some((mouse_x, mouse_y)) = input.mouse_position() {
-                    let (left, right, top, bottom) = bounding_box.as_boundaries(local);
-                    if point_in_rect(
-                        point_mouse_to_world(mouse_x, mouse_y, &*screen_dimensions, camera_local.translation()),
-                        left,
-                        right,
-                        top,
-                        bottom,
-                    ) && input.mouse_button_is_down(MouseButton::Left)
-                    {
-                        self.currently_selecting = true;
-                        selecteds.insert(e, Selected::default()).unwrap();
+        match (input.action_is_down("select").unwrap_or(false), self.drag_start) {
+            // Pressing on a waypoint drags it instead
+            (true, None) if editing.dragging.is_none() => self.drag_start = mouse_world,
+            (false, Some(start)) => {
+                self.drag_start = None;
+                let end = match mouse_world {
+                    Some(end) => end,
+                    None => return,
+                };
+
+                if !adding {
+                    deselect_all(&entities, &mut selecteds, &mut channel);
+                }
+
+                if is_drag(start, end) {
+                    let (left, right, top, bottom) = selection_box(start, end);
+                    for (e, _, _, local) in (&entities, &ships, &controllables, &locals).join() {
+                        let location = Point2::new(local.translation().x, local.translation().y);
+                        if point_in_rect(location, left, right, top, bottom) {
+                            selecteds.insert(e, Selected::default()).unwrap();
+                        }
+                    }
+                } else {
+                    for (e, bounding_box, local) in (&entities, &bounding_boxes, &locals).join() {
+                        let (left, right, top, bottom) = bounding_box.as_boundaries(local);
+                        if point_in_rect(end, left, right, top, bottom) {
+                            selecteds.insert(e, Selected::default()).unwrap();
+                        }
                     }
                 }
             }
+            _ => {}
         }
     }
 }
 
+pub fn deselect_all(
+    entities: &Entities,
+    selecteds: &mut WriteStorage<Selected>,
+    channel: &mut EventChannel<UiUpdateEvent>,
+) {
+    for (e, _) in (entities, &*selecteds).join() {
+        channel.single_write(UiUpdateEvent::Deselected(e));
+    }
+    selecteds.clear();
+}
+
+fn is_drag(start: Point2<f32>, end: Point2<f32>) -> bool {
+    (end - start).magnitude() >= DRAG_THRESHOLD
+}
+
+fn selection_box(start: Point2<f32>, end: Point2<f32>) -> (f32, f32, f32, f32) {
+    (start.x.min(end.x), start.x.max(end.x), start.y.max(end.y), start.y.min(end.y))
+}
+
 pub struct SelectPortSystem;
 
 impl<'s> System<'s> for SelectPortSystem {
@@ -134,13 +207,13 @@ mod tests {
             .with_effect(move |world| {
                 let port = world
                     .create_entity()
-                    .with(Port)
+                    .with(Port::default())
                     .with(Selected)
                     .build();
 
                 let another_port = world
                     .create_entity()
-                    .with(Port)
+                    .with(Port::default())
                     .with(Selected)
                     .build();
 
@@ -215,13 +288,13 @@ mod tests {
             .with_effect(move |world| {
                 let port = world
                     .create_entity()
-                    .with(Port)
+                    .with(Port::default())
                     .with(Selected)
                     .build();
 
                 let another_port = world
                     .create_entity()
-                    .with(Port)
+                    .with(Port::default())
                     .with(Selected)
                     .build();
 
@@ -323,4 +396,19 @@ mod tests {
             })
             .run()
     }
+
+    #[test]
+    fn short_drags_are_clicks() {
+        assert!(!is_drag(Point2::new(10.0, 10.0), Point2::new(11.0, 11.0)));
+        assert!(is_drag(Point2::new(10.0, 10.0), Point2::new(10.0, 15.0)));
+    }
+
+    #[test]
+    fn selection_box_is_the_same_whichever_way_it_is_dragged() {
+        let expected = (10.0, 30.0, 50.0, 20.0);
+
+        assert_eq!(expected, selection_box(Point2::new(10.0, 20.0), Point2::new(30.0, 50.0)));
+        assert_eq!(expected, selection_box(Point2::new(30.0, 50.0), Point2::new(10.0, 20.0)));
+        assert_eq!(expected, selection_box(Point2::new(10.0, 50.0), Point2::new(30.0, 20.0)));
+    }
 }
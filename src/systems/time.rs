@@ -1,6 +1,7 @@
 use amethyst::{
     core::Time,
-    ecs::{Join, System, Write, WriteStorage},
+    ecs::{Join, Read, System, Write, WriteStorage},
+    shrev::EventChannel,
     ui::{UiFinder, UiText},
 };
 
@@ -8,7 +9,10 @@ use amethyst::{
 // At base speed
 pub const IN_GAME_TO_REAL_TIME_SECONDS: f32 = 3600.0;
 
-use crate::{age_of_sail::Date, components::Expiration};
+use crate::{
+    age_of_sail::Date, calendar::Season, components::Expiration, event::UiUpdateEvent,
+    locale::Locale,
+};
 
 pub struct UpdateTimeSystem;
 
@@ -17,18 +21,35 @@ impl<'s> System<'s> for UpdateTimeSystem {
         WriteStorage<'s, UiText>,
         Write<'s, Date>,
         Write<'s, Time>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+        Read<'s, Locale>,
         UiFinder<'s>,
     );
 
-    fn run(&mut self, (mut ui_texts, mut date, mut time, finder): Self::SystemData) {
+    fn run(
+        &mut self,
+        (mut ui_texts, mut date, mut time, mut channel, locale, finder): Self::SystemData,
+    ) {
+        let previous_date = date.current_date();
+
         time.set_time_scale(date.game_speed());
         date.time_elapsed += (IN_GAME_TO_REAL_TIME_SECONDS * time.delta_seconds()) as f64;
 
+        if date.current_date() != previous_date {
+            channel.single_write(UiUpdateEvent::NewDay);
+        }
+
         let current_time = finder.find("current_time");
 
         if let Some(current_time_ui) = current_time {
             if let Some(ui_text) = ui_texts.get_mut(current_time_ui) {
-                ui_text.text = date.current_date_string();
+                ui_text.text = locale.text(
+                    "calendar_date",
+                    &[
+                        ("date", &locale.date(date.current_date())),
+                        ("season", &locale.season(Season::of(date.current_date()))),
+                    ],
+                );
             }
         }
     }
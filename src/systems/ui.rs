@@ -1,74 +1,275 @@
 use ::amethyst::core::SystemDesc;
 use amethyst::{
-    core::{Parent, Named, RunNowDesc, timing::Time},
+    config::Config,
+    core::{math::Point2, Parent, Named, RunNowDesc, timing::Time, Transform},
     ecs::{
         Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, SystemData, World, Write,
         WriteStorage,
     },
+    input::{
+        Axis, BindingError, Bindings, Button, InputEvent, InputHandler, ScrollDirection, StringBindings,
+        VirtualKeyCode,
+    },
+    renderer::Camera,
     shrev::{EventChannel, ReaderId},
     ui::{
         Anchor, Interactable, LineMode, UiEvent, UiEventType, UiFinder, UiImage, UiText,
         UiTransform,
     },
+    window::ScreenDimensions,
 };
 
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
 use crate::{
-    age_of_sail::{Date, PlayerStatus, UiAssets, Notifications},
-    components::{Affiliation, Contract, Controllable, Port, Ship, Cargo, OwnedBy, Expiration},
-    event::UiUpdateEvent,
+    age_of_sail::{
+        sea_bounds, CameraView, ContractHistory, ContractRecord, Date, LoggedNotification,
+        NotificationCategory, NotificationLog, Notifications, PlayerStatus, Severity, UiAssets, WORLD_HEIGHT,
+        WORLD_WIDTH, NOTIFICATION_CATEGORIES,
+    },
+    components::{
+        contract::TRADE_GOODS, Affiliation, Bank, Cargo, CargoTransfer, Contract, ContractKind,
+        Controllable, Crew, Docked, Expiration, Hull, ItemType, Market, Port, Selected, Ship,
+        Shipyard, Tavern, Warehouse,
+    },
+    event::{DockEvent, GameEvent, PortAction, PortActionEvent, UiUpdateEvent, UndockEvent},
+    finance::Ledger,
+    locale::Locale,
+    map::Map,
+    systems::{
+        port::{LOAN_LOT, PROVISIONS_PRICE, TRADE_LOT},
+        select::deselect_all,
+    },
+    widgets::{Card, CardList, Widget, WidgetStorages, CARD_SPACING},
 };
 
 const NOTIFICATION_TIME: f32 = 5.0;
+const JOURNAL_HISTORY_LENGTH: usize = 5;
+const LOG_LINES: usize = 12;
+const LOG_WIDTH: f32 = 600.;
+// Room for the filter and paging controls above the entries
+const LOG_LIST_TOP: f32 = 35.;
+
+// Space left between the tabs and contract controls at the top and the scroll controls at the bottom
+const PORT_LIST_TOP: f32 = 118.;
+const PORT_LIST_BOTTOM: f32 = 35.;
+const ACTIVE_TAB_COLOR: [f32; 4] = [1.0, 0.85, 0.4, 1.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ContractSort {
+    Payment,
+    Expiry,
+    Destination,
+}
+
+impl ContractSort {
+    fn next(self) -> Self {
+        match self {
+            ContractSort::Payment => ContractSort::Expiry,
+            ContractSort::Expiry => ContractSort::Destination,
+            ContractSort::Destination => ContractSort::Payment,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ContractSort::Payment => "sort_payment",
+            ContractSort::Expiry => "sort_expiry",
+            ContractSort::Destination => "sort_destination",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ContractFilter {
+    All,
+    Delivery,
+    Passengers,
+    Mail,
+    Escort,
+    Smuggling,
+    Bulk,
+}
+
+impl ContractFilter {
+    fn next(self) -> Self {
+        match self {
+            ContractFilter::All => ContractFilter::Delivery,
+            ContractFilter::Delivery => ContractFilter::Passengers,
+            ContractFilter::Passengers => ContractFilter::Mail,
+            ContractFilter::Mail => ContractFilter::Escort,
+            ContractFilter::Escort => ContractFilter::Smuggling,
+            ContractFilter::Smuggling => ContractFilter::Bulk,
+            ContractFilter::Bulk => ContractFilter::All,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ContractFilter::All => "filter_all",
+            ContractFilter::Delivery => "filter_delivery",
+            ContractFilter::Passengers => "filter_passengers",
+            ContractFilter::Mail => "filter_mail",
+            ContractFilter::Escort => "filter_escort",
+            ContractFilter::Smuggling => "filter_smuggling",
+            ContractFilter::Bulk => "filter_bulk",
+        }
+    }
+
+    fn matches(self, kind: &ContractKind) -> bool {
+        match (self, kind) {
+            (ContractFilter::All, _) => true,
+            (ContractFilter::Delivery, ContractKind::Delivery) => true,
+            (ContractFilter::Passengers, ContractKind::Passengers) => true,
+            (ContractFilter::Mail, ContractKind::Mail) => true,
+            (ContractFilter::Escort, ContractKind::Escort { .. }) => true,
+            (ContractFilter::Smuggling, ContractKind::Smuggling { .. }) => true,
+            (ContractFilter::Bulk, ContractKind::Bulk { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+// What the port contract list is sorted on
+#[derive(Clone, Debug, PartialEq)]
+struct ContractListing {
+    contract: Entity,
+    payment: u32,
+    expiry: Option<chrono::Date<chrono::Utc>>,
+    destination: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PortTab {
+    Contracts,
+    Market,
+    Ships,
+    Shipyard,
+    Crew,
+    Warehouse,
+    Bank,
+}
+
+const PORT_TABS: [(PortTab, &str); 7] = [
+    (PortTab::Contracts, "port_tab_contracts"),
+    (PortTab::Market, "port_tab_market"),
+    (PortTab::Ships, "port_tab_ships"),
+    (PortTab::Shipyard, "port_tab_shipyard"),
+    (PortTab::Crew, "port_tab_crew"),
+    (PortTab::Warehouse, "port_tab_warehouse"),
+    (PortTab::Bank, "port_tab_bank"),
+];
+
+// Identifies the cards on the port screen so they can be diffed and their buttons told apart
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PortCard {
+    Contract(Entity),
+    Notice,
+    Item(ItemType),
+    Ship(Entity),
+    ForSale(usize),
+    Repair,
+    Tavern,
+    Bank,
+}
 
 pub struct PortPanelSystem {
     reader_id: ReaderId<UiUpdateEvent>,
+    ui_reader_id: ReaderId<UiEvent>,
+    dock_reader_id: ReaderId<DockEvent>,
+    undock_reader_id: ReaderId<UndockEvent>,
     selected_port: Option<Entity>,
+    tab: PortTab,
+    cards: CardList<PortCard>,
+    sort: ContractSort,
+    filter: ContractFilter,
+    // Index of the first card shown
+    scroll: usize,
 }
 
 impl PortPanelSystem {
-    fn new(reader_id: ReaderId<UiUpdateEvent>) -> Self {
+    fn new(
+        reader_id: ReaderId<UiUpdateEvent>,
+        ui_reader_id: ReaderId<UiEvent>,
+        dock_reader_id: ReaderId<DockEvent>,
+        undock_reader_id: ReaderId<UndockEvent>,
+    ) -> Self {
         PortPanelSystem {
             reader_id,
+            ui_reader_id,
+            dock_reader_id,
+            undock_reader_id,
             selected_port: None,
+            tab: PortTab::Contracts,
+            cards: CardList::new("port_card"),
+            sort: ContractSort::Payment,
+            filter: ContractFilter::All,
+            scroll: 0,
         }
     }
 }
 
 impl<'s> System<'s> for PortPanelSystem {
     type SystemData = (
-        Entities<'s>,
         ReadStorage<'s, Port>,
         ReadStorage<'s, Named>,
         ReadStorage<'s, Contract>,
         ReadStorage<'s, Expiration>,
-        WriteStorage<'s, OwnedBy>,
+        ReadStorage<'s, Market>,
+        ReadStorage<'s, Affiliation>,
+        ReadStorage<'s, Hull>,
+        ReadStorage<'s, Crew>,
+        ReadStorage<'s, Cargo>,
+        ReadStorage<'s, Docked>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Selected>,
+        ReadStorage<'s, Shipyard>,
+        ReadStorage<'s, Tavern>,
+        ReadStorage<'s, Warehouse>,
+        ReadStorage<'s, Bank>,
         Read<'s, EventChannel<UiUpdateEvent>>,
-        WriteStorage<'s, UiText>,
-        WriteStorage<'s, UiTransform>,
-        WriteStorage<'s, UiImage>,
-        WriteStorage<'s, Interactable>,
-        WriteStorage<'s, Parent>,
-        ReadExpect<'s, UiAssets>,
+        Read<'s, EventChannel<UiEvent>>,
+        Read<'s, EventChannel<DockEvent>>,
+        Read<'s, EventChannel<UndockEvent>>,
+        Write<'s, EventChannel<PortActionEvent>>,
+        Read<'s, Ledger>,
+        Read<'s, Locale>,
+        WidgetStorages<'s>,
     );
 
     fn run(
         &mut self,
         (
-            entities,
             ports,
             names,
             contracts,
             expirations,
-            mut owned_bys,
+            markets,
+            affiliations,
+            hulls,
+            crews,
+            cargos,
+            dockeds,
+            controllables,
+            selecteds,
+            shipyards,
+            taverns,
+            warehouses,
+            banks,
             channel,
-            mut ui_texts,
-            mut ui_transforms,
-            mut ui_images,
-            mut ui_interactables,
-            mut parents,
-            ui_assets,
+            ui_channel,
+            dock_channel,
+            undock_channel,
+            mut action_channel,
+            ledger,
+            locale,
+            mut widgets,
         ): Self::SystemData,
     ) {
+        let mut port_to_show = None;
+
         for event in channel.read(&mut self.reader_id) {
             let target = match event {
                 UiUpdateEvent::Target(e) => Some(*e),
@@ -77,7 +278,9 @@ impl<'s> System<'s> for PortPanelSystem {
 
             if let Some(target) = target {
                 let e = if ports.get(target).is_some() {
-                    self.selected_port.replace(target);
+                    if self.selected_port.replace(target) != Some(target) {
+                        self.scroll = 0;
+                    }
                     target
                 } else if contracts.get(target).is_some() {
                     self.selected_port.unwrap_or(target)
@@ -86,296 +289,412 @@ impl<'s> System<'s> for PortPanelSystem {
                 };
 
                 if ports.get(e).is_some() {
-                    let contract_ui_elements =
-                        find_ui_elements(&entities, &ui_transforms, "contract");
-                    for c in contract_ui_elements {
-                        entities.delete(c).unwrap();
-                    }
-
-                    let port_name_element =
-                        find_ui_element(&entities, &ui_transforms, "port_info_name").unwrap();
-
-                    if let Some(text) = ui_texts.get_mut(port_name_element) {
-                        text.text = names.get(e).map_or("???".to_string(),|name| name.name.to_string());
-                    };
+                    port_to_show = Some(e);
+                }
+            }
+        }
 
-                    let unexpired_contracts_held_by_port = (&entities, &contracts, &owned_bys)
-                        .join()
-                        .filter(|(entity, _, o)| 
-                            { 
-                                let expired = if let Some(expiration) = expirations.get(*entity) {
-                                    expiration.expired
-                                } else {
-                                    false
-                                };
-                                return o.entity == e && !expired;
+        // Ships coming and going change who can trade here
+        let docking_ports = dock_channel
+            .read(&mut self.dock_reader_id)
+            .map(|event| event.port)
+            .chain(undock_channel.read(&mut self.undock_reader_id).map(|event| event.port))
+            .collect::<Vec<Entity>>();
+        if self.selected_port.map_or(false, |port| docking_ports.contains(&port)) {
+            port_to_show = port_to_show.or(self.selected_port);
+        }
 
-                        })
-                        .map(|(e, c, _)| (e, c))
-                        .collect::<Vec<(Entity, &Contract)>>();
+        for event in ui_channel.read(&mut self.ui_reader_id) {
+            if event.event_type != UiEventType::ClickStop {
+                continue;
+            }
 
-                    let port_info_container =
-                        find_ui_element(&entities, &ui_transforms, "port_info").unwrap();
+            let port = match self.selected_port {
+                Some(port) => port,
+                None => continue,
+            };
 
-                    let mut offset = 50.;
+            if let Some((card, button)) = self.cards.button_at(event.target) {
+                let action = match (button, card) {
+                    ("port_buy", PortCard::Item(item)) => Some(PortAction::Buy(item)),
+                    ("port_sell", PortCard::Item(item)) => Some(PortAction::Sell(item)),
+                    ("port_store", PortCard::Item(item)) => Some(PortAction::Store(item)),
+                    ("port_retrieve", PortCard::Item(item)) => Some(PortAction::Retrieve(item)),
+                    ("port_repair", _) => Some(PortAction::Repair),
+                    ("port_hire", _) => Some(PortAction::Hire),
+                    ("port_provisions", _) => Some(PortAction::BuyProvisions),
+                    ("port_buy_ship", PortCard::ForSale(index)) => Some(PortAction::BuyShip(index)),
+                    ("port_take_loan", _) => Some(PortAction::TakeLoan),
+                    ("port_repay_loan", _) => Some(PortAction::RepayLoan),
+                    _ => None,
+                };
+                if let Some(action) = action {
+                    action_channel.single_write(PortActionEvent {
+                        port,
+                        ship: trading_ship(port, &widgets.entities, &dockeds, &controllables, &selecteds),
+                        action,
+                    });
+                }
+                continue;
+            }
 
-                    for (e, c) in unexpired_contracts_held_by_port {
-                        let destination_name = names.get(c.destination).unwrap().name.to_string();
+            let clicked = widgets.ui_transforms.get(event.target).map(|t| t.id.clone());
+            let tab = PORT_TABS
+                .iter()
+                .find(|(_, id)| clicked.as_ref().map(String::as_str) == Some(*id))
+                .map(|(tab, _)| *tab);
+            match (clicked.as_ref().map(String::as_str), tab) {
+                (_, Some(tab)) => {
+                    self.tab = tab;
+                    self.scroll = 0;
+                }
+                (Some("port_contract_sort"), _) if self.tab == PortTab::Contracts => {
+                    self.sort = self.sort.next();
+                    self.scroll = 0;
+                }
+                (Some("port_contract_filter"), _) if self.tab == PortTab::Contracts => {
+                    self.filter = self.filter.next();
+                    self.scroll = 0;
+                }
+                (Some("port_scroll_up"), _) => self.scroll = self.scroll.saturating_sub(1),
+                (Some("port_scroll_down"), _) => self.scroll += 1,
+                _ => continue,
+            }
+            port_to_show = port_to_show.or(Some(port));
+        }
 
-                        let expiration_ui_space = if expirations.get(e).is_some() {
-                            20.
-                        } else {
-                            0.
-                        };
-
-                        let contract_ui_height = 70. + 20. * c.goods_required.keys().len() as f32 + expiration_ui_space;
-                        let contract_ui_width = 175.;
-
-                        let contract_parent = entities
-                            .build_entity()
-                            .with(
-                                UiTransform::new(
-                                    "contract".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -offset,
-                                    1.,
-                                    contract_ui_width,
-                                    contract_ui_height,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                UiImage::NineSlice {
-                                    x_start: 4,
-                                    y_start: 4,
-                                    width: 56,
-                                    height: 56,
-                                    left_dist: 4,
-                                    right_dist: 4,
-                                    top_dist: 4,
-                                    bottom_dist: 4,
-                                    tex: ui_assets.panel.clone(),
-                                    texture_dimensions: [64, 64],
-                                },
-                                &mut ui_images,
-                            )
-                            .with(
-                                Parent {
-                                    entity: port_info_container,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        entities
-                            .build_entity()
-                            .with(
-                                UiText::new(
-                                    ui_assets.font.clone(),
-                                    format!("For: {}", destination_name),
-                                    [1.0, 1.0, 1.0, 1.0],
-                                    15.,
-                                    LineMode::Single,
-                                    Anchor::Middle,
-                                ),
-                                &mut ui_texts,
-                            )
-                            .with(
-                                UiTransform::new(
-                                    "contract_destination".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -5.,
-                                    1.,
-                                    contract_ui_width,
-                                    20.,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                Parent {
-                                    entity: contract_parent,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        entities
-                            .build_entity()
-                            .with(
-                                UiText::new(
-                                    ui_assets.font.clone(),
-                                    format!("£{}", c.payment),
-                                    [1.0, 1.0, 1.0, 1.0],
-                                    15.,
-                                    LineMode::Single,
-                                    Anchor::Middle,
-                                ),
-                                &mut ui_texts,
-                            )
-                            .with(
-                                UiTransform::new(
-                                    "contract_payment".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -25.,
-                                    1.,
-                                    contract_ui_width,
-                                    20.,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                Parent {
-                                    entity: contract_parent,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        let mut goods_offset = 45.;
-                        for (item, amount) in &c.goods_required {
-                            entities
-                                .build_entity()
-                                .with(
-                                    UiText::new(
-                                        ui_assets.font.clone(),
-                                        format!("{}: {} tons", item, amount),
-                                        [1.0, 1.0, 1.0, 1.0],
-                                        15.,
-                                        LineMode::Single,
-                                        Anchor::Middle,
-                                    ),
-                                    &mut ui_texts,
-                                )
-                                .with(
-                                    UiTransform::new(
-                                        "goods".to_string(),
-                                        Anchor::TopMiddle,
-                                        Anchor::TopMiddle,
-                                        0.,
-                                        -goods_offset,
-                                        1.,
-                                        175.,
-                                        20.,
-                                    ),
-                                    &mut ui_transforms,
-                                )
-                                .with(
-                                    Parent {
-                                        entity: contract_parent,
-                                    },
-                                    &mut parents,
-                                )
-                                .build();
-
-                            goods_offset += 20.;
-                        }
+        let e = match port_to_show {
+            Some(e) => e,
+            None => return,
+        };
 
-                        if let Some(expiration) = expirations.get(e) {
-                            entities
-                                .build_entity()
-                                .with(
-                                    UiText::new(
-                                        ui_assets.font.clone(),
-                                        format!("Expires: {}", expiration.expiration_date.format("%e %B %Y").to_string()),
-                                        [1.0, 1.0, 1.0, 1.0],
-                                        15.,
-                                        LineMode::Single,
-                                        Anchor::Middle,
-                                    ),
-                                    &mut ui_texts,
-                                )
-                                .with(
-                                    UiTransform::new(
-                                        "expiration_date".to_string(),
-                                        Anchor::TopMiddle,
-                                        Anchor::TopMiddle,
-                                        0.,
-                                        -goods_offset,
-                                        1.,
-                                        175.,
-                                        20.,
-                                    ),
-                                    &mut ui_transforms,
-                                )
-                                .with(
-                                    Parent {
-                                        entity: contract_parent,
-                                    },
-                                    &mut parents,
-                                )
-                                .build();
+        let port_name_element =
+            find_ui_element(&widgets.entities, &widgets.ui_transforms, "port_info_name").unwrap();
 
-                        }
+        if let Some(text) = widgets.ui_texts.get_mut(port_name_element) {
+            text.text = names.get(e).map_or("???".to_string(),|name| name.name.to_string());
+        };
 
+        let ship = trading_ship(e, &widgets.entities, &dockeds, &controllables, &selecteds);
+        let name_of = |e: Entity| names.get(e).map_or("???".to_string(), |name| name.name.to_string());
 
+        let mut cards = Vec::new();
+        let needs_ship = ![PortTab::Contracts, PortTab::Ships, PortTab::Bank].contains(&self.tab);
+        if ship.is_none() && needs_ship {
+            cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("dock_to_trade", &[]))])));
+        }
 
-                        entities
-                            .build_entity()
-                            .with(
-                                UiText::new(
-                                    ui_assets.font.clone(),
-                                    "Accept".to_string(),
-                                    [1.0, 1.0, 1.0, 1.0],
-                                    15.,
-                                    LineMode::Single,
-                                    Anchor::Middle,
-                                ),
-                                &mut ui_texts,
-                            )
-                            .with(
-                                UiTransform::new(
-                                    "accept_button".to_string(),
-                                    Anchor::BottomMiddle,
-                                    Anchor::BottomMiddle,
-                                    0.,
-                                    5.,
-                                    1.,
-                                    60.,
-                                    20.,
+        match self.tab {
+            PortTab::Contracts => {
+                let mut listings = (&widgets.entities, &contracts, &widgets.owned_bys)
+                    .join()
+                    .filter(|(entity, c, o)| {
+                        let expired = expirations.get(*entity).map_or(false, |expiration| expiration.expired);
+                        o.entity == e && !expired && self.filter.matches(&c.kind)
+                    })
+                    .map(|(contract_entity, c, _)| ContractListing {
+                        contract: contract_entity,
+                        payment: c.payment,
+                        expiry: expirations.get(contract_entity).map(|expiration| expiration.expiration_date),
+                        destination: name_of(c.destination),
+                    })
+                    .collect::<Vec<ContractListing>>();
+                sort_listings(&mut listings, self.sort);
+
+                cards.extend(listings.iter().map(|listing| {
+                    let c = contracts.get(listing.contract).unwrap();
+                    (
+                        PortCard::Contract(listing.contract),
+                        port_contract_card(listing.contract, c, &names, &expirations, &locale),
+                    )
+                }));
+            }
+            PortTab::Market => match markets.get(e) {
+                Some(market) => cards.extend(TRADE_GOODS.iter().map(|item| {
+                    let mut rows = vec![
+                        Widget::Label(locale.item(*item)),
+                        Widget::Label(locale.text(
+                            "market_prices",
+                            &[("buy", &locale.money(market.buy_price(item))), ("sell", &locale.money(market.sell_price(item)))],
+                        )),
+                    ];
+                    if let Some(ship) = ship {
+                        let aboard = cargos.get(ship).and_then(|cargo| cargo.items.get(item)).copied().unwrap_or(0);
+                        rows.push(Widget::Label(locale.text("aboard", &[("quantity", &locale.quantity(*item, aboard))])));
+                        rows.push(port_button("port_buy", locale.text("buy_lot", &[("count", &TRADE_LOT)]), e));
+                        rows.push(port_button("port_sell", locale.text("sell_lot", &[("count", &TRADE_LOT)]), e));
+                    }
+                    (PortCard::Item(*item), Card::framed(rows))
+                })),
+                None => cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_market", &[]))]))),
+            },
+            PortTab::Ships => {
+                cards.extend((&widgets.entities, &dockeds).join().filter(|(_, docked)| docked.port == e).map(
+                    |(docked_ship, _)| {
+                        let mut rows = vec![
+                            Widget::Label(name_of(docked_ship)),
+                            Widget::Label(affiliations.get(docked_ship).map_or("".to_string(), |a| a.name.clone())),
+                        ];
+                        if let Some(hull) = hulls.get(docked_ship) {
+                            rows.push(Widget::Label(locale.text("hull_condition", &[("condition", &hull.condition)])));
+                        }
+                        if let Some(crew) = crews.get(docked_ship) {
+                            rows.push(Widget::Label(crew_count(crew, &locale)));
+                            rows.push(Widget::Label(crew_morale(crew, &locale)));
+                        }
+                        if let Some(cargo) = cargos.get(docked_ship) {
+                            rows.push(Widget::Label(
+                                locale.text("cargo_total", &[("total", &cargo.items.values().sum::<u32>())]),
+                            ));
+                        }
+                        (PortCard::Ship(docked_ship), Card::framed(rows))
+                    },
+                ));
+                if cards.is_empty() {
+                    cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_ships_in_port", &[]))])));
+                }
+            }
+            PortTab::Shipyard => match shipyards.get(e) {
+                Some(shipyard) => {
+                    if let Some((ship, hull)) = ship.and_then(|ship| hulls.get(ship).map(|hull| (ship, hull))) {
+                        let mut rows = vec![Widget::Label(locale.text(
+                            "ship_hull_condition",
+                            &[("ship", &name_of(ship)), ("condition", &hull.condition)],
+                        ))];
+                        if hull.condition < 100 {
+                            let cost = (100 - hull.condition) * shipyard.repair_price;
+                            rows.push(port_button("port_repair", locale.text("repair", &[("cost", &locale.money(cost))]), e));
+                        }
+                        cards.push((PortCard::Repair, Card::framed(rows)));
+                    }
+                    cards.extend(shipyard.ships_for_sale.iter().enumerate().map(|(index, for_sale)| {
+                        (
+                            PortCard::ForSale(index),
+                            Card::framed(vec![
+                                Widget::Label(for_sale.name.clone()),
+                                Widget::Label(locale.text(
+                                    "ship_for_sale",
+                                    &[("speed", &for_sale.base_speed), ("berths", &for_sale.berths)],
+                                )),
+                                Widget::Label(locale.money(for_sale.price)),
+                                port_button("port_buy_ship", locale.text("buy_ship", &[]), e),
+                            ]),
+                        )
+                    }));
+                }
+                None => cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_shipyard", &[]))]))),
+            },
+            PortTab::Crew => match taverns.get(e) {
+                Some(tavern) => {
+                    cards.push((
+                        PortCard::Tavern,
+                        Card::framed(vec![
+                            Widget::Label(locale.text("sailors_for_hire", &[("sailors", &tavern.sailors)])),
+                            Widget::Label(locale.text("signing_fee", &[("fee", &locale.money(tavern.signing_fee))])),
+                        ]),
+                    ));
+                    if let Some((ship, crew)) = ship.and_then(|ship| crews.get(ship).map(|crew| (ship, crew))) {
+                        cards.push((
+                            PortCard::Ship(ship),
+                            Card::framed(vec![
+                                Widget::Label(name_of(ship)),
+                                Widget::Label(crew_count(crew, &locale)),
+                                Widget::Label(crew_morale(crew, &locale)),
+                                port_button("port_hire", locale.text("hire", &[]), e),
+                                port_button(
+                                    "port_provisions",
+                                    locale.text("buy_provisions", &[("cost", &locale.money(PROVISIONS_PRICE))]),
+                                    e,
                                 ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                UiImage::NineSlice {
-                                    x_start: 6,
-                                    y_start: 6,
-                                    width: 52,
-                                    height: 52,
-                                    left_dist: 2,
-                                    right_dist: 2,
-                                    top_dist: 2,
-                                    bottom_dist: 2,
-                                    tex: ui_assets.panel.clone(),
-                                    texture_dimensions: [64, 64],
-                                },
-                                &mut ui_images,
-                            )
-                            .with(
-                                Parent {
-                                    entity: contract_parent,
-                                },
-                                &mut parents,
-                            )
-                            .with(Interactable, &mut ui_interactables)
-                            .with(OwnedBy { entity: e }, &mut owned_bys)
-                            .build();
-
-                        offset += contract_ui_height + 5.;
+                            ]),
+                        ));
+                    }
+                }
+                None => cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_sailors", &[]))]))),
+            },
+            PortTab::Warehouse => match warehouses.get(e) {
+                Some(warehouse) => {
+                    let aboard = ship.and_then(|ship| cargos.get(ship)).map_or(HashMap::new(), |cargo| cargo.items.clone());
+                    let items = TRADE_GOODS
+                        .iter()
+                        .chain(warehouse.items.keys())
+                        .chain(aboard.keys())
+                        .unique()
+                        .sorted()
+                        .copied()
+                        .collect::<Vec<ItemType>>();
+                    cards.extend(items.into_iter().map(|item| {
+                        let stored = warehouse.items.get(&item).copied().unwrap_or(0);
+                        let mut rows = vec![
+                            Widget::Label(locale.item(item)),
+                            Widget::Label(locale.text("stored", &[("quantity", &locale.quantity(item, stored))])),
+                        ];
+                        if ship.is_some() {
+                            let held = aboard.get(&item).copied().unwrap_or(0);
+                            rows.push(Widget::Label(locale.text("aboard", &[("quantity", &locale.quantity(item, held))])));
+                            rows.push(port_button("port_store", locale.text("store_lot", &[("count", &TRADE_LOT)]), e));
+                            rows.push(port_button("port_retrieve", locale.text("take_lot", &[("count", &TRADE_LOT)]), e));
+                        }
+                        (PortCard::Item(item), Card::framed(rows))
+                    }));
+                }
+                None => cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_warehouse", &[]))]))),
+            },
+            PortTab::Bank => match banks.get(e) {
+                Some(bank) => {
+                    let owed = ledger.owed_to(e);
+                    let rate = (bank.monthly_rate * 100.0).round() as u32;
+                    let mut rows = vec![
+                        Widget::Label(locale.text("bank_terms", &[("limit", &locale.money(bank.limit)), ("rate", &rate)])),
+                        Widget::Label(locale.text("owed", &[("owed", &locale.money(owed))])),
+                    ];
+                    if owed + LOAN_LOT <= bank.limit {
+                        rows.push(port_button("port_take_loan", locale.text("borrow", &[("amount", &locale.money(LOAN_LOT))]), e));
                     }
+                    if owed > 0 {
+                        let amount = owed.min(LOAN_LOT);
+                        rows.push(port_button("port_repay_loan", locale.text("repay", &[("amount", &locale.money(amount))]), e));
+                    }
+                    cards.push((PortCard::Bank, Card::framed(rows)));
+                }
+                None => cards.push((PortCard::Notice, Card::plain(vec![Widget::Label(locale.text("no_bank", &[]))]))),
+            },
+        }
+
+        let port_info_container =
+            find_ui_element(&widgets.entities, &widgets.ui_transforms, "port_info").unwrap();
+        let list_space = widgets
+            .ui_transforms
+            .get(port_info_container)
+            .map_or(0., |t| t.height - PORT_LIST_TOP - PORT_LIST_BOTTOM);
+
+        self.scroll = self.scroll.min(cards.len().saturating_sub(1));
+        let heights = cards.iter().map(|(_, card)| card.height()).collect::<Vec<f32>>();
+        let shown = cards_that_fit(&heights[self.scroll..], list_space);
+
+        let count_text = if cards.len() > shown {
+            locale.text(
+                "list_count",
+                &[("first", &(self.scroll + 1)), ("last", &(self.scroll + shown)), ("total", &cards.len())],
+            )
+        } else {
+            "".to_string()
+        };
+        let (sort_text, filter_text) = if self.tab == PortTab::Contracts {
+            (
+                locale.text("sort_by", &[("sort", &locale.text(self.sort.key(), &[]))]),
+                locale.text("show", &[("filter", &locale.text(self.filter.key(), &[]))]),
+            )
+        } else {
+            ("".to_string(), "".to_string())
+        };
+        let control_texts = vec![
+            ("port_contract_sort", sort_text),
+            ("port_contract_filter", filter_text),
+            ("port_list_count", count_text),
+            ("port_scroll_up", locale.text("port_scroll_up", &[])),
+            ("port_scroll_down", locale.text("port_scroll_down", &[])),
+        ];
+        for (id, text) in control_texts {
+            if let Some(element) = find_ui_element(&widgets.entities, &widgets.ui_transforms, id) {
+                if let Some(ui_text) = widgets.ui_texts.get_mut(element) {
+                    ui_text.text = text;
+                }
+            }
+        }
+
+        for (tab, id) in PORT_TABS.iter() {
+            if let Some(element) = find_ui_element(&widgets.entities, &widgets.ui_transforms, id) {
+                if let Some(ui_text) = widgets.ui_texts.get_mut(element) {
+                    ui_text.text = locale.text(id, &[]);
+                    ui_text.color = if *tab == self.tab { ACTIVE_TAB_COLOR } else { [1.0, 1.0, 1.0, 1.0] };
                 }
             }
         }
+
+        let visible = cards.into_iter().skip(self.scroll).take(shown).collect();
+        self.cards.update(port_info_container, PORT_LIST_TOP, visible, &mut widgets);
+    }
+}
+
+fn sort_listings(listings: &mut Vec<ContractListing>, sort: ContractSort) {
+    match sort {
+        ContractSort::Payment => listings.sort_by(|a, b| b.payment.cmp(&a.payment)),
+        // Contracts that never expire go last
+        ContractSort::Expiry => listings.sort_by_key(|listing| (listing.expiry.is_none(), listing.expiry)),
+        ContractSort::Destination => listings.sort_by(|a, b| a.destination.cmp(&b.destination)),
     }
 }
 
+// How many cards from the top fit in the space, always at least one so a tall card can still be seen
+fn cards_that_fit(heights: &[f32], space: f32) -> usize {
+    let mut used = 0.;
+    let fitting = heights
+        .iter()
+        .take_while(|height| {
+            used += *height + CARD_SPACING;
+            used - CARD_SPACING <= space
+        })
+        .count();
+
+    fitting.max(heights.len().min(1))
+}
+
+// The selected ship if it is docked at the port, otherwise any of the player's ships docked there
+fn trading_ship<'a>(
+    port: Entity,
+    entities: &Entities<'a>,
+    dockeds: &ReadStorage<'a, Docked>,
+    controllables: &ReadStorage<'a, Controllable>,
+    selecteds: &ReadStorage<'a, Selected>,
+) -> Option<Entity> {
+    let docked_here = (entities, dockeds, controllables)
+        .join()
+        .filter(|(_, docked, _)| docked.port == port)
+        .map(|(e, _, _)| e)
+        .collect::<Vec<Entity>>();
+    docked_here
+        .iter()
+        .find(|e| selecteds.get(**e).is_some())
+        .or_else(|| docked_here.first())
+        .copied()
+}
+
+fn port_button(id: &str, text: String, port: Entity) -> Widget {
+    Widget::Button {
+        id: id.to_string(),
+        text,
+        owner: port,
+    }
+}
 
+fn port_contract_card<'a>(
+    e: Entity,
+    contract: &Contract,
+    names: &ReadStorage<'a, Named>,
+    expirations: &ReadStorage<'a, Expiration>,
+    locale: &Locale,
+) -> Card {
+    let destination_name = names.get(contract.destination).unwrap().name.to_string();
+
+    let mut rows = vec![
+        Widget::Label(locale.text("contract_for", &[("destination", &destination_name)])),
+        Widget::Label(locale.money(contract.payment)),
+    ];
+    rows.extend(contract_description(contract, names, locale).into_iter().map(Widget::Label));
+    if let Some(expiration) = expirations.get(e) {
+        rows.push(Widget::Label(
+            locale.text("contract_expires", &[("date", &locale.date(expiration.expiration_date))]),
+        ));
+    }
+    rows.push(Widget::Button {
+        id: "accept_button".to_string(),
+        text: locale.text("accept", &[]),
+        owner: e,
+    });
 
+    Card::framed(rows)
+}
 
 pub struct PortPanelSystemDesc;
 
@@ -392,8 +711,11 @@ impl<'a, 'b> SystemDesc<'a, 'b, PortPanelSystem> for PortPanelSystemDesc {
         let reader_id = world
             .fetch_mut::<EventChannel<UiUpdateEvent>>()
             .register_reader();
+        let ui_reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
+        let dock_reader_id = world.fetch_mut::<EventChannel<DockEvent>>().register_reader();
+        let undock_reader_id = world.fetch_mut::<EventChannel<UndockEvent>>().register_reader();
 
-        PortPanelSystem::new(reader_id)
+        PortPanelSystem::new(reader_id, ui_reader_id, dock_reader_id, undock_reader_id)
     }
 }
 
@@ -405,278 +727,106 @@ impl<'a, 'b> RunNowDesc<'a, 'b, PortPanelSystem> for PortPanelSystemDesc {
 
 pub struct ContractPanelSystem {
     reader_id: ReaderId<UiUpdateEvent>,
+    // Keyed by contract, the history block at the bottom has no contract
+    contract_cards: CardList<Option<Entity>>,
 }
 
 impl ContractPanelSystem {
     fn new(reader_id: ReaderId<UiUpdateEvent>) -> Self {
         ContractPanelSystem {
             reader_id,
+            contract_cards: CardList::new("player_contract"),
         }
     }
 }
 
 impl<'s> System<'s> for ContractPanelSystem {
     type SystemData = (
-        Entities<'s>,
         ReadStorage<'s, Named>,
         ReadStorage<'s, Contract>,
         ReadStorage<'s, Expiration>,
-        WriteStorage<'s, OwnedBy>,
+        ReadStorage<'s, CargoTransfer>,
+        Read<'s, Date>,
+        Read<'s, ContractHistory>,
         Read<'s, EventChannel<UiUpdateEvent>>,
-        WriteStorage<'s, UiText>,
-        WriteStorage<'s, UiTransform>,
-        WriteStorage<'s, UiImage>,
-        WriteStorage<'s, Parent>,
-        ReadExpect<'s, UiAssets>,
+        Read<'s, Locale>,
+        WidgetStorages<'s>,
     );
 
     fn run(
         &mut self,
         (
-            entities,
             names,
             contracts,
             expirations,
-            owned_bys,
+            cargo_transfers,
+            date,
+            history,
             channel,
-            mut ui_texts,
-            mut ui_transforms,
-            mut ui_images,
-            mut parents,
-            ui_assets,
+            locale,
+            mut widgets,
         ): Self::SystemData,
     ) {
         for event in channel.read(&mut self.reader_id) {
-            let target = match event {
-                UiUpdateEvent::Target(e) => Some(*e),
-                _ => None,
+            let refresh = match event {
+                UiUpdateEvent::Target(e) => contracts.get(*e).is_some(),
+                UiUpdateEvent::Journal | UiUpdateEvent::NewDay => true,
+                _ => false,
             };
 
-            if let Some(target) = target {
-                if contracts.get(target).is_some() {
-                    let contract_ui_elements =
-                        find_ui_elements(&entities, &ui_transforms, "player_contract");
-                    for c in contract_ui_elements {
-                        entities.delete(c).unwrap();
-                    }
-
-                    let active_contracts_held_by_player = (&entities, &contracts, !&owned_bys)
-                        .join()
-                        .filter(|(entity, contract, _)| 
-                            { 
-                                let expired = if let Some(expiration) = expirations.get(*entity) {
-                                    expiration.expired
-                                } else {
-                                    false
-                                };
-                                return !expired && !contract.fulfilled;
-
-                        })
-                        .map(|(e, c, _)| (e, c))
-                        .collect::<Vec<(Entity, &Contract)>>();
-
-                    let player_contracts_info_container =
-                        find_ui_element(&entities, &ui_transforms, "player_contracts_info").unwrap();
-
-                    let mut offset = 50.;
-
-                    for (e, c) in active_contracts_held_by_player {
+            if refresh {
+                let mut cards = (&widgets.entities, &contracts, !&widgets.owned_bys)
+                    .join()
+                    .filter(|(entity, contract, _)| {
+                        let expired = expirations.get(*entity).map_or(false, |expiration| expiration.expired);
+                        !expired && !contract.fulfilled
+                    })
+                    .map(|(e, c, _)| {
                         let destination_name = names.get(c.destination).unwrap().name.to_string();
 
-                        let expiration_ui_space = if expirations.get(e).is_some() {
-                            20.
-                        } else {
-                            0.
-                        };
-
-                        let contract_ui_height = 50. + 20. * c.goods_required.keys().len() as f32 + expiration_ui_space;
-                        let contract_ui_width = 175.;
-
-                        let contract_parent = entities
-                            .build_entity()
-                            .with(
-                                UiTransform::new(
-                                    "player_contract".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -offset,
-                                    1.,
-                                    contract_ui_width,
-                                    contract_ui_height,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                UiImage::NineSlice {
-                                    x_start: 4,
-                                    y_start: 4,
-                                    width: 56,
-                                    height: 56,
-                                    left_dist: 4,
-                                    right_dist: 4,
-                                    top_dist: 4,
-                                    bottom_dist: 4,
-                                    tex: ui_assets.panel.clone(),
-                                    texture_dimensions: [64, 64],
-                                },
-                                &mut ui_images,
-                            )
-                            .with(
-                                Parent {
-                                    entity: player_contracts_info_container,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        entities
-                            .build_entity()
-                            .with(
-                                UiText::new(
-                                    ui_assets.font.clone(),
-                                    format!("For: {}", destination_name),
-                                    [1.0, 1.0, 1.0, 1.0],
-                                    15.,
-                                    LineMode::Single,
-                                    Anchor::Middle,
-                                ),
-                                &mut ui_texts,
-                            )
-                            .with(
-                                UiTransform::new(
-                                    "contract_destination".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -5.,
-                                    1.,
-                                    contract_ui_width,
-                                    20.,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                Parent {
-                                    entity: contract_parent,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        entities
-                            .build_entity()
-                            .with(
-                                UiText::new(
-                                    ui_assets.font.clone(),
-                                    format!("£{}", c.payment),
-                                    [1.0, 1.0, 1.0, 1.0],
-                                    15.,
-                                    LineMode::Single,
-                                    Anchor::Middle,
-                                ),
-                                &mut ui_texts,
-                            )
-                            .with(
-                                UiTransform::new(
-                                    "contract_payment".to_string(),
-                                    Anchor::TopMiddle,
-                                    Anchor::TopMiddle,
-                                    0.,
-                                    -25.,
-                                    1.,
-                                    contract_ui_width,
-                                    20.,
-                                ),
-                                &mut ui_transforms,
-                            )
-                            .with(
-                                Parent {
-                                    entity: contract_parent,
-                                },
-                                &mut parents,
-                            )
-                            .build();
-
-                        let mut goods_offset = 45.;
-                        for (item, amount) in &c.goods_required {
-                            entities
-                                .build_entity()
-                                .with(
-                                    UiText::new(
-                                        ui_assets.font.clone(),
-                                        format!("{}: {} tons", item, amount),
-                                        [1.0, 1.0, 1.0, 1.0],
-                                        15.,
-                                        LineMode::Single,
-                                        Anchor::Middle,
-                                    ),
-                                    &mut ui_texts,
-                                )
-                                .with(
-                                    UiTransform::new(
-                                        "goods".to_string(),
-                                        Anchor::TopMiddle,
-                                        Anchor::TopMiddle,
-                                        0.,
-                                        -goods_offset,
-                                        1.,
-                                        175.,
-                                        20.,
-                                    ),
-                                    &mut ui_transforms,
-                                )
-                                .with(
-                                    Parent {
-                                        entity: contract_parent,
-                                    },
-                                    &mut parents,
-                                )
-                                .build();
-
-                            goods_offset += 20.;
-                        }
-
+                        let mut rows = vec![
+                            Widget::Label(locale.text("contract_for", &[("destination", &destination_name)])),
+                            Widget::Label(locale.money(c.payment)),
+                        ];
+                        rows.extend(contract_description(c, &names, &locale).into_iter().map(Widget::Label));
+                        rows.push(Widget::Label(loading_status(e, c, &names, &cargo_transfers, &locale)));
                         if let Some(expiration) = expirations.get(e) {
-                            entities
-                                .build_entity()
-                                .with(
-                                    UiText::new(
-                                        ui_assets.font.clone(),
-                                        format!("Expires: {}", expiration.expiration_date.format("%e %B %Y").to_string()),
-                                        [1.0, 1.0, 1.0, 1.0],
-                                        15.,
-                                        LineMode::Single,
-                                        Anchor::Middle,
-                                    ),
-                                    &mut ui_texts,
-                                )
-                                .with(
-                                    UiTransform::new(
-                                        "expiration_date".to_string(),
-                                        Anchor::TopMiddle,
-                                        Anchor::TopMiddle,
-                                        0.,
-                                        -goods_offset,
-                                        1.,
-                                        175.,
-                                        20.,
-                                    ),
-                                    &mut ui_transforms,
-                                )
-                                .with(
-                                    Parent {
-                                        entity: contract_parent,
-                                    },
-                                    &mut parents,
-                                )
-                                .build();
-
+                            rows.push(Widget::Label(expiry_countdown(
+                                expiration.expiration_date,
+                                date.current_date(),
+                                &locale,
+                            )));
                         }
 
-                        offset += contract_ui_height + 5.;
+                        (Some(e), Card::framed(rows))
+                    })
+                    .collect::<Vec<(Option<Entity>, Card)>>();
+
+                if !history.records.is_empty() {
+                    let mut history_lines = vec![Widget::Label(locale.text("history", &[]))];
+                    history_lines.extend(
+                        history
+                            .records
+                            .iter()
+                            .rev()
+                            .take(JOURNAL_HISTORY_LENGTH)
+                            .flat_map(|record| history_description(record, &locale))
+                            .map(Widget::Label),
+                    );
+                    cards.push((None, Card::plain(history_lines)));
+                }
+
+                let player_contracts_info_container =
+                    find_ui_element(&widgets.entities, &widgets.ui_transforms, "player_contracts_info").unwrap();
+                if let Some(title) =
+                    find_ui_element(&widgets.entities, &widgets.ui_transforms, "player_contracts_info_title")
+                {
+                    if let Some(ui_text) = widgets.ui_texts.get_mut(title) {
+                        ui_text.text = locale.text("player_contracts_info_title", &[]);
                     }
                 }
+
+                self.contract_cards.update(player_contracts_info_container, 50., cards, &mut widgets);
             }
         }
     }
@@ -715,14 +865,20 @@ impl<'a, 'b> RunNowDesc<'a, 'b, ContractPanelSystem> for ContractPanelSystemDesc
 
 pub struct ShipPanelSystem {
     reader_id: ReaderId<UiUpdateEvent>,
-    selected_ship: Option<Entity>,
+    dock_reader_id: ReaderId<DockEvent>,
+    undock_reader_id: ReaderId<UndockEvent>,
 }
 
 impl ShipPanelSystem {
-    fn new(reader_id: ReaderId<UiUpdateEvent>) -> Self {
+    fn new(
+        reader_id: ReaderId<UiUpdateEvent>,
+        dock_reader_id: ReaderId<DockEvent>,
+        undock_reader_id: ReaderId<UndockEvent>,
+    ) -> Self {
         ShipPanelSystem {
             reader_id,
-            selected_ship: None,
+            dock_reader_id,
+            undock_reader_id,
         }
     }
 }
@@ -735,11 +891,16 @@ impl<'s> System<'s> for ShipPanelSystem {
         ReadStorage<'s, Cargo>,
         ReadStorage<'s, Controllable>,
         ReadStorage<'s, Named>,
+        ReadStorage<'s, Docked>,
+        ReadStorage<'s, Selected>,
         Read<'s, EventChannel<UiUpdateEvent>>,
+        Read<'s, EventChannel<DockEvent>>,
+        Read<'s, EventChannel<UndockEvent>>,
         WriteStorage<'s, UiText>,
         WriteStorage<'s, UiTransform>,
         WriteStorage<'s, Parent>,
         ReadExpect<'s, UiAssets>,
+        Read<'s, Locale>,
     );
 
     fn run(
@@ -751,353 +912,1460 @@ impl<'s> System<'s> for ShipPanelSystem {
             cargos,
             controllables,
             nameds,
+            dockeds,
+            selecteds,
             channel,
+            dock_channel,
+            undock_channel,
             mut ui_texts,
             mut ui_transforms,
             mut parents,
             ui_assets,
+            locale,
         ): Self::SystemData,
     ) {
-        for event in channel.read(&mut self.reader_id) {
+        let selected_ships = (&entities, &ships, &selecteds).join().map(|(e, _, _)| e).collect::<Vec<_>>();
+
+        let mut refresh = channel.read(&mut self.reader_id).fold(false, |refresh, event| match event {
+            UiUpdateEvent::Target(_) | UiUpdateEvent::Deselected(_) => true,
+            _ => refresh,
+        });
+
+        // Refresh when a selected ship docks or leaves port
+        refresh |= dock_channel
+            .read(&mut self.dock_reader_id)
+            .map(|event| event.ship)
+            .chain(undock_channel.read(&mut self.undock_reader_id).map(|event| event.ship))
+            .fold(false, |refresh, ship| refresh || selected_ships.contains(&ship));
+
+        if !refresh {
+            return;
+        }
 
-            let target = match event {
-                UiUpdateEvent::Target(e) => Some(*e),
-                UiUpdateEvent::Deselected(e) => {
-                    if let Some(selected_ship) = self.selected_ship {
-                        if selected_ship == *e {
-                            self.selected_ship = None;
-                            let cargo_uis = find_ui_elements(&entities, &ui_transforms, "ship_info_cargo");
-                            for cargo_ui in cargo_uis {
-                                entities.delete(cargo_ui).unwrap();
-                            };
-
-                            let ship_name_element =
-                                find_ui_element(&entities, &ui_transforms, "ship_info_name").unwrap();
-
-                            if let Some(text) = ui_texts.get_mut(ship_name_element) {
-                                text.text = "".to_string(); 
-                            };
-                            
-                             let ship_affiliation_element =
-                                find_ui_element(&entities, &ui_transforms, "ship_info_affiliation").unwrap();
-
-                            if let Some(text) = ui_texts.get_mut(ship_affiliation_element) {
-                                text.text = "".to_string(); 
-                            }
-                        };
-                    };
-                    None
+        let cargo_uis = find_ui_elements(&entities, &ui_transforms, "ship_info_cargo");
+        for cargo_ui in cargo_uis {
+            entities.delete(cargo_ui).unwrap();
+        };
+
+        let (name, affiliation, status) = match selected_ships.as_slice() {
+            [] => ("".to_string(), "".to_string(), "".to_string()),
+            [e] => (
+                nameds.get(*e).map_or("???".to_string(), |named| named.name.to_string()),
+                affiliations.get(*e).map_or("???".to_string(), |affiliation| affiliation.name.to_string()),
+                match dockeds.get(*e).and_then(|docked| nameds.get(docked.port)) {
+                    Some(port_name) => locale.text("docked_at", &[("port", &port_name.name)]),
+                    None => locale.text("at_sea", &[]),
                 },
-                _ => None,
-            };
+            ),
+            _ => selection_summary(
+                selected_ships.len(),
+                selected_ships
+                    .iter()
+                    .map(|e| affiliations.get(*e).map_or("???", |affiliation| affiliation.name.as_str()))
+                    .collect(),
+                selected_ships.iter().filter(|e| dockeds.get(**e).is_some()).count(),
+                &locale,
+            ),
+        };
+
+        for (element, text) in vec![
+            ("ship_info_name", name),
+            ("ship_info_affiliation", affiliation),
+            ("ship_info_status", status),
+        ] {
+            if let Some(ui_element) = find_ui_element(&entities, &ui_transforms, element) {
+                if let Some(ui_text) = ui_texts.get_mut(ui_element) {
+                    ui_text.text = text;
+                }
+            }
+        }
 
-            if let Some(target) = target {
-                let e = if ships.get(target).is_some() {
-                    self.selected_ship.replace(target);
-                    target
-                } else {
-                    self.selected_ship.unwrap_or(target)
-                };
+        // Player can only see cargo in ships they control. May change later
+        let cargo = total_cargo(
+            selected_ships
+                .iter()
+                .filter(|e| controllables.get(**e).is_some())
+                .filter_map(|e| cargos.get(*e))
+                .collect(),
+        );
+
+        if cargo.is_empty() {
+            return;
+        }
 
-                if ships.get(e).is_some() {
-                    let cargo_uis = find_ui_elements(&entities, &ui_transforms, "ship_info_cargo");
-                    for cargo_ui in cargo_uis {
-                        entities.delete(cargo_ui).unwrap();
-                    };
+        let ship_info_container =
+            find_ui_element(&entities, &ui_transforms, "ship_info").unwrap();
 
-                    let ship_name_element =
-                        find_ui_element(&entities, &ui_transforms, "ship_info_name").unwrap();
+        let mut offset = 105.;
 
-                    if let Some(text) = ui_texts.get_mut(ship_name_element) {
-                        text.text = if let Some(named) = nameds.get(e) { 
-                            named.name.to_string()
-                        } else {
-                            "???".to_string()
-                        }
-                    };
+        for (item, amount) in cargo {
+            entities
+                .build_entity()
+                .with(
+                    UiText::new(
+                        ui_assets.font.clone(),
+                        locale.text("goods_line", &[("item", &locale.item(item)), ("quantity", &locale.quantity(item, amount))]),
+                        [1.0, 1.0, 1.0, 1.0],
+                        15.,
+                        LineMode::Single,
+                        Anchor::Middle,
+                    ),
+                    &mut ui_texts,
+                )
+                .with(
+                    UiTransform::new(
+                        "ship_info_cargo".to_string(),
+                        Anchor::TopMiddle,
+                        Anchor::TopMiddle,
+                        0.,
+                        -offset,
+                        1.,
+                        155.,
+                        20.,
+                    ),
+                    &mut ui_transforms,
+                )
+                .with(
+                    Parent {
+                        entity: ship_info_container,
+                    },
+                    &mut parents,
+                )
+                .build();
+
+            offset += 20.;
+        }
+    }
+}
 
-                    let ship_affiliation_element =
-                        find_ui_element(&entities, &ui_transforms, "ship_info_affiliation").unwrap();
+pub struct ShipPanelSystemDesc;
 
-                    if let Some(text) = ui_texts.get_mut(ship_affiliation_element) {
-                        text.text = if let Some(affiliation) = affiliations.get(e) { 
-                            affiliation.name.to_string()
-                        } else {
-                            "???".to_string()
-                        }
-                    };
+impl Default for ShipPanelSystemDesc {
+    fn default() -> Self {
+        ShipPanelSystemDesc {}
+    }
+}
 
-                    // Player can only see cargo in ships they control. May change later
-                    if controllables.get(e).is_some() {
-                        if let Some(cargo) = cargos.get(e) {
-                            let ship_info_container =
-                                find_ui_element(&entities, &ui_transforms, "ship_info").unwrap();
-
-                            let mut offset = 80.;
-                            
-                            for (item, amount) in &cargo.items {
-                                if *amount == 0 {
-                                    continue;
-                                }
-                                entities
-                                    .build_entity()
-                                    .with(
-                                        UiText::new(
-                                            ui_assets.font.clone(),
-                                            format!("{}: {} tons", item, amount),
-                                            [1.0, 1.0, 1.0, 1.0],
-                                            15.,
-                                            LineMode::Single,
-                                            Anchor::Middle,
-                                        ),
-                                        &mut ui_texts,
-                                    )
-                                    .with(
-                                        UiTransform::new(
-                                            "ship_info_cargo".to_string(),
-                                            Anchor::TopMiddle,
-                                            Anchor::TopMiddle,
-                                            0.,
-                                            -offset,
-                                            1.,
-                                            155.,
-                                            20.,
-                                        ),
-                                        &mut ui_transforms,
-                                    )
-                                    .with(
-                                        Parent {
-                                            entity: ship_info_container,
-                                        },
-                                        &mut parents,
-                                    )
-                                    .build();
-
-                                offset += 20.;
-                            }
-                        }
-                    }
-                     
-                }
+impl<'a, 'b> SystemDesc<'a, 'b, ShipPanelSystem> for ShipPanelSystemDesc {
+    fn build(self, world: &mut World) -> ShipPanelSystem {
+        <ShipPanelSystem as System<'_>>::SystemData::setup(world);
 
-            }
-        }
+        let reader_id = world
+            .fetch_mut::<EventChannel<UiUpdateEvent>>()
+            .register_reader();
+        let dock_reader_id = world.fetch_mut::<EventChannel<DockEvent>>().register_reader();
+        let undock_reader_id = world
+            .fetch_mut::<EventChannel<UndockEvent>>()
+            .register_reader();
+
+        ShipPanelSystem::new(reader_id, dock_reader_id, undock_reader_id)
+    }
+}
+
+impl<'a, 'b> RunNowDesc<'a, 'b, ShipPanelSystem> for ShipPanelSystemDesc {
+    fn build(self, world: &mut World) -> ShipPanelSystem {
+        <ShipPanelSystemDesc as SystemDesc<'a, 'b, ShipPanelSystem>>::build(self, world)
+    }
+}
+
+pub struct PlayerStatusSystem {
+    reader_id: ReaderId<UiUpdateEvent>,
+}
+
+impl PlayerStatusSystem {
+    fn new(reader_id: ReaderId<UiUpdateEvent>) -> Self {
+        PlayerStatusSystem { reader_id }
     }
 }
 
+impl<'s> System<'s> for PlayerStatusSystem {
+    type SystemData = (
+        WriteStorage<'s, UiText>,
+        Read<'s, PlayerStatus>,
+        Read<'s, Ledger>,
+        Read<'s, EventChannel<UiUpdateEvent>>,
+        Read<'s, Locale>,
+        UiFinder<'s>,
+    );
 
+    fn run(&mut self, (mut ui_texts, player_status, ledger, channel, locale, finder): Self::SystemData) {
+        for event in channel.read(&mut self.reader_id) {
+            if let UiUpdateEvent::PlayerStatus = event {
+                let player_money = finder.find("player_money").unwrap();
+                if let Some(ui_text) = ui_texts.get_mut(player_money) {
+                    ui_text.text = match ledger.total_owed() {
+                        0 => locale.money(player_status.money),
+                        owed => locale.text(
+                            "money_and_debt",
+                            &[("money", &locale.money(player_status.money)), ("owed", &locale.money(owed))],
+                        ),
+                    };
+                }
+            }
+        }
+    }
+}
 
+pub struct PlayerStatusSystemDesc;
 
-pub struct ShipPanelSystemDesc;
+impl Default for PlayerStatusSystemDesc {
+    fn default() -> Self {
+        PlayerStatusSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, PlayerStatusSystem> for PlayerStatusSystemDesc {
+    fn build(self, world: &mut World) -> PlayerStatusSystem {
+        <PlayerStatusSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world
+            .fetch_mut::<EventChannel<UiUpdateEvent>>()
+            .register_reader();
+
+        PlayerStatusSystem::new(reader_id)
+    }
+}
+
+pub struct GameSpeedSystem {
+    reader_id: ReaderId<UiEvent>,
+    input_reader_id: ReaderId<InputEvent<StringBindings>>,
+}
+
+impl GameSpeedSystem {
+    fn new(reader_id: ReaderId<UiEvent>, input_reader_id: ReaderId<InputEvent<StringBindings>>) -> Self {
+        GameSpeedSystem {
+            reader_id,
+            input_reader_id,
+        }
+    }
+}
+
+impl<'s> System<'s> for GameSpeedSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        Read<'s, EventChannel<UiEvent>>,
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, Date>,
+    );
+
+    fn run(&mut self, (entities, mut ui_transforms, channel, input_channel, mut date): Self::SystemData) {
+        for event in channel.read(&mut self.reader_id) {
+            if event.event_type == UiEventType::ClickStop {
+                let button = ui_transforms
+                    .get(event.target)
+                    .map_or(None, |t| Some(t.id.clone()));
+
+                if let Some(button_id) = button {
+                    match button_id.as_ref() {
+                        "play_button" => set_paused(false, &mut date, &entities, &mut ui_transforms),
+                        "pause_button" => set_paused(true, &mut date, &entities, &mut ui_transforms),
+                        "increase_speed_button" => change_speed(2., &mut date),
+                        "decrease_speed_button" => change_speed(0.5, &mut date),
+                        _ => (),
+                    };
+                }
+            }
+        }
+
+        for event in input_channel.read(&mut self.input_reader_id) {
+            if let InputEvent::ActionPressed(action) = event {
+                match action.as_ref() {
+                    "pause" => {
+                        let paused = !date.paused;
+                        set_paused(paused, &mut date, &entities, &mut ui_transforms);
+                    }
+                    "speed_up" => change_speed(2., &mut date),
+                    "slow_down" => change_speed(0.5, &mut date),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+// Only the button for the opposite state is shown above the time panel
+fn set_paused<'a>(
+    paused: bool,
+    date: &mut Date,
+    entities: &Entities<'a>,
+    ui_transforms: &mut WriteStorage<'a, UiTransform>,
+) {
+    date.paused = paused;
+
+    let (shown, hidden) = if paused {
+        ("play_button", "pause_button")
+    } else {
+        ("pause_button", "play_button")
+    };
+
+    for (id, local_z) in vec![(shown, 1.), (hidden, -1.)] {
+        if let Some(button) = find_ui_element(entities, ui_transforms, id) {
+            ui_transforms.get_mut(button).unwrap().local_z = local_z;
+        }
+    }
+}
+
+fn change_speed(factor: f32, date: &mut Date) {
+    date.current_speed = (factor * date.current_speed).max(1.).min(8.);
+}
+
+pub struct GameSpeedSystemDesc;
+
+impl Default for GameSpeedSystemDesc {
+    fn default() -> Self {
+        GameSpeedSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, GameSpeedSystem> for GameSpeedSystemDesc {
+    fn build(self, world: &mut World) -> GameSpeedSystem {
+        <GameSpeedSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
+        let input_reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
+            .register_reader();
+
+        GameSpeedSystem::new(reader_id, input_reader_id)
+    }
+}
+
+
+// Line of the key bindings screen
+#[derive(Clone, PartialEq)]
+enum BindingId {
+    Action(String),
+    Axis(String),
+}
+
+impl BindingId {
+    fn name(&self) -> &str {
+        match self {
+            BindingId::Action(name) | BindingId::Axis(name) => name,
+        }
+    }
+}
+
+pub struct KeyBindingsSystem {
+    reader_id: ReaderId<UiEvent>,
+    input_reader_id: ReaderId<InputEvent<StringBindings>>,
+    config_path: PathBuf,
+    open: bool,
+    // Action or axis waiting for the next key or mouse button pressed
+    rebinding: Option<BindingId>,
+    // Button already pressed for the positive end of the axis being rebound
+    positive: Option<Button>,
+    buttons: HashMap<Entity, BindingId>,
+}
+
+impl KeyBindingsSystem {
+    fn new(
+        reader_id: ReaderId<UiEvent>,
+        input_reader_id: ReaderId<InputEvent<StringBindings>>,
+        config_path: PathBuf,
+    ) -> Self {
+        KeyBindingsSystem {
+            reader_id,
+            input_reader_id,
+            config_path,
+            open: false,
+            rebinding: None,
+            positive: None,
+            buttons: HashMap::new(),
+        }
+    }
+}
+
+impl<'s> System<'s> for KeyBindingsSystem {
+    type SystemData = (
+        Entities<'s>,
+        Write<'s, InputHandler<StringBindings>>,
+        Read<'s, EventChannel<UiEvent>>,
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, EventChannel<GameEvent>>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiImage>,
+        WriteStorage<'s, Interactable>,
+        WriteStorage<'s, Parent>,
+        ReadExpect<'s, UiAssets>,
+        Read<'s, Locale>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut input,
+            channel,
+            input_channel,
+            mut game_events,
+            mut ui_texts,
+            mut ui_transforms,
+            mut ui_images,
+            mut ui_interactables,
+            mut parents,
+            ui_assets,
+            locale,
+        ): Self::SystemData,
+    ) {
+        let mut refresh = false;
+
+        for event in input_channel.read(&mut self.input_reader_id) {
+            if let Some(rebinding) = self.rebinding.clone() {
+                let button = match event {
+                    InputEvent::KeyPressed { key_code: VirtualKeyCode::Escape, .. } => {
+                        self.rebinding = None;
+                        self.positive = None;
+                        refresh = true;
+                        None
+                    }
+                    InputEvent::KeyPressed { key_code, .. } => Some(Button::Key(*key_code)),
+                    InputEvent::MouseButtonPressed(mouse_button) => Some(Button::Mouse(*mouse_button)),
+                    _ => None,
+                };
+
+                // An axis takes a button for each end in turn, or the mouse wheel
+                let result = match &rebinding {
+                    BindingId::Action(action) => button.map(|button| rebind(&mut input.bindings, action, button)),
+                    BindingId::Axis(axis) => match (event, button, self.positive) {
+                        (InputEvent::MouseWheelMoved(direction), _, None) => {
+                            let horizontal =
+                                *direction == ScrollDirection::ScrollLeft || *direction == ScrollDirection::ScrollRight;
+                            Some(rebind_axis(&mut input.bindings, axis, Axis::MouseWheel { horizontal }))
+                        }
+                        (_, Some(pos), None) => {
+                            self.positive = Some(pos);
+                            refresh = true;
+                            None
+                        }
+                        (_, Some(neg), Some(pos)) if neg != pos => {
+                            Some(rebind_axis(&mut input.bindings, axis, Axis::Emulated { pos, neg }))
+                        }
+                        _ => None,
+                    },
+                };
+
+                if let Some(result) = result {
+                    let name = rebinding.name().to_string();
+                    let event = match result {
+                        Ok(()) => match input.bindings.write(&self.config_path) {
+                            Ok(()) => GameEvent::KeyRebound {
+                                binding: describe_binding(&input.bindings, &rebinding),
+                                action: name,
+                            },
+                            Err(error) => GameEvent::KeyBindingsNotSaved { error: error.to_string() },
+                        },
+                        Err(error) => GameEvent::KeyNotRebound { action: name, error: error.to_string() },
+                    };
+                    game_events.single_write(event);
+                    self.rebinding = None;
+                    self.positive = None;
+                    refresh = true;
+                }
+            } else if let InputEvent::ActionPressed(action) = event {
+                if action == "key_bindings" {
+                    self.open = !self.open;
+                    refresh = true;
+                }
+            }
+        }
+
+        for event in channel.read(&mut self.reader_id) {
+            if event.event_type == UiEventType::ClickStop {
+                if let Some(binding) = self.buttons.get(&event.target) {
+                    self.rebinding = Some(binding.clone());
+                    self.positive = None;
+                    refresh = true;
+                }
+            }
+        }
+
+        if !refresh {
+            return;
+        }
+
+        for ui_element in find_ui_elements(&entities, &ui_transforms, "key_binding")
+            .into_iter()
+            .chain(find_ui_elements(&entities, &ui_transforms, "key_bindings"))
+        {
+            entities.delete(ui_element).unwrap();
+        }
+        self.buttons.clear();
+
+        if !self.open {
+            self.rebinding = None;
+            self.positive = None;
+            return;
+        }
+
+        let bindings = input
+            .bindings
+            .actions()
+            .cloned()
+            .sorted()
+            .map(BindingId::Action)
+            .chain(input.bindings.axes().cloned().sorted().map(BindingId::Axis))
+            .collect::<Vec<_>>();
+        let panel_height = 50. + 20. * bindings.len() as f32;
+        let background = find_ui_element(&entities, &ui_transforms, "background");
+
+        let mut panel = entities
+            .build_entity()
+            .with(
+                UiTransform::new(
+                    "key_bindings".to_string(),
+                    Anchor::Middle,
+                    Anchor::Middle,
+                    0.,
+                    0.,
+                    5.,
+                    300.,
+                    panel_height,
+                ),
+                &mut ui_transforms,
+            )
+            .with(
+                UiImage::NineSlice {
+                    x_start: 4,
+                    y_start: 4,
+                    width: 56,
+                    height: 56,
+                    left_dist: 4,
+                    right_dist: 4,
+                    top_dist: 4,
+                    bottom_dist: 4,
+                    tex: ui_assets.panel.clone(),
+                    texture_dimensions: [64, 64],
+                },
+                &mut ui_images,
+            );
+        if let Some(background) = background {
+            panel = panel.with(Parent { entity: background }, &mut parents);
+        }
+        let panel = panel.build();
+
+        let mut lines = vec![(None, locale.text("key_bindings", &[]))];
+        lines.extend(bindings.into_iter().map(|id| {
+            let binding = if self.rebinding.as_ref() != Some(&id) {
+                describe_binding(&input.bindings, &id)
+            } else if let BindingId::Action(_) = id {
+                locale.text("press_a_key", &[])
+            } else if self.positive.is_none() {
+                locale.text("press_axis_positive", &[])
+            } else {
+                locale.text("press_axis_negative", &[])
+            };
+            let line = locale.text("binding", &[("action", &id.name()), ("binding", &binding)]);
+            (Some(id), line)
+        }));
+
+        for (index, (id, line)) in lines.into_iter().enumerate() {
+            let mut builder = entities
+                .build_entity()
+                .with(
+                    UiText::new(
+                        ui_assets.font.clone(),
+                        line,
+                        [1.0, 1.0, 1.0, 1.0],
+                        15.,
+                        LineMode::Single,
+                        Anchor::MiddleLeft,
+                    ),
+                    &mut ui_texts,
+                )
+                .with(
+                    UiTransform::new(
+                        "key_binding".to_string(),
+                        Anchor::TopMiddle,
+                        Anchor::TopMiddle,
+                        0.,
+                        -(15. + 20. * index as f32),
+                        1.,
+                        280.,
+                        20.,
+                    ),
+                    &mut ui_transforms,
+                )
+                .with(Parent { entity: panel }, &mut parents);
+            if id.is_some() {
+                builder = builder.with(Interactable, &mut ui_interactables);
+            }
+            let line_entity = builder.build();
+
+            if let Some(id) = id {
+                self.buttons.insert(line_entity, id);
+            }
+        }
+    }
+}
+
+pub struct KeyBindingsSystemDesc {
+    config_path: PathBuf,
+}
+
+impl KeyBindingsSystemDesc {
+    pub fn new(config_path: PathBuf) -> Self {
+        KeyBindingsSystemDesc { config_path }
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, KeyBindingsSystem> for KeyBindingsSystemDesc {
+    fn build(self, world: &mut World) -> KeyBindingsSystem {
+        <KeyBindingsSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
+        let input_reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
+            .register_reader();
+
+        KeyBindingsSystem::new(reader_id, input_reader_id, self.config_path)
+    }
+}
+
+impl<'a, 'b> RunNowDesc<'a, 'b, KeyBindingsSystem> for KeyBindingsSystemDesc {
+    fn build(self, world: &mut World) -> KeyBindingsSystem {
+        <KeyBindingsSystemDesc as SystemDesc<'a, 'b, KeyBindingsSystem>>::build(self, world)
+    }
+}
+
+// Replaces every binding for the action, keeping the old ones if the button can't be used
+fn rebind(
+    bindings: &mut Bindings<StringBindings>,
+    action: &str,
+    button: Button,
+) -> Result<(), BindingError<StringBindings>> {
+    let previous = bindings
+        .action_bindings(action)
+        .map(|combo| combo.to_vec())
+        .collect::<Vec<_>>();
+    for combo in &previous {
+        bindings
+            .remove_action_binding(action, combo)
+            .expect("Removing a combo just listed for the action");
+    }
+
+    let result = bindings.insert_action_binding(action.to_string(), vec![button]);
+    if result.is_err() {
+        for combo in previous {
+            bindings.insert_action_binding(action.to_string(), combo).unwrap();
+        }
+    }
+    result
+}
+
+// Replaces the axis, keeping the old one if the new one conflicts with other bindings
+fn rebind_axis(
+    bindings: &mut Bindings<StringBindings>,
+    axis: &str,
+    new_axis: Axis,
+) -> Result<(), BindingError<StringBindings>> {
+    bindings.insert_axis(axis.to_string(), new_axis).map(|_| ())
+}
+
+fn describe_binding(bindings: &Bindings<StringBindings>, id: &BindingId) -> String {
+    match id {
+        BindingId::Action(action) => describe_bindings(bindings, action),
+        BindingId::Axis(axis) => bindings.axis(axis.as_str()).map_or(String::new(), describe_axis),
+    }
+}
+
+fn describe_axis(axis: &Axis) -> String {
+    match axis {
+        Axis::Emulated { pos, neg } => format!("{} / {}", button_name(pos), button_name(neg)),
+        Axis::MouseWheel { horizontal: false } => "Mouse wheel".to_string(),
+        Axis::MouseWheel { horizontal: true } => "Mouse wheel sideways".to_string(),
+        Axis::Multiple(axes) => axes.iter().map(describe_axis).join(" or "),
+        _ => format!("{:?}", axis),
+    }
+}
+
+fn describe_bindings(bindings: &Bindings<StringBindings>, action: &str) -> String {
+    bindings
+        .action_bindings(action)
+        .map(|combo| combo.iter().map(button_name).join(" + "))
+        .join(" or ")
+}
+
+fn button_name(button: &Button) -> String {
+    match button {
+        Button::Key(key) => format!("{:?}", key),
+        Button::Mouse(mouse_button) => format!("Mouse {:?}", mouse_button),
+        _ => format!("{:?}", button),
+    }
+}
+
+// Land is drawn on the minimap as a grid of this many cells across
+const MINIMAP_LAND_CELLS: usize = 40;
+
+pub struct MinimapSystem {
+    reader_id: ReaderId<UiEvent>,
+    land_drawn: bool,
+    // Minimap marker for each ship and port
+    markers: HashMap<Entity, Entity>,
+}
+
+impl MinimapSystem {
+    fn new(reader_id: ReaderId<UiEvent>) -> Self {
+        MinimapSystem {
+            reader_id,
+            land_drawn: false,
+            markers: HashMap::new(),
+        }
+    }
+}
+
+impl<'s> System<'s> for MinimapSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Ship>,
+        ReadStorage<'s, Port>,
+        ReadStorage<'s, Controllable>,
+        Read<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, Map>,
+        Read<'s, EventChannel<UiEvent>>,
+        Write<'s, CameraView>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiImage>,
+        WriteStorage<'s, Parent>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            cameras,
+            mut locals,
+            ships,
+            ports,
+            controllables,
+            input,
+            screen_dimensions,
+            map,
+            channel,
+            mut camera_view,
+            mut ui_transforms,
+            mut ui_images,
+            mut parents,
+        ): Self::SystemData,
+    ) {
+        let (bounds, minimap) = match (sea_bounds(&map), find_ui_element(&entities, &ui_transforms, "minimap")) {
+            (Some(bounds), Some(minimap)) => (bounds, minimap),
+            _ => return,
+        };
+        let (width, height) = {
+            let minimap_transform = ui_transforms.get(minimap).unwrap();
+            (minimap_transform.width, minimap_transform.height)
+        };
+
+        // Clicking the minimap moves the camera there
+        for event in channel.read(&mut self.reader_id) {
+            if event.event_type != UiEventType::ClickStop || event.target != minimap {
+                continue;
+            }
+
+            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                let minimap_transform = ui_transforms.get(minimap).unwrap();
+                // Ui positions are measured from the bottom of the screen, the mouse from the top
+                let point = minimap_to_world(
+                    (
+                        mouse_x - (minimap_transform.pixel_x() - minimap_transform.pixel_width() / 2.),
+                        screen_dimensions.height() - mouse_y
+                            - (minimap_transform.pixel_y() - minimap_transform.pixel_height() / 2.),
+                    ),
+                    (minimap_transform.pixel_width(), minimap_transform.pixel_height()),
+                    bounds,
+                );
+
+                for (_, local) in (&cameras, &mut locals).join() {
+                    local.set_translation_x(point.x);
+                    local.set_translation_y(point.y);
+                }
+                camera_view.following = false;
+            }
+        }
+
+        if !self.land_drawn {
+            let (left, right, top, bottom) = bounds;
+            let cell_size = (right - left) / MINIMAP_LAND_CELLS as f32;
+            let rows = ((top - bottom) / cell_size).ceil() as usize;
+            let (cell_width, cell_height) = (width / MINIMAP_LAND_CELLS as f32, height * cell_size / (top - bottom));
+
+            for column in 0..MINIMAP_LAND_CELLS {
+                for row in 0..rows {
+                    let centre = Point2::new(
+                        left + (column as f32 + 0.5) * cell_size,
+                        bottom + (row as f32 + 0.5) * cell_size,
+                    );
+                    if !map.on_land(centre) {
+                        continue;
+                    }
+
+                    let (x, y) = world_to_minimap(centre, (width, height), bounds);
+                    entities
+                        .build_entity()
+                        .with(
+                            UiTransform::new(
+                                "minimap_land".to_string(),
+                                Anchor::BottomLeft,
+                                Anchor::Middle,
+                                x,
+                                y,
+                                1.,
+                                cell_width,
+                                cell_height,
+                            )
+                            .into_transparent(),
+                            &mut ui_transforms,
+                        )
+                        .with(UiImage::SolidColor([0.2, 0.5, 0.2, 1.0]), &mut ui_images)
+                        .with(Parent { entity: minimap }, &mut parents)
+                        .build();
+                }
+            }
+            self.land_drawn = true;
+        }
+
+        self.markers.retain(|e, marker| {
+            let alive = entities.is_alive(*e);
+            if !alive {
+                entities.delete(*marker).unwrap();
+            }
+            alive
+        });
+
+        let camera_view_size = (WORLD_WIDTH * camera_view.zoom, WORLD_HEIGHT * camera_view.zoom);
+        let mut positions = (&entities, &locals)
+            .join()
+            .filter(|(e, _)| ports.get(*e).is_some() || ships.get(*e).is_some())
+            .map(|(e, local)| {
+                let (size, color) = if ports.get(e).is_some() {
+                    (6., [1.0, 0.8, 0.2, 1.0])
+                } else if controllables.get(e).is_some() {
+                    (4., [0.2, 1.0, 0.2, 1.0])
+                } else {
+                    (4., [1.0, 0.2, 0.2, 1.0])
+                };
+                (e, Point2::new(local.translation().x, local.translation().y), (size, size), color)
+            })
+            .collect::<Vec<_>>();
+        // The camera is shown as a faint rectangle over everything else
+        positions.extend((&entities, &cameras, &locals).join().map(|(e, _, local)| {
+            (
+                e,
+                Point2::new(local.translation().x, local.translation().y),
+                (
+                    camera_view_size.0 * width / (bounds.1 - bounds.0),
+                    camera_view_size.1 * height / (bounds.2 - bounds.3),
+                ),
+                [1.0, 1.0, 1.0, 0.2],
+            )
+        }));
+
+        for (e, location, (marker_width, marker_height), color) in positions {
+            let (x, y) = world_to_minimap(location, (width, height), bounds);
+            let marker = self.markers.get(&e).cloned().filter(|marker| ui_transforms.get(*marker).is_some());
+
+            if let Some(marker) = marker {
+                let marker_transform = ui_transforms.get_mut(marker).unwrap();
+                marker_transform.local_x = x;
+                marker_transform.local_y = y;
+                marker_transform.width = marker_width;
+                marker_transform.height = marker_height;
+            } else {
+                let marker = entities
+                    .build_entity()
+                    .with(
+                        UiTransform::new(
+                            "minimap_marker".to_string(),
+                            Anchor::BottomLeft,
+                            Anchor::Middle,
+                            x,
+                            y,
+                            2.,
+                            marker_width,
+                            marker_height,
+                        )
+                        .into_transparent(),
+                        &mut ui_transforms,
+                    )
+                    .with(UiImage::SolidColor(color), &mut ui_images)
+                    .with(Parent { entity: minimap }, &mut parents)
+                    .build();
+                self.markers.insert(e, marker);
+            }
+        }
+    }
+}
+
+pub struct MinimapSystemDesc;
+
+impl Default for MinimapSystemDesc {
+    fn default() -> Self {
+        MinimapSystemDesc {}
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, MinimapSystem> for MinimapSystemDesc {
+    fn build(self, world: &mut World) -> MinimapSystem {
+        <MinimapSystem as System<'_>>::SystemData::setup(world);
+
+        let reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
+
+        MinimapSystem::new(reader_id)
+    }
+}
+
+impl<'a, 'b> RunNowDesc<'a, 'b, MinimapSystem> for MinimapSystemDesc {
+    fn build(self, world: &mut World) -> MinimapSystem {
+        <MinimapSystemDesc as SystemDesc<'a, 'b, MinimapSystem>>::build(self, world)
+    }
+}
+
+// Position on a minimap of the given size, measured from its bottom left corner
+fn world_to_minimap(
+    point: Point2<f32>,
+    (width, height): (f32, f32),
+    (left, right, top, bottom): (f32, f32, f32, f32),
+) -> (f32, f32) {
+    (
+        width * (point.x - left) / (right - left),
+        height * (point.y - bottom) / (top - bottom),
+    )
+}
+
+fn minimap_to_world(
+    (x, y): (f32, f32),
+    (width, height): (f32, f32),
+    (left, right, top, bottom): (f32, f32, f32, f32),
+) -> Point2<f32> {
+    Point2::new(
+        left + (right - left) * x / width,
+        bottom + (top - bottom) * y / height,
+    )
+}
+
+#[derive(Default)]
+pub struct NotificationSystem {
+    time_passed: Option<f32>,
+}
+
+impl<'s> System<'s> for NotificationSystem {
+    type SystemData = (
+        WriteStorage<'s, UiText>,
+        Write<'s, Notifications>, 
+        Write<'s, NotificationLog>,
+        Read<'s, Time>,
+        Read<'s, Date>,
+        UiFinder<'s>, 
+    );
+
+    fn run(&mut self, (mut ui_texts, mut notifications, mut log, time, date, finder): Self::SystemData) {
+        let mut replace_message = true;
+        let mut new_notification = None;
+        if let Some(t) = self.time_passed {
+             let new_t = t + time.delta_real_seconds();
+             if new_t > NOTIFICATION_TIME {
+                 new_notification = notifications.pop_front();
+                 self.time_passed = None;
+             } else if notifications.len() > 0 {
+                new_notification = notifications.pop_front();
+             } else {
+                self.time_passed.replace(new_t);
+                replace_message = false;
+             }
+         } else {
+            new_notification = notifications.pop_front();
+        }
+
+        if replace_message {
+            let (new_message, color) = match new_notification {
+                Some(notification) => {
+                    self.time_passed.replace(0.0);
+                    let shown = (notification.message.clone(), severity_color(notification.severity));
+                    log.entries.push(LoggedNotification { notification, date: date.current_date_time() });
+                    log.shown = Some(log.entries.len() - 1);
+                    shown
+                }
+                None => {
+                    log.shown = None;
+                    ("".to_string(), severity_color(Severity::Info))
+                }
+            };
+
+            if let Some(notification) = finder.find("notification") {
+                if let Some(ui_text) = ui_texts.get_mut(notification) {
+                    ui_text.text = new_message;
+                    ui_text.color = color;
+                }
+            }
+        }
+    }
+}
 
-impl Default for ShipPanelSystemDesc {
-    fn default() -> Self {
-        ShipPanelSystemDesc {}
+fn severity_color(severity: Severity) -> [f32; 4] {
+    match severity {
+        Severity::Info => [1.0, 1.0, 1.0, 1.0],
+        Severity::Warning => [1.0, 0.85, 0.4, 1.0],
+        Severity::Critical => [1.0, 0.4, 0.4, 1.0],
     }
 }
 
-impl<'a, 'b> SystemDesc<'a, 'b, ShipPanelSystem> for ShipPanelSystemDesc {
-    fn build(self, world: &mut World) -> ShipPanelSystem {
-        <ShipPanelSystem as System<'_>>::SystemData::setup(world);
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LogControl {
+    Filter,
+    Newer,
+    Older,
+}
 
-        let reader_id = world
-            .fetch_mut::<EventChannel<UiUpdateEvent>>()
-            .register_reader();
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LogCard {
+    Empty,
+    Entry(usize),
+}
 
-        ShipPanelSystem::new(reader_id)
+// Cycles through every category, then back to showing all of them
+fn next_filter(filter: Option<NotificationCategory>) -> Option<NotificationCategory> {
+    match filter {
+        None => Some(NOTIFICATION_CATEGORIES[0]),
+        Some(category) => NOTIFICATION_CATEGORIES
+            .iter()
+            .skip_while(|c| **c != category)
+            .nth(1)
+            .cloned(),
     }
 }
 
-impl<'a, 'b> RunNowDesc<'a, 'b, ShipPanelSystem> for ShipPanelSystemDesc {
-    fn build(self, world: &mut World) -> ShipPanelSystem {
-        <ShipPanelSystemDesc as SystemDesc<'a, 'b, ShipPanelSystem>>::build(self, world)
-    }
+// Indices of the entries to list, newest first
+fn filtered_entries(log: &NotificationLog, filter: Option<NotificationCategory>) -> Vec<usize> {
+    log.entries
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, entry)| filter.map_or(true, |category| entry.notification.category == category))
+        .map(|(index, _)| index)
+        .collect()
 }
 
-pub struct PlayerStatusSystem {
-    reader_id: ReaderId<UiUpdateEvent>,
+pub struct NotificationLogSystem {
+    reader_id: ReaderId<UiEvent>,
+    input_reader_id: ReaderId<InputEvent<StringBindings>>,
+    open: bool,
+    filter: Option<NotificationCategory>,
+    scroll: usize,
+    // Length of the log when the panel was last refreshed
+    logged: usize,
+    panel: Option<Entity>,
+    controls: HashMap<Entity, LogControl>,
+    cards: CardList<LogCard>,
 }
 
-impl PlayerStatusSystem {
-    fn new(reader_id: ReaderId<UiUpdateEvent>) -> Self {
-        PlayerStatusSystem { reader_id }
+impl NotificationLogSystem {
+    fn new(reader_id: ReaderId<UiEvent>, input_reader_id: ReaderId<InputEvent<StringBindings>>) -> Self {
+        NotificationLogSystem {
+            reader_id,
+            input_reader_id,
+            open: false,
+            filter: None,
+            scroll: 0,
+            logged: 0,
+            panel: None,
+            controls: HashMap::new(),
+            cards: CardList::with_width("notification_log_entry", LOG_WIDTH - 20.),
+        }
     }
 }
 
-impl<'s> System<'s> for PlayerStatusSystem {
+impl<'s> System<'s> for NotificationLogSystem {
     type SystemData = (
-        WriteStorage<'s, UiText>,
-        Read<'s, PlayerStatus>,
-        Read<'s, EventChannel<UiUpdateEvent>>,
-        UiFinder<'s>,
+        Read<'s, EventChannel<UiEvent>>,
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, EventChannel<UiUpdateEvent>>,
+        Read<'s, NotificationLog>,
+        Write<'s, CameraView>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Camera>,
+        WriteStorage<'s, Selected>,
+        Read<'s, Locale>,
+        WidgetStorages<'s>,
     );
 
-    fn run(&mut self, (mut ui_texts, player_status, channel, finder): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            channel,
+            input_channel,
+            mut update_channel,
+            log,
+            mut camera_view,
+            mut locals,
+            cameras,
+            mut selecteds,
+            locale,
+            mut widgets,
+        ): Self::SystemData,
+    ) {
+        let mut refresh = self.open && log.entries.len() != self.logged;
+
+        for event in input_channel.read(&mut self.input_reader_id) {
+            if let InputEvent::ActionPressed(action) = event {
+                if action == "notification_log" {
+                    self.open = !self.open;
+                    self.scroll = 0;
+                    refresh = true;
+                }
+            }
+        }
+
+        let toast = find_ui_element(&widgets.entities, &widgets.ui_transforms, "notification");
+        let entries = filtered_entries(&log, self.filter);
+        let mut focus = None;
+
         for event in channel.read(&mut self.reader_id) {
-            if let UiUpdateEvent::PlayerStatus = event {
-                let player_money = finder.find("player_money").unwrap();
-                if let Some(ui_text) = ui_texts.get_mut(player_money) {
-                    ui_text.text = format!("£{}", player_status.money);
+            if event.event_type != UiEventType::ClickStop {
+                continue;
+            }
+
+            if Some(event.target) == toast {
+                focus = log.shown.and_then(|index| log.entries[index].notification.entity);
+                continue;
+            }
+
+            if let Some(LogCard::Entry(index)) = self.cards.card_at(event.target) {
+                focus = log.entries[index].notification.entity;
+                continue;
+            }
+
+            match self.controls.get(&event.target) {
+                Some(LogControl::Filter) => {
+                    self.filter = next_filter(self.filter);
+                    self.scroll = 0;
+                }
+                Some(LogControl::Newer) => self.scroll = self.scroll.saturating_sub(LOG_LINES),
+                Some(LogControl::Older) => {
+                    if self.scroll + LOG_LINES < entries.len() {
+                        self.scroll += LOG_LINES;
+                    }
+                }
+                None => continue,
+            }
+            refresh = true;
+        }
+
+        // The ship or port may be gone by the time its notification is clicked
+        if let Some(e) = focus.filter(|e| widgets.entities.is_alive(*e)) {
+            if let Some(position) = locals.get(e).map(|local| *local.translation()) {
+                for (_, local) in (&cameras, &mut locals).join() {
+                    local.set_translation_x(position.x);
+                    local.set_translation_y(position.y);
+                }
+                camera_view.following = false;
+
+                deselect_all(&widgets.entities, &mut selecteds, &mut update_channel);
+                selecteds.insert(e, Selected::default()).unwrap();
+            }
+        }
+
+        if !refresh {
+            return;
+        }
+        self.logged = log.entries.len();
+
+        if !self.open {
+            if let Some(panel) = self.panel.take() {
+                self.cards.update(panel, LOG_LIST_TOP, Vec::new(), &mut widgets);
+                for (control, _) in self.controls.drain() {
+                    widgets.entities.delete(control).unwrap();
                 }
+                widgets.entities.delete(panel).unwrap();
+            }
+            return;
+        }
+
+        let panel = match self.panel {
+            Some(panel) => panel,
+            None => {
+                let panel = build_log_panel(&mut self.controls, &locale, &mut widgets);
+                self.panel = Some(panel);
+                panel
             }
+        };
+
+        let filter_key = self.filter.map_or("filter_all".to_string(), |c| format!("category_{:?}", c).to_lowercase());
+        let filter_text = locale.text("show", &[("filter", &locale.text(&filter_key, &[]))]);
+        for (control, _) in self.controls.iter().filter(|(_, control)| **control == LogControl::Filter) {
+            if let Some(ui_text) = widgets.ui_texts.get_mut(*control) {
+                ui_text.text = filter_text.clone();
+            }
+        }
+
+        let entries = filtered_entries(&log, self.filter);
+        let mut cards = Vec::new();
+        if entries.is_empty() {
+            cards.push((LogCard::Empty, Card::plain(vec![Widget::Label(locale.text("log_empty", &[]))])));
         }
+        cards.extend(entries.iter().skip(self.scroll).take(LOG_LINES).map(|index| {
+            let entry = &log.entries[*index];
+            let text = locale.text(
+                "log_entry",
+                &[("date", &locale.date_time(entry.date)), ("message", &entry.notification.message)],
+            );
+            let line = Widget::Line { text, color: severity_color(entry.notification.severity) };
+            (LogCard::Entry(*index), Card::plain(vec![line]))
+        }));
+
+        self.cards.update(panel, LOG_LIST_TOP, cards, &mut widgets);
     }
 }
 
-pub struct PlayerStatusSystemDesc;
+// The log's panel, with the filter and paging controls along its top
+fn build_log_panel(controls: &mut HashMap<Entity, LogControl>, locale: &Locale, widgets: &mut WidgetStorages) -> Entity {
+    let background = find_ui_element(&widgets.entities, &widgets.ui_transforms, "background");
+    let entry_height = Card::plain(vec![Widget::Label("".to_string())]).height() + CARD_SPACING;
+
+    let mut panel = widgets
+        .entities
+        .build_entity()
+        .with(
+            UiTransform::new(
+                "notification_log".to_string(),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                0.,
+                5.,
+                LOG_WIDTH,
+                LOG_LIST_TOP + entry_height * LOG_LINES as f32,
+            ),
+            &mut widgets.ui_transforms,
+        )
+        .with(
+            UiImage::NineSlice {
+                x_start: 4,
+                y_start: 4,
+                width: 56,
+                height: 56,
+                left_dist: 4,
+                right_dist: 4,
+                top_dist: 4,
+                bottom_dist: 4,
+                tex: widgets.ui_assets.panel.clone(),
+                texture_dimensions: [64, 64],
+            },
+            &mut widgets.ui_images,
+        );
+    if let Some(background) = background {
+        panel = panel.with(Parent { entity: background }, &mut widgets.parents);
+    }
+    let panel = panel.build();
+
+    // The filter's text is set whenever the log is refreshed
+    let lines = vec![
+        (LogControl::Filter, "".to_string(), Anchor::TopLeft, 10., 200.),
+        (LogControl::Newer, locale.text("log_newer", &[]), Anchor::TopRight, -80., 60.),
+        (LogControl::Older, locale.text("log_older", &[]), Anchor::TopRight, -10., 60.),
+    ];
+    for (control, text, anchor, x, width) in lines {
+        let line_entity = widgets
+            .entities
+            .build_entity()
+            .with(
+                UiText::new(
+                    widgets.ui_assets.font.clone(),
+                    text,
+                    severity_color(Severity::Info),
+                    15.,
+                    LineMode::Single,
+                    Anchor::MiddleLeft,
+                ),
+                &mut widgets.ui_texts,
+            )
+            .with(
+                UiTransform::new("notification_log_line".to_string(), anchor, anchor, x, -10., 1., width, 20.),
+                &mut widgets.ui_transforms,
+            )
+            .with(Parent { entity: panel }, &mut widgets.parents)
+            .with(Interactable, &mut widgets.ui_interactables)
+            .build();
+        controls.insert(line_entity, control);
+    }
 
-impl Default for PlayerStatusSystemDesc {
+    panel
+}
+
+pub struct NotificationLogSystemDesc;
+
+impl Default for NotificationLogSystemDesc {
     fn default() -> Self {
-        PlayerStatusSystemDesc {}
+        NotificationLogSystemDesc {}
     }
 }
 
-impl<'a, 'b> SystemDesc<'a, 'b, PlayerStatusSystem> for PlayerStatusSystemDesc {
-    fn build(self, world: &mut World) -> PlayerStatusSystem {
-        <PlayerStatusSystem as System<'_>>::SystemData::setup(world);
+impl<'a, 'b> SystemDesc<'a, 'b, NotificationLogSystem> for NotificationLogSystemDesc {
+    fn build(self, world: &mut World) -> NotificationLogSystem {
+        <NotificationLogSystem as System<'_>>::SystemData::setup(world);
 
-        let reader_id = world
-            .fetch_mut::<EventChannel<UiUpdateEvent>>()
+        let reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
+        let input_reader_id = world
+            .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
             .register_reader();
 
-        PlayerStatusSystem::new(reader_id)
+        NotificationLogSystem::new(reader_id, input_reader_id)
     }
 }
 
-pub struct GameSpeedSystem {
-    reader_id: ReaderId<UiEvent>,
-}
-
-impl GameSpeedSystem {
-    fn new(reader_id: ReaderId<UiEvent>) -> Self {
-        GameSpeedSystem { reader_id }
+impl<'a, 'b> RunNowDesc<'a, 'b, NotificationLogSystem> for NotificationLogSystemDesc {
+    fn build(self, world: &mut World) -> NotificationLogSystem {
+        <NotificationLogSystemDesc as SystemDesc<'a, 'b, NotificationLogSystem>>::build(self, world)
     }
 }
 
-impl<'s> System<'s> for GameSpeedSystem {
-    type SystemData = (
-        Entities<'s>,
-        WriteStorage<'s, UiTransform>,
-        Read<'s, EventChannel<UiEvent>>,
-        Write<'s, Date>,
-    );
-
-    fn run(&mut self, (entities, mut ui_transforms, channel, mut date): Self::SystemData) {
-        for event in channel.read(&mut self.reader_id) {
-            if event.event_type == UiEventType::ClickStop {
-                let button = ui_transforms
-                    .get(event.target)
-                    .map_or(None, |t| Some(t.id.clone()));
+// Lines describing what a contract asks for, shown on contract cards
+fn contract_description<'a>(contract: &Contract, names: &ReadStorage<'a, Named>, locale: &Locale) -> Vec<String> {
+    let name_of = |e: Entity| names.get(e).map_or("???".to_string(), |name| name.name.to_string());
+    let goods_lines = |goods: &HashMap<ItemType, u32>| {
+        goods
+            .iter()
+            .sorted_by_key(|(&item, _)| item)
+            .map(|(item, amount)| {
+                locale.text("goods_line", &[("item", &locale.item(*item)), ("quantity", &locale.quantity(*item, *amount))])
+            })
+            .collect::<Vec<_>>()
+    };
 
-                if let Some(button_id) = button {
-                    match button_id.as_ref() {
-                        "play_button" => {
-                            date.paused = false;
-
-                            let mut pause_button = ui_transforms
-                                .get_mut(
-                                    find_ui_element(&entities, &ui_transforms, "pause_button")
-                                        .unwrap(),
-                                )
-                                .unwrap();
-                            pause_button.local_z = 1.;
-
-                            let mut play_button = ui_transforms.get_mut(event.target).unwrap();
-                            play_button.local_z = -1.
-                        }
-                        "pause_button" => {
-                            date.paused = true;
-                            let mut play_button = ui_transforms
-                                .get_mut(
-                                    find_ui_element(&entities, &ui_transforms, "play_button")
-                                        .unwrap(),
-                                )
-                                .unwrap();
-                            play_button.local_z = 1.;
-
-                            let mut pause_button = ui_transforms.get_mut(event.target).unwrap();
-                            pause_button.local_z = -1.
-                        }
-                        "increase_speed_button" => {
-                            let new_speed = 2. * date.current_speed;
-                            let max = 8.;
-                            date.current_speed = if new_speed <= max { new_speed } else { max };
-                        }
-                        "decrease_speed_button" => {
-                            let new_speed = 0.5 * date.current_speed;
-                            let min = 1.;
-                            date.current_speed = if new_speed >= min { new_speed } else { min };
-                        }
-                        _ => (),
-                    };
+    let mut lines = match &contract.kind {
+        ContractKind::Delivery => goods_lines(&contract.remaining_goods()),
+        ContractKind::Passengers => {
+            let mut lines = vec![locale.text("passenger_transport", &[])];
+            lines.extend(goods_lines(&contract.remaining_goods()));
+            lines
+        }
+        ContractKind::Mail => {
+            let mut lines = vec![locale.text("mail_run", &[])];
+            lines.extend(goods_lines(&contract.remaining_goods()));
+            lines
+        }
+        ContractKind::Escort { escortee } => vec![locale.text("escort_description", &[("escortee", &name_of(*escortee))])],
+        ContractKind::Smuggling { inspection_chance } => {
+            let risk = format!("{:.0}", inspection_chance * 100.0);
+            let mut lines = vec![locale.text("smuggling_description", &[("risk", &risk)])];
+            lines.extend(goods_lines(&contract.goods_required));
+            lines
+        }
+        ContractKind::Bulk { drops } => {
+            let mut lines = vec![locale.plural("bulk_description", drops.len() as u32, &[])];
+            for drop_off in drops {
+                if drop_off.delivered {
+                    lines.push(locale.text("drop_delivered", &[("port", &name_of(drop_off.destination))]));
+                } else {
+                    lines.push(locale.text("drop_destination", &[("port", &name_of(drop_off.destination))]));
+                    lines.extend(goods_lines(&drop_off.goods));
                 }
             }
+            lines
         }
+    };
+
+    if contract.deposit > 0 {
+        lines.push(locale.text("deposit", &[("deposit", &locale.money(contract.deposit))]));
+    }
+    if contract.penalty > 0 {
+        lines.push(locale.text("penalty", &[("penalty", &locale.money(contract.penalty))]));
+    }
+    if let Some(due_date) = contract.due_date {
+        lines.push(locale.text("due", &[("date", &locale.date(due_date))]));
     }
+
+    lines
 }
 
-pub struct GameSpeedSystemDesc;
+fn loading_status<'a>(
+    e: Entity,
+    contract: &Contract,
+    names: &ReadStorage<'a, Named>,
+    cargo_transfers: &ReadStorage<'a, CargoTransfer>,
+    locale: &Locale,
+) -> String {
+    if let ContractKind::Escort { .. } = contract.kind {
+        return locale.text("status_escorting", &[]);
+    }
 
-impl Default for GameSpeedSystemDesc {
-    fn default() -> Self {
-        GameSpeedSystemDesc {}
+    let transfer = contract
+        .carrier
+        .and_then(|carrier| cargo_transfers.get(carrier))
+        .filter(|transfer| transfer.contracts.contains(&e));
+
+    match transfer {
+        Some(transfer) if transfer.unloading => locale.text("status_unloading", &[]),
+        Some(_) => locale.text("status_loading", &[]),
+        None if !contract.reserved.is_empty() => {
+            let origin_name = contract
+                .origin
+                .and_then(|origin| names.get(origin))
+                .map_or("???".to_string(), |name| name.name.to_string());
+            locale.text("status_waiting", &[("port", &origin_name)])
+        }
+        None if !contract.delivered.is_empty() => locale.text("status_part_delivered", &[]),
+        None => locale.text("status_loaded", &[]),
     }
 }
 
-impl<'a, 'b> SystemDesc<'a, 'b, GameSpeedSystem> for GameSpeedSystemDesc {
-    fn build(self, world: &mut World) -> GameSpeedSystem {
-        <GameSpeedSystem as System<'_>>::SystemData::setup(world);
-
-        let reader_id = world.fetch_mut::<EventChannel<UiEvent>>().register_reader();
-
-        GameSpeedSystem::new(reader_id)
+fn expiry_countdown(
+    expiration_date: chrono::Date<chrono::Utc>,
+    current_date: chrono::Date<chrono::Utc>,
+    locale: &Locale,
+) -> String {
+    match (expiration_date - current_date).num_days() {
+        days if days <= 0 => locale.text("expires_today", &[]),
+        days => locale.plural("expires_in", days as u32, &[]),
     }
 }
 
-
-#[derive(Default)]
-pub struct NotificationSystem {
-    time_passed: Option<f32>,
+// Outcome on the first line, what was carried and when it ended on the second
+fn history_description(record: &ContractRecord, locale: &Locale) -> Vec<String> {
+    let key = if record.completed { "history_completed" } else { "history_failed" };
+    vec![
+        locale.text(key, &[("destination", &record.destination), ("earnings", &locale.money(record.earnings))]),
+        locale.text("history_details", &[("goods", &record.goods), ("date", &locale.date(record.date))]),
+    ]
 }
 
-impl<'s> System<'s> for NotificationSystem {
-    type SystemData = (
-        WriteStorage<'s, UiText>,
-        Write<'s, Notifications>, 
-        Read<'s, Time>,
-        UiFinder<'s>, 
-    );
+fn crew_count(crew: &Crew, locale: &Locale) -> String {
+    locale.text("crew_count", &[("sailors", &crew.sailors), ("berths", &crew.berths)])
+}
 
-    fn run(&mut self, (mut ui_texts, mut notifications, time, finder): Self::SystemData) {
-        let mut replace_message = true;
-        let mut new_message = "".to_string();
-        if let Some(t) = self.time_passed {
-             let new_t = t + time.delta_real_seconds();
-             if new_t > NOTIFICATION_TIME {
-                 new_message = notifications.pop_front().unwrap_or("".to_string());
-                 self.time_passed = None;
-             } else if notifications.len() > 0 {
-                new_message = notifications.pop_front().unwrap();
-             } else {
-                self.time_passed.replace(new_t);
-                replace_message = false;
-             }
-         } else {
-            new_message = notifications.pop_front().unwrap_or("".to_string());
-        }
+fn crew_morale(crew: &Crew, locale: &Locale) -> String {
+    locale.plural("crew_morale", crew.days_of_provisions(), &[("morale", &(crew.morale.round() as u32))])
+}
 
-        if replace_message {
-            if new_message != "".to_string() {
-                self.time_passed.replace(0.0);
-            }
+// Name, affiliation and status lines for more than one selected ship
+fn selection_summary(
+    ships: usize,
+    mut affiliations: Vec<&str>,
+    docked: usize,
+    locale: &Locale,
+) -> (String, String, String) {
+    affiliations.sort();
+    affiliations.dedup();
+    (
+        locale.plural("ships_selected", ships as u32, &[]),
+        locale.list(&affiliations),
+        locale.text("docked_and_at_sea", &[("docked", &docked), ("at_sea", &(ships - docked))]),
+    )
+}
 
-            if let Some(notification) = finder.find("notification") {
-                if let Some(ui_text) = ui_texts.get_mut(notification) {
-                    ui_text.text = new_message;
-                }
-            }
+fn total_cargo(cargos: Vec<&Cargo>) -> BTreeMap<ItemType, u32> {
+    let mut total = BTreeMap::new();
+    for (item, amount) in cargos.into_iter().flat_map(|cargo| cargo.items.iter()) {
+        if *amount > 0 {
+            *total.entry(*item).or_insert(0) += amount;
         }
     }
+    total
 }
 
 fn find_ui_element<'a>(
@@ -1128,14 +2396,17 @@ fn find_ui_elements<'a>(
 mod tests {
     use super::*;
     use amethyst::{
-        input::StringBindings,
+        input::{Axis, StringBindings},
         prelude::*,
+        winit::MouseButton,
         ecs::Entity,
         assets::{Loader, AssetStorage},
         ui::{Anchor, UiTransform, FontAsset, TtfFormat},
         Result,
     };
     use amethyst_test::prelude::*;
+    use chrono::{TimeZone, Utc};
+    use crate::age_of_sail::Notification;
     use test_case::test_case;
 
 
@@ -1441,7 +2712,7 @@ mod tests {
                 world.insert(EffectReturn(notification));
 
                 let mut notifications = world.write_resource::<Notifications>();
-                notifications.push_back("notification in queue".to_string());
+                notifications.push_back(Notification::new(NotificationCategory::General, "notification in queue".to_string()));
             })
             .with_assertion(move |world| {
                 let notification = world.read_resource::<EffectReturn<Entity>>().0.clone();
@@ -1490,8 +2761,8 @@ mod tests {
                 world.insert(EffectReturn(notification));
                 
                 let mut notifications = world.write_resource::<Notifications>();
-                notifications.push_back("notification in front of queue".to_string());
-                notifications.push_back("notification in back of queue".to_string());
+                notifications.push_back(Notification::new(NotificationCategory::General, "notification in front of queue".to_string()));
+                notifications.push_back(Notification::new(NotificationCategory::General, "notification in back of queue".to_string()));
             })
             .with_assertion(move |world| {
                 let notification = world.read_resource::<EffectReturn<Entity>>().0.clone();
@@ -1499,6 +2770,10 @@ mod tests {
 
                 let ui_text = ui_texts.get(notification).unwrap();
                 assert_eq!("notification in front of queue".to_string(), ui_text.text, "Notification");
+
+                let log = world.read_resource::<NotificationLog>();
+                assert_eq!(1, log.entries.len(), "Logged notifications");
+                assert_eq!(Some(0), log.shown, "Shown notification");
             })
             .run()
             .unwrap();
@@ -1540,8 +2815,8 @@ mod tests {
                 world.insert(EffectReturn(notification));
                 
                 let mut notifications = world.write_resource::<Notifications>();
-                notifications.push_back("notification in front of queue".to_string());
-                notifications.push_back("notification in back of queue".to_string());
+                notifications.push_back(Notification::new(NotificationCategory::General, "notification in front of queue".to_string()));
+                notifications.push_back(Notification::new(NotificationCategory::General, "notification in back of queue".to_string()));
             })
             .with_assertion(move |world| {
                 let notification = world.read_resource::<EffectReturn<Entity>>().0.clone();
@@ -1553,4 +2828,282 @@ mod tests {
             .run()
             .unwrap();
     }
+
+    #[test]
+    fn selection_summary_counts_docked_ships_and_lists_each_affiliation_once() {
+        let (name, affiliation, status) =
+            selection_summary(3, vec!["Royal Navy", "East India Company", "Royal Navy"], 1, &Locale::default());
+
+        assert_eq!("3 ships selected", name);
+        assert_eq!("East India Company, Royal Navy", affiliation);
+        assert_eq!("1 docked, 2 at sea", status);
+    }
+
+    #[test]
+    fn history_description_lists_goods_and_date() {
+        let record = ContractRecord {
+            destination: "Bristol".to_string(),
+            goods: "10 tons of Sugar".to_string(),
+            completed: false,
+            earnings: -250,
+            date: Utc.ymd(1805, 3, 14),
+        };
+
+        assert_eq!(
+            vec!["Bristol: failed -£250".to_string(), "10 tons of Sugar, 14 March 1805".to_string()],
+            history_description(&record, &Locale::default())
+        );
+    }
+
+    #[test]
+    fn total_cargo_sums_goods_across_ships() {
+        let mut cargo = Cargo::default();
+        cargo.items.insert(ItemType::Rum, 10);
+        cargo.items.insert(ItemType::Sugar, 0);
+        let mut another_cargo = Cargo::default();
+        another_cargo.items.insert(ItemType::Rum, 5);
+        another_cargo.items.insert(ItemType::Whiskey, 20);
+
+        let total = total_cargo(vec![&cargo, &another_cargo]);
+
+        assert_eq!(
+            vec![(ItemType::Rum, 15), (ItemType::Whiskey, 20)],
+            total.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn pause_action_toggles_pause_and_swaps_buttons() {
+        AmethystApplication::ui_base::<StringBindings>()
+            .with_system_desc(GameSpeedSystemDesc, "game_speed", &[])
+            .with_effect(|world| {
+                let pause_button = world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        "pause_button".to_string(),
+                        Anchor::TopLeft,
+                        Anchor::TopLeft,
+                        0.0,
+                        0.0,
+                        0.0,
+                        0.0,
+                        0.0,
+                    ))
+                    .build();
+
+                world.insert(EffectReturn(pause_button));
+
+                world
+                    .fetch_mut::<EventChannel<InputEvent<StringBindings>>>()
+                    .single_write(InputEvent::ActionPressed("pause".to_string()));
+            })
+            .with_assertion(|world| {
+                let pause_button = world.read_resource::<EffectReturn<Entity>>().0.clone();
+
+                assert!(world.read_resource::<Date>().paused);
+                assert_eq!(-1.0, world.read_storage::<UiTransform>().get(pause_button).unwrap().local_z);
+            })
+            .run()
+            .unwrap();
+    }
+
+    #[test]
+    fn rebinding_an_action_replaces_its_buttons() {
+        let mut bindings = Bindings::<StringBindings>::new();
+        bindings
+            .insert_action_binding("append".to_string(), vec![Button::Key(VirtualKeyCode::LShift)])
+            .unwrap();
+        bindings
+            .insert_action_binding("append".to_string(), vec![Button::Key(VirtualKeyCode::RShift)])
+            .unwrap();
+
+        rebind(&mut bindings, "append", Button::Key(VirtualKeyCode::A)).unwrap();
+
+        assert_eq!("A", describe_bindings(&bindings, "append"));
+    }
+
+    #[test]
+    fn rebinding_to_a_button_used_by_an_axis_keeps_the_old_binding() {
+        let mut bindings = Bindings::<StringBindings>::new();
+        bindings
+            .insert_axis(
+                "pan_horizontal".to_string(),
+                Axis::Emulated {
+                    pos: Button::Key(VirtualKeyCode::Right),
+                    neg: Button::Key(VirtualKeyCode::Left),
+                },
+            )
+            .unwrap();
+        bindings
+            .insert_action_binding("order".to_string(), vec![Button::Mouse(MouseButton::Right)])
+            .unwrap();
+
+        assert!(rebind(&mut bindings, "order", Button::Key(VirtualKeyCode::Left)).is_err());
+        assert_eq!("Mouse Right", describe_bindings(&bindings, "order"));
+    }
+
+    #[test]
+    fn rebinding_an_axis_replaces_it() {
+        let mut bindings = Bindings::<StringBindings>::new();
+        bindings.insert_axis("zoom".to_string(), Axis::MouseWheel { horizontal: false }).unwrap();
+
+        rebind_axis(
+            &mut bindings,
+            "zoom",
+            Axis::Emulated {
+                pos: Button::Key(VirtualKeyCode::PageUp),
+                neg: Button::Key(VirtualKeyCode::PageDown),
+            },
+        )
+        .unwrap();
+
+        assert_eq!("PageUp / PageDown", describe_binding(&bindings, &BindingId::Axis("zoom".to_string())));
+    }
+
+    #[test]
+    fn rebinding_an_axis_to_a_button_used_by_an_action_keeps_the_old_axis() {
+        let mut bindings = Bindings::<StringBindings>::new();
+        bindings
+            .insert_axis(
+                "pan_vertical".to_string(),
+                Axis::Emulated {
+                    pos: Button::Key(VirtualKeyCode::Up),
+                    neg: Button::Key(VirtualKeyCode::Down),
+                },
+            )
+            .unwrap();
+        bindings
+            .insert_action_binding("pause".to_string(), vec![Button::Key(VirtualKeyCode::Space)])
+            .unwrap();
+
+        let result = rebind_axis(
+            &mut bindings,
+            "pan_vertical",
+            Axis::Emulated {
+                pos: Button::Key(VirtualKeyCode::W),
+                neg: Button::Key(VirtualKeyCode::Space),
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!("Up / Down", describe_binding(&bindings, &BindingId::Axis("pan_vertical".to_string())));
+    }
+
+    #[test]
+    fn minimap_positions_convert_back_to_the_same_world_point() {
+        let bounds = (-130.0, 420.0, 700.0, -110.0);
+        let point = Point2::new(150.0, 50.0);
+
+        let (x, y) = world_to_minimap(point, (150.0, 220.0), bounds);
+        assert!((x - 150.0 * 280.0 / 550.0).abs() < 0.001);
+        assert!((y - 220.0 * 160.0 / 810.0).abs() < 0.001);
+
+        let back = minimap_to_world((x, y), (150.0, 220.0), bounds);
+        assert!((back - point).magnitude() < 0.001);
+    }
+
+    #[test_case(ContractSort::Payment, vec!["Nassau", "Havana", "Kingston"] ; "highest payment first")]
+    #[test_case(ContractSort::Expiry, vec!["Havana", "Kingston", "Nassau"] ; "soonest expiry first")]
+    #[test_case(ContractSort::Destination, vec!["Havana", "Kingston", "Nassau"] ; "destination alphabetically")]
+    fn port_contracts_are_sorted(sort: ContractSort, expected: Vec<&str>) {
+        let mut world = World::new();
+        let listing = |world: &mut World, payment: u32, expiry: Option<u32>, destination: &str| ContractListing {
+            contract: world.create_entity().build(),
+            payment,
+            expiry: expiry.map(|day| Utc.ymd(1680, 5, day)),
+            destination: destination.to_string(),
+        };
+        let mut listings = vec![
+            listing(&mut world, 200, Some(10), "Kingston"),
+            listing(&mut world, 300, None, "Nassau"),
+            listing(&mut world, 250, Some(3), "Havana"),
+        ];
+
+        sort_listings(&mut listings, sort);
+
+        assert_eq!(
+            expected,
+            listings.iter().map(|listing| listing.destination.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn contract_list_shows_as_many_cards_as_fit() {
+        assert_eq!(2, cards_that_fit(&[100., 100., 100.], 250.));
+        assert_eq!(3, cards_that_fit(&[100., 100., 90.], 300.));
+        assert_eq!(1, cards_that_fit(&[400., 100.], 300.));
+        assert_eq!(0, cards_that_fit(&[], 300.));
+    }
+
+    #[test]
+    fn contract_filter_matches_its_kind() {
+        assert!(ContractFilter::All.matches(&ContractKind::Mail));
+        assert!(ContractFilter::Mail.matches(&ContractKind::Mail));
+        assert!(!ContractFilter::Delivery.matches(&ContractKind::Mail));
+        assert!(ContractFilter::Smuggling.matches(&ContractKind::Smuggling { inspection_chance: 0.5 }));
+        assert_eq!(ContractFilter::All, ContractFilter::Bulk.next());
+    }
+
+    #[test]
+    fn selected_ship_docked_at_the_port_trades_there() {
+        let mut world = World::new();
+        world.register::<Docked>();
+        world.register::<Controllable>();
+        world.register::<Selected>();
+
+        let port = world.create_entity().build();
+        let other_port = world.create_entity().build();
+        world.create_entity().with(Docked { port }).with(Controllable).build();
+        let selected = world
+            .create_entity()
+            .with(Docked { port })
+            .with(Controllable)
+            .with(Selected)
+            .build();
+        world.create_entity().with(Docked { port: other_port }).with(Selected).build();
+
+        world.exec(
+            |(entities, dockeds, controllables, selecteds): (
+                Entities,
+                ReadStorage<Docked>,
+                ReadStorage<Controllable>,
+                ReadStorage<Selected>,
+            )| {
+                assert_eq!(Some(selected), trading_ship(port, &entities, &dockeds, &controllables, &selecteds));
+                assert_eq!(None, trading_ship(other_port, &entities, &dockeds, &controllables, &selecteds));
+            },
+        );
+    }
+
+    #[test_case(None, Some(NotificationCategory::Contracts) ; "all to first category")]
+    #[test_case(Some(NotificationCategory::Cargo), Some(NotificationCategory::Ships) ; "next category")]
+    #[test_case(Some(NotificationCategory::General), None ; "last category back to all")]
+    fn notification_filter_cycles(filter: Option<NotificationCategory>, expected: Option<NotificationCategory>) {
+        assert_eq!(expected, next_filter(filter));
+    }
+
+    #[test]
+    fn notification_log_lists_newest_entries_of_the_category() {
+        let date = Utc.ymd(1700, 1, 1).and_hms(0, 0, 0);
+        let log = NotificationLog {
+            entries: vec![
+                LoggedNotification {
+                    notification: Notification::new(NotificationCategory::Cargo, "Rum loaded".to_string()),
+                    date,
+                },
+                LoggedNotification {
+                    notification: Notification::new(NotificationCategory::Trade, "Swift purchased".to_string()),
+                    date,
+                },
+                LoggedNotification {
+                    notification: Notification::new(NotificationCategory::Cargo, "Sugar loaded".to_string()),
+                    date,
+                },
+            ],
+            shown: None,
+        };
+
+        assert_eq!(vec![2, 1, 0], filtered_entries(&log, None));
+        assert_eq!(vec![2, 0], filtered_entries(&log, Some(NotificationCategory::Cargo)));
+    }
 }
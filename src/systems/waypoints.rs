@@ -0,0 +1,372 @@
+use amethyst::{
+    core::{
+        math::{Point2, Point3},
+        Transform,
+    },
+    ecs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    input::{InputHandler, StringBindings},
+    renderer::{debug_drawing::DebugLines, palette::Srgba, Camera},
+    ui::{Anchor, LineMode, UiText, UiTransform},
+    window::ScreenDimensions,
+};
+use chrono::Duration;
+use std::collections::VecDeque;
+
+use crate::{
+    age_of_sail::{
+        point_mouse_to_world, point_world_to_screen, CameraView, CourseEditing, Date, UiAssets,
+        WORLD_WIDTH,
+    },
+    components::{Controllable, Course, Formation, Selected, Ship},
+    locale::Locale,
+    map::Map,
+    systems::time::IN_GAME_TO_REAL_TIME_SECONDS,
+};
+
+// Waypoints this many pixels from the cursor can be dragged or deleted
+const WAYPOINT_GRAB_PIXELS: f32 = 8.0;
+const WAYPOINT_MARKER_SIZE: f32 = 1.5;
+
+#[derive(Default)]
+pub struct EditWaypointsSystem {
+    select_down: bool,
+    delete_key_down: bool,
+}
+
+impl<'s> System<'s> for EditWaypointsSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Selected>,
+        ReadStorage<'s, Controllable>,
+        WriteStorage<'s, Course>,
+        Read<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, CameraView>,
+        Read<'s, Map>,
+        Write<'s, CourseEditing>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            cameras,
+            locals,
+            selecteds,
+            controllables,
+            mut courses,
+            input,
+            screen_dimensions,
+            camera_view,
+            map,
+            mut editing,
+        ): Self::SystemData,
+    ) {
+        let select_down = input.action_is_down("select").unwrap_or(false);
+        let delete_key_down = input.action_is_down("delete_waypoint").unwrap_or(false);
+        let select_pressed = select_down && !self.select_down;
+        let delete_pressed = delete_key_down && !self.delete_key_down;
+        self.select_down = select_down;
+        self.delete_key_down = delete_key_down;
+
+        let mut mouse_world = None;
+        for (_, camera_local) in (&cameras, &locals).join() {
+            if let Some((mouse_x, mouse_y)) = input.mouse_position() {
+                mouse_world = Some(point_mouse_to_world(
+                    mouse_x,
+                    mouse_y,
+                    &*screen_dimensions,
+                    camera_local.translation(),
+                    camera_view.zoom,
+                ));
+            }
+        }
+
+        let mouse_world = match mouse_world {
+            Some(mouse_world) => mouse_world,
+            None => return,
+        };
+
+        if let Some((e, last_position)) = editing.dragging {
+            let waypoint = courses.get_mut(e).and_then(|course| {
+                let index = closest_waypoint(course, last_position, f32::EPSILON)?;
+                course.waypoints.get_mut(index)
+            });
+
+            editing.dragging = match waypoint {
+                // Waypoints can't be dragged onto land, they stay at the last place at sea instead
+                Some(waypoint) if select_down => {
+                    if !map.on_land(mouse_world) {
+                        *waypoint = mouse_world;
+                    }
+                    Some((e, *waypoint))
+                }
+                _ => None,
+            };
+            return;
+        }
+
+        if !select_pressed && !delete_pressed {
+            return;
+        }
+
+        let grab_radius =
+            WAYPOINT_GRAB_PIXELS * WORLD_WIDTH * camera_view.zoom / screen_dimensions.width();
+        let hovered = (&entities, &selecteds, &controllables, &courses)
+            .join()
+            .filter_map(|(e, _, _, course)| {
+                closest_waypoint(course, mouse_world, grab_radius)
+                    .map(|index| (e, course.waypoints[index]))
+            })
+            .next();
+
+        if let Some((e, waypoint)) = hovered {
+            if delete_pressed {
+                let course = courses.get_mut(e).unwrap();
+                if let Some(index) = closest_waypoint(course, waypoint, f32::EPSILON) {
+                    course.waypoints.remove(index);
+                }
+            } else {
+                editing.dragging = Some((e, waypoint));
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CourseDisplaySystem {
+    // Reused each frame, spare labels are left blank
+    eta_labels: Vec<Entity>,
+}
+
+impl<'s> System<'s> for CourseDisplaySystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Ship>,
+        ReadStorage<'s, Formation>,
+        ReadStorage<'s, Selected>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Course>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, CameraView>,
+        Read<'s, Date>,
+        Read<'s, Locale>,
+        Write<'s, DebugLines>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiTransform>,
+        ReadExpect<'s, UiAssets>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            cameras,
+            locals,
+            ships,
+            formations,
+            selecteds,
+            controllables,
+            courses,
+            screen_dimensions,
+            camera_view,
+            date,
+            locale,
+            mut debug_lines,
+            mut ui_texts,
+            mut ui_transforms,
+            ui_assets,
+        ): Self::SystemData,
+    ) {
+        let camera_position = match (&cameras, &locals).join().next() {
+            Some((_, camera_local)) => *camera_local.translation(),
+            None => return,
+        };
+
+        let mut labels = Vec::new();
+
+        for (e, ship, _, course, local) in (&entities, &ships, &selecteds, &courses, &locals).join()
+        {
+            let color = if controllables.get(e).is_some() {
+                Srgba::new(1.0, 1.0, 1.0, 0.8)
+            } else {
+                Srgba::new(1.0, 0.4, 0.4, 0.8)
+            };
+
+            let location = Point2::new(local.translation().x, local.translation().y);
+            let mut previous = location;
+            for waypoint in &course.waypoints {
+                debug_lines.draw_line(
+                    Point3::new(previous.x, previous.y, 0.5),
+                    Point3::new(waypoint.x, waypoint.y, 0.5),
+                    color,
+                );
+                draw_waypoint_marker(&mut debug_lines, *waypoint, color);
+                previous = *waypoint;
+            }
+
+            let speed = formations
+                .get(e)
+                .map_or(ship.base_speed, |f| ship.base_speed.min(f.speed));
+            for (waypoint, eta) in
+                course
+                    .waypoints
+                    .iter()
+                    .zip(waypoint_etas(location, &course.waypoints, speed))
+            {
+                let arrival = date.current_date_time() + Duration::seconds(eta as i64);
+                labels.push((*waypoint, locale.date_time(arrival)));
+            }
+        }
+
+        for (index, (waypoint, text)) in labels.iter().enumerate() {
+            let (x, y) = point_world_to_screen(
+                *waypoint,
+                &*screen_dimensions,
+                &camera_position,
+                camera_view.zoom,
+            );
+            // Ui positions are measured from the bottom of the screen
+            let (x, y) = (x + 8., screen_dimensions.height() - y);
+
+            if index == self.eta_labels.len() {
+                let label = entities
+                    .build_entity()
+                    .with(
+                        UiText::new(
+                            ui_assets.font.clone(),
+                            "".to_string(),
+                            [1.0, 1.0, 1.0, 1.0],
+                            12.,
+                            LineMode::Single,
+                            Anchor::MiddleLeft,
+                        ),
+                        &mut ui_texts,
+                    )
+                    .with(
+                        UiTransform::new(
+                            "waypoint_eta".to_string(),
+                            Anchor::BottomLeft,
+                            Anchor::MiddleLeft,
+                            x,
+                            y,
+                            1.,
+                            120.,
+                            20.,
+                        )
+                        .into_transparent(),
+                        &mut ui_transforms,
+                    )
+                    .build();
+                self.eta_labels.push(label);
+            }
+
+            let label = self.eta_labels[index];
+            if let Some(ui_text) = ui_texts.get_mut(label) {
+                ui_text.text = text.to_string();
+            }
+            if let Some(ui_transform) = ui_transforms.get_mut(label) {
+                ui_transform.local_x = x;
+                ui_transform.local_y = y;
+            }
+        }
+
+        for label in self.eta_labels.iter().skip(labels.len()) {
+            if let Some(ui_text) = ui_texts.get_mut(*label) {
+                ui_text.text = "".to_string();
+            }
+        }
+    }
+}
+
+fn draw_waypoint_marker(debug_lines: &mut DebugLines, waypoint: Point2<f32>, color: Srgba) {
+    let corners = [
+        Point3::new(waypoint.x + WAYPOINT_MARKER_SIZE, waypoint.y, 0.5),
+        Point3::new(waypoint.x, waypoint.y + WAYPOINT_MARKER_SIZE, 0.5),
+        Point3::new(waypoint.x - WAYPOINT_MARKER_SIZE, waypoint.y, 0.5),
+        Point3::new(waypoint.x, waypoint.y - WAYPOINT_MARKER_SIZE, 0.5),
+    ];
+    for (index, corner) in corners.iter().enumerate() {
+        debug_lines.draw_line(*corner, corners[(index + 1) % corners.len()], color);
+    }
+}
+
+fn closest_waypoint(course: &Course, point: Point2<f32>, radius: f32) -> Option<usize> {
+    course
+        .waypoints
+        .iter()
+        .enumerate()
+        .map(|(index, waypoint)| (index, (waypoint - point).magnitude()))
+        .filter(|(_, distance)| *distance <= radius)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(index, _)| index)
+}
+
+// In game seconds until each waypoint is reached, sailing straight between them at full speed
+fn waypoint_etas(location: Point2<f32>, waypoints: &VecDeque<Point2<f32>>, speed: f32) -> Vec<f64> {
+    let mut previous = location;
+    let mut distance = 0.0;
+    waypoints
+        .iter()
+        .map(|waypoint| {
+            distance += (waypoint - previous).magnitude();
+            previous = *waypoint;
+            (distance / speed * IN_GAME_TO_REAL_TIME_SECONDS) as f64
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn etas_add_up_along_the_course() {
+        let etas = waypoint_etas(
+            Point2::new(0.0, 0.0),
+            &VecDeque::from(vec![Point2::new(30.0, 40.0), Point2::new(30.0, 60.0)]),
+            10.0,
+        );
+
+        assert_eq!(
+            vec![
+                5.0 * IN_GAME_TO_REAL_TIME_SECONDS as f64,
+                7.0 * IN_GAME_TO_REAL_TIME_SECONDS as f64
+            ],
+            etas
+        );
+    }
+
+    #[test]
+    fn closest_waypoint_within_radius_is_found() {
+        let course = Course {
+            waypoints: VecDeque::from(vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(10.0, 0.0),
+                Point2::new(12.0, 0.0),
+            ]),
+        };
+
+        assert_eq!(
+            Some(2),
+            closest_waypoint(&course, Point2::new(11.5, 1.0), 2.0)
+        );
+        assert_eq!(None, closest_waypoint(&course, Point2::new(5.0, 5.0), 2.0));
+    }
+
+    #[test]
+    fn screen_position_of_a_world_point_matches_the_mouse_position() {
+        let screen_dimensions = ScreenDimensions::new(800, 600, 1.0);
+        let camera_position = amethyst::core::math::Vector3::new(150.0, 120.0, 10.0);
+        let point = Point2::new(130.0, 170.0);
+
+        let (x, y) = point_world_to_screen(point, &screen_dimensions, &camera_position, 0.5);
+        let back = point_mouse_to_world(x, y, &screen_dimensions, &camera_position, 0.5);
+
+        assert!((back - point).magnitude() < 0.001);
+    }
+}
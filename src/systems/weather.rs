@@ -0,0 +1,350 @@
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    ops::Range,
+};
+
+use amethyst::{
+    core::{
+        math::{Point2, Point3, Vector2},
+        Named, Transform,
+    },
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
+    renderer::{debug_drawing::DebugLines, palette::Srgba},
+    shrev::EventChannel,
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    age_of_sail::{sea_bounds, Date, SECONDS_IN_DAY},
+    calendar::Calendar,
+    components::{Controllable, Docked, Hull},
+    event::GameEvent,
+    map::Map,
+    weather::{Weather, WeatherCell, WeatherKind},
+};
+
+const SECONDS_IN_HOUR: f64 = 3600.0;
+// Chance of each kind of weather turning up in a day
+const STORMS_PER_DAY: f64 = 0.2;
+const STORM_SEASON_STORMS_PER_DAY: f64 = 1.0;
+const FOG_PER_DAY: f64 = 0.5;
+const CALM_PER_DAY: f64 = 0.3;
+const MAX_WEATHER_CELLS: usize = 12;
+// Hull condition lost for each day spent in a storm
+const STORM_DAMAGE_PER_DAY: f32 = 20.0;
+
+// Drifts weather across the map, clears it when it blows over and brings in more
+#[derive(Default)]
+pub struct WeatherSystem {
+    last_time: Option<f64>,
+}
+
+impl<'s> System<'s> for WeatherSystem {
+    type SystemData = (
+        Read<'s, Date>,
+        Read<'s, Map>,
+        Read<'s, Calendar>,
+        Write<'s, Weather>,
+    );
+
+    fn run(&mut self, (date, map, calendar, mut weather): Self::SystemData) {
+        let elapsed = date.time_elapsed - self.last_time.unwrap_or(date.time_elapsed);
+        self.last_time = Some(date.time_elapsed);
+        if elapsed <= 0.0 {
+            return;
+        }
+
+        let hours = (elapsed / SECONDS_IN_HOUR) as f32;
+        for cell in weather.cells.iter_mut() {
+            cell.centre += cell.velocity * hours;
+        }
+        weather
+            .cells
+            .retain(|cell| cell.expires > date.time_elapsed);
+
+        let bounds = match sea_bounds(&map) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let mut rng = thread_rng();
+        let days = elapsed / SECONDS_IN_DAY;
+        for kind in [WeatherKind::Storm, WeatherKind::Fog, WeatherKind::Calm].iter() {
+            let per_day = match kind {
+                WeatherKind::Storm if calendar.storm_season => STORM_SEASON_STORMS_PER_DAY,
+                WeatherKind::Storm => STORMS_PER_DAY,
+                WeatherKind::Fog => FOG_PER_DAY,
+                WeatherKind::Calm => CALM_PER_DAY,
+            };
+
+            if weather.cells.len() < MAX_WEATHER_CELLS && rng.gen_bool((per_day * days).min(1.0)) {
+                let cell = random_cell(*kind, bounds, date.time_elapsed, &mut rng);
+                weather.cells.push(cell);
+            }
+        }
+    }
+}
+
+fn random_cell<R: Rng>(
+    kind: WeatherKind,
+    (left, right, top, bottom): (f32, f32, f32, f32),
+    now: f64,
+    rng: &mut R,
+) -> WeatherCell {
+    // Radius, drift per hour and days it lasts
+    let (radius, speed, days): (Range<f32>, Range<f32>, Range<f64>) = match kind {
+        WeatherKind::Storm => (30.0..60.0, 2.0..5.0, 2.0..4.0),
+        WeatherKind::Fog => (40.0..90.0, 0.5..2.0, 1.0..2.0),
+        WeatherKind::Calm => (30.0..70.0, 0.5..1.5, 1.0..3.0),
+    };
+    let heading = rng.gen_range(0.0..2.0 * PI);
+
+    WeatherCell {
+        kind,
+        centre: Point2::new(rng.gen_range(left..right), rng.gen_range(bottom..top)),
+        radius: rng.gen_range(radius),
+        velocity: Vector2::new(heading.cos(), heading.sin()) * rng.gen_range(speed),
+        expires: now + rng.gen_range(days) * SECONDS_IN_DAY,
+    }
+}
+
+// Batters ships caught out in storms. Ships in port are sheltered
+#[derive(Default)]
+pub struct StormDamageSystem {
+    last_time: Option<f64>,
+    // Damage short of a whole point of hull condition, carried over to the next frame
+    damage: HashMap<Entity, f32>,
+    in_storm: HashSet<Entity>,
+}
+
+impl<'s> System<'s> for StormDamageSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Named>,
+        ReadStorage<'s, Controllable>,
+        ReadStorage<'s, Docked>,
+        WriteStorage<'s, Hull>,
+        Read<'s, Date>,
+        Read<'s, Weather>,
+        Write<'s, EventChannel<GameEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            locals,
+            names,
+            controllables,
+            dockeds,
+            mut hulls,
+            date,
+            weather,
+            mut game_events,
+        ): Self::SystemData,
+    ) {
+        let elapsed = date.time_elapsed - self.last_time.unwrap_or(date.time_elapsed);
+        self.last_time = Some(date.time_elapsed);
+        let days = (elapsed / SECONDS_IN_DAY) as f32;
+
+        let mut in_storm = HashSet::new();
+
+        for (e, local, hull, _) in (&entities, &locals, &mut hulls, !&dockeds).join() {
+            let location = Point2::new(local.translation().x, local.translation().y);
+            if !weather.in_storm(location) {
+                continue;
+            }
+
+            in_storm.insert(e);
+            if !self.in_storm.contains(&e) && controllables.get(e).is_some() {
+                game_events.single_write(GameEvent::CaughtInStorm { ship: e });
+            }
+
+            let damage = self.damage.entry(e).or_insert(0.0);
+            *damage += STORM_DAMAGE_PER_DAY * days.max(0.0);
+            let whole_points = damage.floor();
+            *damage -= whole_points;
+            hull.condition = hull.condition.saturating_sub(whole_points as u32);
+
+            if hull.condition == 0 {
+                let name = names
+                    .get(e)
+                    .map_or("???".to_string(), |n| n.name.to_string());
+                entities.delete(e).unwrap();
+                game_events.single_write(GameEvent::ShipFoundered { name });
+            }
+        }
+
+        self.damage.retain(|e, _| in_storm.contains(e));
+        self.in_storm = in_storm;
+    }
+}
+
+// Draws the weather on the map
+pub struct WeatherDisplaySystem;
+
+impl<'s> System<'s> for WeatherDisplaySystem {
+    type SystemData = (Read<'s, Weather>, Write<'s, DebugLines>);
+
+    fn run(&mut self, (weather, mut debug_lines): Self::SystemData) {
+        for cell in &weather.cells {
+            let centre = Point3::new(cell.centre.x, cell.centre.y, 0.4);
+            let (color, rings) = match cell.kind {
+                WeatherKind::Storm => (Srgba::new(0.5, 0.2, 0.6, 0.9), 3),
+                WeatherKind::Fog => (Srgba::new(0.8, 0.8, 0.8, 0.5), 2),
+                WeatherKind::Calm => (Srgba::new(0.6, 0.9, 1.0, 0.5), 1),
+            };
+
+            // Extra rings inside the edge so storms stand out from the rest
+            for ring in 0..rings {
+                let radius = cell.radius * (1.0 - ring as f32 * 0.2);
+                debug_lines.draw_circle(centre, radius, 32, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::{core::WithNamed, prelude::*, shrev::ReaderId, Result};
+    use amethyst_test::prelude::*;
+
+    fn storm_at(x: f32, y: f32) -> Weather {
+        Weather {
+            cells: vec![WeatherCell {
+                kind: WeatherKind::Storm,
+                centre: Point2::new(x, y),
+                radius: 20.0,
+                velocity: Vector2::new(0.0, 0.0),
+                expires: f64::MAX,
+            }],
+        }
+    }
+
+    #[test]
+    fn weather_drifts_and_blows_over() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(WeatherSystem::default(), "weather", &[])
+            .with_effect(|world| {
+                world.insert(Weather {
+                    cells: vec![
+                        WeatherCell {
+                            kind: WeatherKind::Fog,
+                            centre: Point2::new(0.0, 0.0),
+                            radius: 20.0,
+                            velocity: Vector2::new(2.0, 0.0),
+                            expires: 2.0 * SECONDS_IN_DAY,
+                        },
+                        WeatherCell {
+                            kind: WeatherKind::Calm,
+                            centre: Point2::new(50.0, 0.0),
+                            radius: 20.0,
+                            velocity: Vector2::new(0.0, 0.0),
+                            expires: 0.5 * SECONDS_IN_DAY,
+                        },
+                    ],
+                });
+            })
+            .with_effect(|world| {
+                world.write_resource::<Date>().time_elapsed = SECONDS_IN_DAY;
+            })
+            .with_assertion(|world| {
+                let weather = world.read_resource::<Weather>();
+                assert_eq!(1, weather.cells.len(), "Cells left");
+                assert_eq!(WeatherKind::Fog, weather.cells[0].kind);
+                assert_eq!(Point2::new(48.0, 0.0), weather.cells[0].centre);
+            })
+            .run()
+    }
+
+    #[test]
+    fn storms_damage_ships_at_sea() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(StormDamageSystem::default(), "storm_damage", &[])
+            .with_effect(|world| {
+                world.insert(storm_at(0.0, 0.0));
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                let at_sea = world
+                    .create_entity()
+                    .with(Transform::default())
+                    .with(Hull { condition: 50 })
+                    .with(Controllable)
+                    .build();
+                let port = world.create_entity().build();
+                let in_port = world
+                    .create_entity()
+                    .with(Transform::default())
+                    .with(Hull { condition: 50 })
+                    .with(Docked { port })
+                    .build();
+
+                world.insert(EffectReturn((at_sea, in_port)));
+            })
+            .with_effect(|world| {
+                world.write_resource::<Date>().time_elapsed = SECONDS_IN_DAY;
+            })
+            .with_assertion(|world| {
+                let (at_sea, in_port) = world.read_resource::<EffectReturn<(Entity, Entity)>>().0;
+
+                let hulls = world.read_storage::<Hull>();
+                assert_eq!(30, hulls.get(at_sea).unwrap().condition, "Ship at sea");
+                assert_eq!(50, hulls.get(in_port).unwrap().condition, "Ship in port");
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(vec![GameEvent::CaughtInStorm { ship: at_sea }], events);
+            })
+            .run()
+    }
+
+    #[test]
+    fn ships_founder_when_hull_gives_out() -> Result<()> {
+        AmethystApplication::blank()
+            .with_system(StormDamageSystem::default(), "storm_damage", &[])
+            .with_effect(|world| {
+                world.insert(storm_at(0.0, 0.0));
+
+                let reader_id = world
+                    .fetch_mut::<EventChannel<GameEvent>>()
+                    .register_reader();
+                world.insert(reader_id);
+
+                let ship = world
+                    .create_entity()
+                    .with(Transform::default())
+                    .with(Hull { condition: 10 })
+                    .named("Endeavour")
+                    .build();
+
+                world.insert(EffectReturn(ship));
+            })
+            .with_effect(|world| {
+                world.write_resource::<Date>().time_elapsed = SECONDS_IN_DAY;
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let ship = world.read_resource::<EffectReturn<Entity>>().0;
+                assert!(!world.entities().is_alive(ship), "Ship foundered");
+
+                let channel = world.fetch_mut::<EventChannel<GameEvent>>();
+                let mut reader_id = world.fetch_mut::<ReaderId<GameEvent>>();
+                let events = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+                assert_eq!(
+                    vec![GameEvent::ShipFoundered {
+                        name: "Endeavour".to_string()
+                    }],
+                    events
+                );
+            })
+            .run()
+    }
+}
@@ -0,0 +1,191 @@
+use amethyst::core::math::{Point2, Vector2};
+use std::f32::consts::PI;
+
+use crate::age_of_sail::distance_to_segment;
+
+// Multipliers on the speed of ships inside a cell
+const STORM_SPEED: f32 = 0.6;
+const CALM_SPEED: f32 = 0.3;
+// Multiplier on how far ships can see inside fog
+const FOG_SIGHT: f32 = 0.4;
+// Each unit sailed through a storm costs as much as this many in open water when plotting a route
+const STORM_ROUTE_COST: f32 = 4.0;
+// Points around a storm offered as a way round it, a little outside its edge
+const STORM_DETOUR_POINTS: usize = 8;
+const STORM_DETOUR_MARGIN: f32 = 1.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeatherKind {
+    Storm,
+    Fog,
+    Calm,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeatherCell {
+    pub kind: WeatherKind,
+    pub centre: Point2<f32>,
+    pub radius: f32,
+    // Distance drifted each game hour
+    pub velocity: Vector2<f32>,
+    // In game seconds since the start
+    pub expires: f64,
+}
+
+impl WeatherCell {
+    pub fn covers(&self, point: Point2<f32>) -> bool {
+        (point - self.centre).magnitude() < self.radius
+    }
+
+    pub fn crosses(&self, from: Point2<f32>, to: Point2<f32>) -> bool {
+        distance_to_segment(self.centre, from, to) < self.radius
+    }
+
+    // Length of the line from one point to another that lies inside the cell
+    fn length_inside(&self, from: Point2<f32>, to: Point2<f32>) -> f32 {
+        let line = to - from;
+        let offset = from - self.centre;
+        let a = line.magnitude_squared();
+        let b = 2.0 * offset.dot(&line);
+        let c = offset.magnitude_squared() - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if a == 0.0 || discriminant <= 0.0 {
+            return 0.0;
+        }
+
+        let entry = ((-b - discriminant.sqrt()) / (2.0 * a)).max(0.0);
+        let exit = ((-b + discriminant.sqrt()) / (2.0 * a)).min(1.0);
+        (exit - entry).max(0.0) * a.sqrt()
+    }
+}
+
+#[derive(Default)]
+pub struct Weather {
+    pub cells: Vec<WeatherCell>,
+}
+
+impl Weather {
+    pub fn speed_multiplier(&self, point: Point2<f32>) -> f32 {
+        self.cells
+            .iter()
+            .filter(|cell| cell.covers(point))
+            .map(|cell| match cell.kind {
+                WeatherKind::Storm => STORM_SPEED,
+                WeatherKind::Calm => CALM_SPEED,
+                WeatherKind::Fog => 1.0,
+            })
+            .product()
+    }
+
+    pub fn sight_multiplier(&self, point: Point2<f32>) -> f32 {
+        if self
+            .cells
+            .iter()
+            .any(|cell| cell.kind == WeatherKind::Fog && cell.covers(point))
+        {
+            FOG_SIGHT
+        } else {
+            1.0
+        }
+    }
+
+    pub fn in_storm(&self, point: Point2<f32>) -> bool {
+        self.storms().any(|cell| cell.covers(point))
+    }
+
+    // Extra cost on top of the distance for sailing from one point to another
+    pub fn route_penalty(&self, from: Point2<f32>, to: Point2<f32>) -> f32 {
+        self.storms()
+            .map(|cell| cell.length_inside(from, to) * (STORM_ROUTE_COST - 1.0))
+            .sum()
+    }
+
+    // Points around each storm between the two points, to route around it by
+    pub fn detour_points(&self, from: Point2<f32>, to: Point2<f32>) -> Vec<Point2<f32>> {
+        self.storms()
+            .filter(|cell| cell.crosses(from, to))
+            .flat_map(|cell| {
+                (0..STORM_DETOUR_POINTS).map(move |i| {
+                    let angle = i as f32 * 2.0 * PI / STORM_DETOUR_POINTS as f32;
+                    cell.centre
+                        + Vector2::new(angle.cos(), angle.sin()) * cell.radius * STORM_DETOUR_MARGIN
+                })
+            })
+            .collect()
+    }
+
+    fn storms(&self) -> impl Iterator<Item = &WeatherCell> {
+        self.cells
+            .iter()
+            .filter(|cell| cell.kind == WeatherKind::Storm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn cell(kind: WeatherKind, x: f32, y: f32, radius: f32) -> WeatherCell {
+        WeatherCell {
+            kind,
+            centre: Point2::new(x, y),
+            radius,
+            velocity: Vector2::new(0.0, 0.0),
+            expires: 0.0,
+        }
+    }
+
+    #[test_case(WeatherKind::Storm, 0.6 ; "storm")]
+    #[test_case(WeatherKind::Calm, 0.3 ; "calm")]
+    #[test_case(WeatherKind::Fog, 1.0 ; "fog")]
+    fn weather_slows_ships_inside_it(kind: WeatherKind, expected: f32) {
+        let weather = Weather {
+            cells: vec![cell(kind, 0.0, 0.0, 10.0)],
+        };
+
+        assert_eq!(expected, weather.speed_multiplier(Point2::new(5.0, 0.0)));
+        assert_eq!(1.0, weather.speed_multiplier(Point2::new(15.0, 0.0)));
+    }
+
+    #[test]
+    fn fog_reduces_sight() {
+        let weather = Weather {
+            cells: vec![cell(WeatherKind::Fog, 0.0, 0.0, 10.0)],
+        };
+
+        assert_eq!(0.4, weather.sight_multiplier(Point2::new(0.0, 5.0)));
+        assert_eq!(1.0, weather.sight_multiplier(Point2::new(0.0, 15.0)));
+    }
+
+    #[test_case(Point2::new(-20.0, 0.0), Point2::new(20.0, 0.0), 60.0 ; "straight through")]
+    #[test_case(Point2::new(0.0, 0.0), Point2::new(20.0, 0.0), 30.0 ; "out from the centre")]
+    #[test_case(Point2::new(-20.0, 20.0), Point2::new(20.0, 20.0), 0.0 ; "passing by")]
+    fn routes_through_storms_cost_more(from: Point2<f32>, to: Point2<f32>, expected: f32) {
+        let weather = Weather {
+            cells: vec![
+                cell(WeatherKind::Storm, 0.0, 0.0, 10.0),
+                cell(WeatherKind::Fog, 0.0, 20.0, 10.0),
+            ],
+        };
+
+        assert!((expected - weather.route_penalty(from, to)).abs() < 0.001);
+    }
+
+    #[test]
+    fn detour_points_are_only_offered_around_storms_in_the_way() {
+        let weather = Weather {
+            cells: vec![
+                cell(WeatherKind::Storm, 0.0, 0.0, 10.0),
+                cell(WeatherKind::Storm, 0.0, 50.0, 10.0),
+            ],
+        };
+
+        let points = weather.detour_points(Point2::new(-20.0, 0.0), Point2::new(20.0, 0.0));
+
+        assert_eq!(8, points.len(), "Detour points");
+        assert!(points
+            .iter()
+            .all(|p| (p - Point2::new(0.0, 0.0)).magnitude() > 10.0));
+    }
+}
@@ -0,0 +1,500 @@
+use amethyst::{
+    core::Parent,
+    ecs::{Entities, Entity, ReadExpect, SystemData, World, WriteStorage},
+    shred::ResourceId,
+    ui::{Anchor, Interactable, LineMode, UiImage, UiText, UiTransform},
+};
+
+use crate::{age_of_sail::UiAssets, components::OwnedBy};
+
+const CARD_WIDTH: f32 = 175.;
+const CARD_PADDING: f32 = 5.;
+pub const CARD_SPACING: f32 = 5.;
+const ROW_HEIGHT: f32 = 20.;
+const BUTTON_WIDTH: f32 = 60.;
+const FONT_SIZE: f32 = 15.;
+
+// Storages needed to build widgets, fetched together so panels don't have to list them all
+#[derive(SystemData)]
+pub struct WidgetStorages<'s> {
+    pub entities: Entities<'s>,
+    pub ui_texts: WriteStorage<'s, UiText>,
+    pub ui_transforms: WriteStorage<'s, UiTransform>,
+    pub ui_images: WriteStorage<'s, UiImage>,
+    pub ui_interactables: WriteStorage<'s, Interactable>,
+    pub parents: WriteStorage<'s, Parent>,
+    pub owned_bys: WriteStorage<'s, OwnedBy>,
+    pub ui_assets: ReadExpect<'s, UiAssets>,
+}
+
+// One row of a card
+#[derive(Clone, Debug, PartialEq)]
+pub enum Widget {
+    Label(String),
+    // Clicks on the button can be traced back to the owner through its OwnedBy
+    Button {
+        id: String,
+        text: String,
+        owner: Entity,
+    },
+    // Coloured text along the whole card that can be clicked, like an entry in a log
+    Line {
+        text: String,
+        color: [f32; 4],
+    },
+}
+
+impl Widget {
+    fn same_kind(&self, other: &Widget) -> bool {
+        match (self, other) {
+            (Widget::Label(_), Widget::Label(_)) => true,
+            (Widget::Button { id, .. }, Widget::Button { id: other_id, .. }) => id == other_id,
+            (Widget::Line { .. }, Widget::Line { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+// Rows stacked top to bottom, framed with the panel texture unless plain
+#[derive(Clone, Debug, PartialEq)]
+pub struct Card {
+    pub rows: Vec<Widget>,
+    pub framed: bool,
+}
+
+impl Card {
+    pub fn framed(rows: Vec<Widget>) -> Self {
+        Card { rows, framed: true }
+    }
+
+    pub fn plain(rows: Vec<Widget>) -> Self {
+        Card {
+            rows,
+            framed: false,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        2. * CARD_PADDING + ROW_HEIGHT * self.rows.len() as f32
+    }
+
+    fn same_layout(&self, other: &Card) -> bool {
+        self.framed == other.framed
+            && self.rows.len() == other.rows.len()
+            && self
+                .rows
+                .iter()
+                .zip(&other.rows)
+                .all(|(row, other_row)| row.same_kind(other_row))
+    }
+}
+
+struct BuiltCard<K> {
+    key: K,
+    card: Card,
+    root: Entity,
+    rows: Vec<Entity>,
+}
+
+// Cards stacked down a container. Cards are matched up by key when updated, so only new cards
+// are built, changed text is set in place and cards that are gone are deleted
+pub struct CardList<K> {
+    id: String,
+    width: f32,
+    cards: Vec<BuiltCard<K>>,
+}
+
+impl<K: PartialEq + Copy> CardList<K> {
+    pub fn new(id: &str) -> Self {
+        CardList::with_width(id, CARD_WIDTH)
+    }
+
+    pub fn with_width(id: &str, width: f32) -> Self {
+        CardList {
+            id: id.to_string(),
+            width,
+            cards: Vec::new(),
+        }
+    }
+
+    // Card and id of a button that was clicked
+    pub fn button_at(&self, e: Entity) -> Option<(K, &str)> {
+        self.cards.iter().find_map(|built| {
+            built
+                .card
+                .rows
+                .iter()
+                .zip(&built.rows)
+                .find_map(|(row, row_entity)| match row {
+                    Widget::Button { id, .. } if *row_entity == e => Some((built.key, id.as_str())),
+                    _ => None,
+                })
+        })
+    }
+
+    // Card with a line that was clicked
+    pub fn card_at(&self, e: Entity) -> Option<K> {
+        self.cards
+            .iter()
+            .find(|built| {
+                built
+                    .card
+                    .rows
+                    .iter()
+                    .zip(&built.rows)
+                    .any(|(row, row_entity)| match row {
+                        Widget::Line { .. } => *row_entity == e,
+                        _ => false,
+                    })
+            })
+            .map(|built| built.key)
+    }
+
+    // Lays the cards out from `top` down and returns where the next thing should go below them
+    pub fn update(
+        &mut self,
+        container: Entity,
+        top: f32,
+        cards: Vec<(K, Card)>,
+        widgets: &mut WidgetStorages,
+    ) -> f32 {
+        let mut previous = std::mem::replace(&mut self.cards, Vec::new());
+        let mut offset = top;
+
+        for (key, card) in cards {
+            let existing = previous
+                .iter()
+                .position(|built| built.key == key)
+                .map(|index| previous.remove(index));
+
+            let built = match existing {
+                Some(mut built) if built.card.same_layout(&card) => {
+                    if built.card != card {
+                        update_card(&mut built, &card, widgets);
+                    }
+                    built
+                }
+                Some(built) => {
+                    delete_card(built, widgets);
+                    build_card(&self.id, self.width, key, card.clone(), container, widgets)
+                }
+                None => build_card(&self.id, self.width, key, card.clone(), container, widgets),
+            };
+
+            if let Some(transform) = widgets.ui_transforms.get_mut(built.root) {
+                transform.local_y = -offset;
+            }
+            offset += card.height() + CARD_SPACING;
+            self.cards.push(built);
+        }
+
+        for built in previous {
+            delete_card(built, widgets);
+        }
+
+        offset
+    }
+}
+
+fn build_card<K>(
+    id: &str,
+    width: f32,
+    key: K,
+    card: Card,
+    container: Entity,
+    widgets: &mut WidgetStorages,
+) -> BuiltCard<K> {
+    let mut root = widgets
+        .entities
+        .build_entity()
+        .with(
+            UiTransform::new(
+                id.to_string(),
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                0.,
+                1.,
+                width,
+                card.height(),
+            ),
+            &mut widgets.ui_transforms,
+        )
+        .with(Parent { entity: container }, &mut widgets.parents);
+    if card.framed {
+        root = root.with(
+            UiImage::NineSlice {
+                x_start: 4,
+                y_start: 4,
+                width: 56,
+                height: 56,
+                left_dist: 4,
+                right_dist: 4,
+                top_dist: 4,
+                bottom_dist: 4,
+                tex: widgets.ui_assets.panel.clone(),
+                texture_dimensions: [64, 64],
+            },
+            &mut widgets.ui_images,
+        );
+    }
+    let root = root.build();
+
+    let rows = card
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| build_row(id, row, index, width, root, widgets))
+        .collect();
+
+    BuiltCard {
+        key,
+        card,
+        root,
+        rows,
+    }
+}
+
+fn build_row(
+    id: &str,
+    row: &Widget,
+    index: usize,
+    card_width: f32,
+    root: Entity,
+    widgets: &mut WidgetStorages,
+) -> Entity {
+    let (text, width, color, align) = match row {
+        Widget::Label(text) => (text, card_width, [1.0, 1.0, 1.0, 1.0], Anchor::Middle),
+        Widget::Button { text, .. } => (text, BUTTON_WIDTH, [1.0, 1.0, 1.0, 1.0], Anchor::Middle),
+        Widget::Line { text, color } => (
+            text,
+            card_width - 2. * CARD_PADDING,
+            *color,
+            Anchor::MiddleLeft,
+        ),
+    };
+    let row_id = match row {
+        Widget::Button { id: button_id, .. } => button_id.to_string(),
+        _ => format!("{}_line", id),
+    };
+
+    let builder = widgets
+        .entities
+        .build_entity()
+        .with(
+            UiText::new(
+                widgets.ui_assets.font.clone(),
+                text.to_string(),
+                color,
+                FONT_SIZE,
+                LineMode::Single,
+                align,
+            ),
+            &mut widgets.ui_texts,
+        )
+        .with(
+            UiTransform::new(
+                row_id,
+                Anchor::TopMiddle,
+                Anchor::TopMiddle,
+                0.,
+                -(CARD_PADDING + ROW_HEIGHT * index as f32),
+                1.,
+                width,
+                ROW_HEIGHT,
+            ),
+            &mut widgets.ui_transforms,
+        )
+        .with(Parent { entity: root }, &mut widgets.parents);
+
+    match row {
+        Widget::Label(_) => builder.build(),
+        Widget::Button { owner, .. } => builder
+            .with(
+                UiImage::NineSlice {
+                    x_start: 6,
+                    y_start: 6,
+                    width: 52,
+                    height: 52,
+                    left_dist: 2,
+                    right_dist: 2,
+                    top_dist: 2,
+                    bottom_dist: 2,
+                    tex: widgets.ui_assets.panel.clone(),
+                    texture_dimensions: [64, 64],
+                },
+                &mut widgets.ui_images,
+            )
+            .with(Interactable, &mut widgets.ui_interactables)
+            .with(OwnedBy { entity: *owner }, &mut widgets.owned_bys)
+            .build(),
+        Widget::Line { .. } => builder
+            .with(Interactable, &mut widgets.ui_interactables)
+            .build(),
+    }
+}
+
+// Only called for cards with the same layout, so every row is already there
+fn update_card<K>(built: &mut BuiltCard<K>, card: &Card, widgets: &mut WidgetStorages) {
+    for ((row, old_row), entity) in card.rows.iter().zip(&built.card.rows).zip(&built.rows) {
+        if row == old_row {
+            continue;
+        }
+
+        let text = match row {
+            Widget::Label(text) => text,
+            Widget::Button { text, owner, .. } => {
+                widgets
+                    .owned_bys
+                    .insert(*entity, OwnedBy { entity: *owner })
+                    .unwrap();
+                text
+            }
+            Widget::Line { text, color } => {
+                if let Some(ui_text) = widgets.ui_texts.get_mut(*entity) {
+                    ui_text.color = *color;
+                }
+                text
+            }
+        };
+        if let Some(ui_text) = widgets.ui_texts.get_mut(*entity) {
+            ui_text.text = text.to_string();
+        }
+    }
+    built.card = card.clone();
+}
+
+fn delete_card<K>(built: BuiltCard<K>, widgets: &mut WidgetStorages) {
+    for row in built.rows {
+        widgets.entities.delete(row).unwrap();
+    }
+    widgets.entities.delete(built.root).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::{
+        assets::{AssetStorage, Loader},
+        input::StringBindings,
+        prelude::*,
+        renderer::{formats::texture::ImageFormat, Texture},
+        ui::{FontAsset, TtfFormat},
+        Result,
+    };
+    use amethyst_test::prelude::*;
+
+    fn label_card(text: &str) -> Card {
+        Card::framed(vec![Widget::Label(text.to_string())])
+    }
+
+    fn built_entities(cards: &CardList<u32>) -> Vec<(Entity, Vec<Entity>)> {
+        cards
+            .cards
+            .iter()
+            .map(|built| (built.root, built.rows.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn card_height_fits_its_rows() {
+        let card = Card::framed(vec![
+            Widget::Label("For: Port Royal".to_string()),
+            Widget::Label("£100".to_string()),
+        ]);
+
+        assert_eq!(50., card.height());
+    }
+
+    #[test]
+    fn cards_with_different_text_share_a_layout() {
+        let card = Card::framed(vec![Widget::Label("£100".to_string())]);
+
+        assert!(card.same_layout(&Card::framed(vec![Widget::Label("£200".to_string())])));
+        assert!(!card.same_layout(&Card::plain(vec![Widget::Label("£100".to_string())])));
+        assert!(!card.same_layout(&Card::framed(vec![
+            Widget::Label("£100".to_string()),
+            Widget::Label("Expires: 1 May 1700".to_string()),
+        ])));
+    }
+
+    #[test]
+    fn updating_cards_only_rebuilds_what_changed() -> Result<()> {
+        AmethystApplication::ui_base::<StringBindings>()
+            .with_effect(|world| {
+                WidgetStorages::setup(world);
+                let ui_assets = {
+                    let loader = world.read_resource::<Loader>();
+                    UiAssets {
+                        font: loader.load(
+                            "font/square.ttf",
+                            TtfFormat,
+                            (),
+                            &world.read_resource::<AssetStorage<FontAsset>>(),
+                        ),
+                        panel: loader.load(
+                            "texture/panel.png",
+                            ImageFormat::default(),
+                            (),
+                            &world.read_resource::<AssetStorage<Texture>>(),
+                        ),
+                    }
+                };
+                world.insert(ui_assets);
+
+                let container = world.create_entity().build();
+                let mut cards = CardList::new("cargo_card");
+                cards.update(
+                    container,
+                    0.,
+                    vec![
+                        (1, label_card("Sugar")),
+                        (2, label_card("Rum")),
+                        (3, label_card("Tea")),
+                    ],
+                    &mut world.system_data(),
+                );
+                let before = built_entities(&cards);
+
+                cards.update(
+                    container,
+                    0.,
+                    vec![(1, label_card("Sugar")), (2, label_card("Rum: 5 tons"))],
+                    &mut world.system_data(),
+                );
+
+                world.insert(EffectReturn((before, built_entities(&cards))));
+            })
+            .with_assertion(|world| {
+                world.maintain();
+                let (before, after) = world
+                    .read_resource::<EffectReturn<(
+                        Vec<(Entity, Vec<Entity>)>,
+                        Vec<(Entity, Vec<Entity>)>,
+                    )>>()
+                    .0
+                    .clone();
+                let ui_texts = world.read_storage::<UiText>();
+                let text_of = |e: Entity| ui_texts.get(e).unwrap().text.clone();
+
+                assert_eq!(2, after.len(), "Cards left");
+                assert_eq!(before[0], after[0], "Kept card");
+                assert_eq!("Sugar", text_of(after[0].1[0]));
+                assert_eq!(before[1], after[1], "Changed card");
+                assert_eq!("Rum: 5 tons", text_of(after[1].1[0]));
+
+                let (removed_root, removed_rows) = &before[2];
+                assert!(
+                    !world.entities().is_alive(*removed_root),
+                    "Removed card deleted"
+                );
+                assert!(
+                    removed_rows
+                        .iter()
+                        .all(|row| !world.entities().is_alive(*row)),
+                    "Removed card's rows deleted"
+                );
+            })
+            .run()
+    }
+}